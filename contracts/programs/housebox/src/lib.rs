@@ -1,6 +1,17 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::stake;
+use anchor_lang::solana_program::sysvar::instructions as ix_sysvar;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_lang::solana_program::sysvar::stake_history;
 use anchor_lang::system_program;
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_spl::token_2022::{self, spl_token_2022, Token2022};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 
 declare_id!("CQ3JPdmZfES8xkUSjBNgzJ3Y1BQqViweL23vkgKmbjDc");
 
@@ -13,6 +24,145 @@ pub const REDEMPTION_DELAY_SECONDS: i64 = 60;
 /// Redemption expiry window in seconds (1 minute after maturity)
 pub const REDEMPTION_EXPIRY_SECONDS: i64 = 60;
 
+/// Furthest ahead `execute_after` can schedule a redemption (30 days)
+pub const MAX_REDEMPTION_SCHEDULE_SECONDS: i64 = 30 * 86400;
+
+/// Delay before an unassisted player withdrawal can execute (72 hours)
+pub const UNASSISTED_WITHDRAWAL_DELAY_SECONDS: i64 = 72 * 3600;
+
+/// Delay before a requested withdrawal address change can be confirmed (24 hours)
+pub const WITHDRAWAL_ADDRESS_CHANGE_DELAY_SECONDS: i64 = 24 * 3600;
+
+/// Public timelock before an authority clawback of a settlement can execute (72 hours)
+pub const CLAWBACK_TIMELOCK_SECONDS: i64 = 72 * 3600;
+
+/// Delay before a recovery-key withdrawal address change can be confirmed (72 hours)
+pub const RECOVERY_DELAY_SECONDS: i64 = 72 * 3600;
+
+/// Public delay before a requested emergency vault evacuation can execute (14 days)
+pub const EVACUATION_TIMELOCK_SECONDS: i64 = 14 * 24 * 3600;
+
+/// Public delay before a requested pool migration can execute (14 days)
+pub const POOL_MIGRATION_TIMELOCK_SECONDS: i64 = 14 * 24 * 3600;
+
+/// Cooling-off period before a player's loosened gaming limits take effect (24 hours)
+pub const PLAYER_LIMIT_COOLDOWN_SECONDS: i64 = 24 * 3600;
+
+/// Length of the day that player deposit and loss limits reset on
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Basis point denominator (10000 = 100%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Fixed-point scale for the lamports-per-vToken exchange rate
+pub const EXCHANGE_RATE_SCALE: u128 = 1_000_000_000;
+
+/// Minimum seconds between vToken interest rate syncs (1 hour)
+pub const RATE_SYNC_MIN_INTERVAL_SECONDS: i64 = 3600;
+
+/// Seconds per year used to annualize the vToken interest rate
+pub const SECONDS_PER_YEAR: i64 = 31_536_000;
+
+/// Fixed-point scale for ln_ratio
+pub const LN_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// ln(2) scaled by LN_SCALE
+pub const LN_2_SCALED: u128 = 693_147_180_559_945_309;
+
+/// Layout version of HouseboxState created by initialize
+pub const POOL_STATE_VERSION: u8 = 1;
+
+/// Field layout of HouseboxState. Fields are only ever appended; bump this
+/// when adding one so migrate_state upgrades existing accounts in place.
+pub const STATE_LAYOUT_VERSION: u16 = 12;
+
+/// Minimum seconds between TWAP crank updates
+pub const TWAP_MIN_INTERVAL_SECONDS: i64 = 60;
+
+/// Number of observations kept in the TWAP ring buffer
+pub const TWAP_OBSERVATIONS: usize = 24;
+
+/// Slots after a coin-flip bet during which it can be revealed (SlotHashes keeps 512)
+pub const COIN_FLIP_REVEAL_WINDOW_SLOTS: u64 = 500;
+
+/// SPL asset slots in a PlayerEscrow (SOL is held in balance)
+pub const MAX_ESCROW_ASSETS: usize = 4;
+
+/// finalize_config flag: lp_percent and the dynamic haircut
+pub const FINALIZE_LP_PERCENT: u32 = 1 << 0;
+
+/// finalize_config flag: rake and its jackpot, insurance, and escrow yield split
+pub const FINALIZE_RAKE: u32 = 1 << 1;
+
+/// finalize_config flag: per-settlement operator fee
+pub const FINALIZE_SETTLEMENT_FEE: u32 = 1 << 2;
+
+/// finalize_config flag: the ConfigParams tunables
+pub const FINALIZE_CONFIG: u32 = 1 << 3;
+
+/// All finalize_config flags
+pub const FINALIZE_ALL: u32 = FINALIZE_LP_PERCENT | FINALIZE_RAKE | FINALIZE_SETTLEMENT_FEE | FINALIZE_CONFIG;
+
+/// Entries kept in the admin action log ring buffer
+pub const ADMIN_LOG_CAPACITY: usize = 32;
+
+/// How long an unconfirmed guardian pause lasts by default (24 hours)
+pub const DEFAULT_GUARDIAN_PAUSE_SECONDS: i64 = 24 * 60 * 60;
+
+/// Longest maintenance window schedule_pause accepts (7 days)
+pub const MAX_MAINTENANCE_WINDOW_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Maximum members of the admin council
+pub const MAX_COUNCIL_MEMBERS: usize = 7;
+
+/// Maximum escrows in one settle_round batch
+pub const MAX_ROUND_SETTLEMENTS: usize = 16;
+
+/// Maximum authorized server keys, including the primary server_pubkey
+pub const MAX_SERVER_KEYS: usize = 5;
+
+/// Game types that can carry their own exposure accounting
+pub const MAX_GAME_TYPES: usize = 8;
+
+/// Slots for server keys beyond the primary
+pub const MAX_EXTRA_SERVER_KEYS: usize = MAX_SERVER_KEYS - 1;
+
+/// Fixed-point scale for the staking reward-per-vToken accumulator
+pub const STAKE_REWARD_SCALE: u128 = 1_000_000_000_000;
+
+/// Maximum sibling hashes in a settlement Merkle proof
+pub const MAX_MERKLE_PROOF_LEN: usize = 32;
+
+/// SPL stake pool program (owner of the stake pools used as LST rate oracles)
+pub mod spl_stake_pool_program {
+    use super::*;
+    declare_id!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+}
+
+/// Metaplex Token Metadata program (vToken name/symbol/URI)
+pub mod token_metadata_program {
+    use super::*;
+    declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+/// Switchboard on-demand program (owner of the randomness accounts consumed
+/// by sessions)
+pub mod switchboard_on_demand_program {
+    use super::*;
+    declare_id!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+}
+
+/// Civic gateway program (owner of the gateway tokens used as player attestations)
+pub mod civic_gateway_program {
+    use super::*;
+    declare_id!("gatem74V238djXdzWnJf94Wo1DcnuGkfijbf3AuBhfs");
+}
+
+/// Token Metadata field limits
+pub const MAX_METADATA_NAME_LEN: usize = 32;
+pub const MAX_METADATA_SYMBOL_LEN: usize = 10;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
 #[program]
 pub mod housebox {
     use super::*;
@@ -24,17 +174,13 @@ pub mod housebox {
         server_pubkey: Pubkey,
         lp_percent: u8,
     ) -> Result<()> {
-        require!(lp_percent > 0 && lp_percent <= 100, HouseboxError::InvalidLpPercent);
-
-        let state = &mut ctx.accounts.housebox_state;
-        state.authority = ctx.accounts.authority.key();
-        state.server_pubkey = server_pubkey;
-        state.pause_authority = ctx.accounts.authority.key();
-        state.vtoken_mint = ctx.accounts.vtoken_mint.key();
-        state.lp_percent = lp_percent;
-        state.paused = false;
-        state.solsum = 0;
-        state.vsum = 0;
+        init_housebox_state(
+            &mut ctx.accounts.housebox_state,
+            ctx.accounts.authority.key(),
+            server_pubkey,
+            ctx.accounts.vtoken_mint.key(),
+            lp_percent,
+        )?;
 
         msg!("Housebox initialized (step 1)");
         msg!("Server pubkey: {}", server_pubkey);
@@ -43,6 +189,81 @@ pub mod housebox {
         Ok(())
     }
 
+    /// Initialize the Housebox program with a Token-2022 vToken mint carrying the
+    /// interest-bearing extension (alternative step 1). Housebox is mint and rate
+    /// authority; sync_vtoken_rate keeps the displayed rate tracking the pool.
+    /// Call initialize_vault after this, passing the Token-2022 program.
+    pub fn initialize_token_2022(
+        ctx: Context<InitializeToken2022>,
+        server_pubkey: Pubkey,
+        lp_percent: u8,
+    ) -> Result<()> {
+        init_housebox_state(
+            &mut ctx.accounts.housebox_state,
+            ctx.accounts.authority.key(),
+            server_pubkey,
+            ctx.accounts.vtoken_mint.key(),
+            lp_percent,
+        )?;
+
+        let state_key = ctx.accounts.housebox_state.key();
+        let mint_key = ctx.accounts.vtoken_mint.key();
+
+        // Create the mint account with room for the interest-bearing extension
+        let space = spl_token_2022::extension::ExtensionType::try_calculate_account_len::<
+            spl_token_2022::state::Mint,
+        >(&[spl_token_2022::extension::ExtensionType::InterestBearingConfig])?;
+        let mint_seeds = &[
+            b"vtoken_mint".as_ref(),
+            &[ctx.bumps.vtoken_mint],
+        ];
+        let mint_signer_seeds = &[&mint_seeds[..]];
+
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.vtoken_mint.to_account_info(),
+                },
+                mint_signer_seeds,
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &token_2022::ID,
+        )?;
+
+        // Extension must be initialized before the mint itself
+        invoke_signed(
+            &spl_token_2022::extension::interest_bearing_mint::instruction::initialize(
+                &token_2022::ID,
+                &mint_key,
+                Some(state_key),
+                0,
+            )?,
+            &[ctx.accounts.vtoken_mint.to_account_info()],
+            &[],
+        )?;
+
+        token_2022::initialize_mint2(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_2022::InitializeMint2 {
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                },
+            ),
+            9,
+            &state_key,
+            None,
+        )?;
+
+        msg!("Housebox initialized with Token-2022 interest-bearing vToken (step 1)");
+        msg!("Server pubkey: {}", server_pubkey);
+        msg!("LP percent: {}%", lp_percent);
+
+        Ok(())
+    }
+
     /// Initialize vault and protocol account (step 2).
     /// Must be called after initialize.
     pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
@@ -57,95 +278,278 @@ pub mod housebox {
 
     /// LP locks SOL in the house, receives vTokens.
     /// Rate-aware minting: vTokens minted proportional to pool share.
-    pub fn lp_lock(ctx: Context<LpLock>, amount_lamports: u64) -> Result<()> {
+    /// Aborts if the LP's vTokens fall below `min_vtokens_out` (slippage floor).
+    /// An optional `referrer` receives lp_referral_bps of the protocol haircut.
+    /// If the LP's deposit PDA is passed, the SOL comes from it instead of the
+    /// LP wallet, so a relayer can pay fees and rent for an LP with no free SOL.
+    pub fn lp_lock(
+        ctx: Context<LpLock>,
+        amount_lamports: u64,
+        min_vtokens_out: Option<u64>,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
         let state = &ctx.accounts.housebox_state;
-        require!(!state.paused, HouseboxError::ProtocolPaused);
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(!state.sunset_mode, HouseboxError::ProtocolSunset);
         require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+        require!(
+            amount_lamports >= state.min_lp_lock_lamports,
+            HouseboxError::LockBelowMinimum
+        );
+        require!(
+            !state.lp_whitelist_enabled || ctx.accounts.lp_whitelist.is_some(),
+            HouseboxError::LpNotWhitelisted
+        );
+        require!(
+            !state.requires_lp_position() || ctx.accounts.lp_position.is_some(),
+            HouseboxError::LpPositionRequired
+        );
+        if let Some(referrer) = referrer {
+            require!(referrer != ctx.accounts.lp.key(), HouseboxError::InvalidReferrer);
+            let stats = ctx.accounts.lp_referrer.as_ref()
+                .ok_or(HouseboxError::InvalidReferrer)?;
+            let referrer_vtoken_account = ctx.accounts.referrer_vtoken_account.as_ref()
+                .ok_or(HouseboxError::InvalidReferrer)?;
+            require!(
+                stats.referrer == referrer && referrer_vtoken_account.owner == referrer,
+                HouseboxError::InvalidReferrer
+            );
+        }
 
-        // Transfer SOL from LP to vault
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.lp.to_account_info(),
-                    to: ctx.accounts.sol_vault.to_account_info(),
-                },
-            ),
-            amount_lamports,
-        )?;
+        // Transfer SOL to vault, from the LP's deposit PDA if given, else the LP wallet
+        if let Some(lp_deposit) = &ctx.accounts.lp_deposit {
+            let lp_key = ctx.accounts.lp.key();
+            let deposit_seeds = &[
+                b"lp_deposit".as_ref(),
+                lp_key.as_ref(),
+                &[ctx.bumps.lp_deposit],
+            ];
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: lp_deposit.to_account_info(),
+                        to: ctx.accounts.sol_vault.to_account_info(),
+                    },
+                    &[&deposit_seeds[..]],
+                ),
+                amount_lamports,
+            )?;
+        } else {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.lp.to_account_info(),
+                        to: ctx.accounts.sol_vault.to_account_info(),
+                    },
+                ),
+                amount_lamports,
+            )?;
+        }
+
+        // Charge the performance fee on pool gains before pricing the lock
+        crystallize_performance_fee(&mut ctx.accounts.housebox_state)?;
 
         // Rate-aware vToken minting
-        let solsum = ctx.accounts.housebox_state.solsum;
-        let vsum = ctx.accounts.housebox_state.vsum;
+        let mut split = compute_lock_split(&ctx.accounts.housebox_state, amount_lamports)?;
 
-        let vtokens_to_mint = if solsum == 0 && vsum == 0 {
-            // Bootstrap: 1:1 ratio (lamports to vTokens)
-            amount_lamports
-        } else {
-            // Proportional: vtokens = amount * vsum / solsum
-            (amount_lamports as u128)
-                .checked_mul(vsum as u128)
+        // Referrer's cut comes out of the protocol haircut
+        let referral_vtokens = if referrer.is_some() {
+            let cut = split.protocol_vtokens
+                .checked_mul(ctx.accounts.housebox_state.lp_referral_bps as u64)
                 .ok_or(HouseboxError::MathOverflow)?
-                .checked_div(solsum as u128)
-                .ok_or(HouseboxError::MathOverflow)? as u64
+                / BPS_DENOMINATOR;
+            split.protocol_vtokens = split.protocol_vtokens.checked_sub(cut)
+                .ok_or(HouseboxError::MathOverflow)?;
+            cut
+        } else {
+            0
         };
 
-        require!(vtokens_to_mint > 0, HouseboxError::AmountTooSmall);
+        if let Some(min_out) = min_vtokens_out {
+            require!(split.lp_vtokens >= min_out, HouseboxError::SlippageExceeded);
+        }
 
-        // Split: LP gets lp_percent, protocol gets the rest
-        let lp_vtokens = vtokens_to_mint
-            .checked_mul(ctx.accounts.housebox_state.lp_percent as u64)
-            .ok_or(HouseboxError::MathOverflow)?
-            .checked_div(100)
-            .ok_or(HouseboxError::MathOverflow)?;
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Protocol share goes to the vesting vault when vesting is enabled
+        let vesting = ctx.accounts.housebox_state.protocol_vesting_period_seconds > 0;
+        let protocol_destination = if vesting {
+            ctx.accounts.protocol_vesting_vault.as_ref()
+                .ok_or(HouseboxError::ProtocolVestingVaultMissing)?
+        } else {
+            &ctx.accounts.protocol_vtoken_account
+        };
+
+        mint_lock_vtokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vtoken_mint,
+            &ctx.accounts.lp_vtoken_account,
+            protocol_destination,
+            &ctx.accounts.housebox_state,
+            signer_seeds,
+            &split,
+        )?;
+
+        // Mint vTokens to referrer
+        if referral_vtokens > 0 {
+            if let Some(referrer_vtoken_account) = &ctx.accounts.referrer_vtoken_account {
+                token_interface::mint_to(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token_interface::MintTo {
+                            mint: ctx.accounts.vtoken_mint.to_account_info(),
+                            to: referrer_vtoken_account.to_account_info(),
+                            authority: ctx.accounts.housebox_state.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    referral_vtokens,
+                )?;
+            }
+        }
+
+        // Referral stats (validated present above when a referrer is given)
+        if let (Some(_), Some(stats)) = (referrer, ctx.accounts.lp_referrer.as_mut()) {
+            stats.referral_count = stats.referral_count.checked_add(1)
+                .ok_or(HouseboxError::MathOverflow)?;
+            stats.total_referred_lamports = stats.total_referred_lamports.checked_add(amount_lamports)
+                .ok_or(HouseboxError::MathOverflow)?;
+            stats.total_referral_vtokens = stats.total_referral_vtokens.checked_add(referral_vtokens)
+                .ok_or(HouseboxError::MathOverflow)?;
+        }
 
-        let protocol_vtokens = vtokens_to_mint.checked_sub(lp_vtokens)
+        // Track the LP's lock time for the early-exit penalty
+        if let Some(position) = ctx.accounts.lp_position.as_mut() {
+            record_lp_position_entry(position, split.lp_vtokens)?;
+        }
+
+        // Update state
+        let state = &mut ctx.accounts.housebox_state;
+        if vesting {
+            accrue_protocol_vesting(state, split.protocol_vtokens, Clock::get()?.unix_timestamp)?;
+        }
+        state.solsum = state.solsum.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.vsum = state.vsum.checked_add(split.vtokens_to_mint)
             .ok_or(HouseboxError::MathOverflow)?;
 
-        // Mint vTokens to LP
+        msg!("LP locked {} lamports, received {} vTokens (LP: {}, Protocol: {}, Referrer: {})", amount_lamports, split.vtokens_to_mint, split.lp_vtokens, split.protocol_vtokens, referral_vtokens);
+        msg!("Solsum: {}, Vsum: {}", state.solsum, state.vsum);
+
+        Ok(())
+    }
+
+    /// LP locks a whitelisted liquid-staking token (e.g. jitoSOL), receives vTokens.
+    /// The LST is valued in lamports at the stake pool's current exchange rate,
+    /// held in the LST vault, and counted in solsum like locked SOL.
+    pub fn lp_lock_lst(ctx: Context<LpLockLst>, amount_lst: u64) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(!state.sunset_mode, HouseboxError::ProtocolSunset);
+        require!(amount_lst > 0, HouseboxError::ZeroAmount);
+        require!(ctx.accounts.lst_config.enabled, HouseboxError::LstNotWhitelisted);
+        require!(
+            !state.lp_whitelist_enabled || ctx.accounts.lp_whitelist.is_some(),
+            HouseboxError::LpNotWhitelisted
+        );
+        require!(
+            !state.requires_lp_position() || ctx.accounts.lp_position.is_some(),
+            HouseboxError::LpPositionRequired
+        );
+
+        let value_lamports = stake_pool_lamports_for(
+            &ctx.accounts.stake_pool,
+            &ctx.accounts.lst_mint.key(),
+            amount_lst,
+        )?;
+        require!(value_lamports > 0, HouseboxError::AmountTooSmall);
+        require!(
+            value_lamports >= state.min_lp_lock_lamports,
+            HouseboxError::LockBelowMinimum
+        );
+
+        // Transfer LST from LP to the LST vault
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.lst_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.lp_lst_account.to_account_info(),
+                    mint: ctx.accounts.lst_mint.to_account_info(),
+                    to: ctx.accounts.lst_vault.to_account_info(),
+                    authority: ctx.accounts.lp.to_account_info(),
+                },
+            ),
+            amount_lst,
+            ctx.accounts.lst_mint.decimals,
+        )?;
+
+        // Bring LST already in the vault to the current rate, then charge the
+        // performance fee on pool gains before pricing the lock
+        remark_lst_value(
+            &mut ctx.accounts.housebox_state,
+            &mut ctx.accounts.lst_config,
+            &ctx.accounts.stake_pool,
+        )?;
+        crystallize_performance_fee(&mut ctx.accounts.housebox_state)?;
+
+        // Rate-aware vToken minting, priced on the SOL value of the LST
+        let split = compute_lock_split(&ctx.accounts.housebox_state, value_lamports)?;
+
         let seeds = &[
             b"housebox_state".as_ref(),
             &[ctx.bumps.housebox_state],
         ];
         let signer_seeds = &[&seeds[..]];
 
-        token::mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::MintTo {
-                    mint: ctx.accounts.vtoken_mint.to_account_info(),
-                    to: ctx.accounts.lp_vtoken_account.to_account_info(),
-                    authority: ctx.accounts.housebox_state.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            lp_vtokens,
+        // Protocol share goes to the vesting vault when vesting is enabled
+        let vesting = ctx.accounts.housebox_state.protocol_vesting_period_seconds > 0;
+        let protocol_destination = if vesting {
+            ctx.accounts.protocol_vesting_vault.as_ref()
+                .ok_or(HouseboxError::ProtocolVestingVaultMissing)?
+        } else {
+            &ctx.accounts.protocol_vtoken_account
+        };
+
+        mint_lock_vtokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vtoken_mint,
+            &ctx.accounts.lp_vtoken_account,
+            protocol_destination,
+            &ctx.accounts.housebox_state,
+            signer_seeds,
+            &split,
         )?;
 
-        // Mint vTokens to protocol
-        if protocol_vtokens > 0 {
-            token::mint_to(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    token::MintTo {
-                        mint: ctx.accounts.vtoken_mint.to_account_info(),
-                        to: ctx.accounts.protocol_vtoken_account.to_account_info(),
-                        authority: ctx.accounts.housebox_state.to_account_info(),
-                    },
-                    signer_seeds,
-                ),
-                protocol_vtokens,
-            )?;
+        // Track the LP's lock time for the early-exit penalty
+        if let Some(position) = ctx.accounts.lp_position.as_mut() {
+            record_lp_position_entry(position, split.lp_vtokens)?;
         }
 
         // Update state
         let state = &mut ctx.accounts.housebox_state;
-        state.solsum = state.solsum.checked_add(amount_lamports)
+        if vesting {
+            accrue_protocol_vesting(state, split.protocol_vtokens, Clock::get()?.unix_timestamp)?;
+        }
+        state.solsum = state.solsum.checked_add(value_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.vsum = state.vsum.checked_add(split.vtokens_to_mint)
             .ok_or(HouseboxError::MathOverflow)?;
-        state.vsum = state.vsum.checked_add(vtokens_to_mint)
+        state.lst_sol_value = state.lst_sol_value.checked_add(value_lamports)
             .ok_or(HouseboxError::MathOverflow)?;
 
-        msg!("LP locked {} lamports, received {} vTokens (LP: {}, Protocol: {})", amount_lamports, vtokens_to_mint, lp_vtokens, protocol_vtokens);
+        let lst_config = &mut ctx.accounts.lst_config;
+        lst_config.total_locked = lst_config.total_locked.checked_add(amount_lst)
+            .ok_or(HouseboxError::MathOverflow)?;
+        lst_config.sol_value = lst_config.sol_value.checked_add(value_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("LP locked {} LST (valued {} lamports), received {} vTokens (LP: {}, Protocol: {})", amount_lst, value_lamports, split.vtokens_to_mint, split.lp_vtokens, split.protocol_vtokens);
         msg!("Solsum: {}, Vsum: {}", state.solsum, state.vsum);
 
         Ok(())
@@ -154,21 +558,33 @@ pub mod housebox {
     /// LP requests redemption of vTokens. Records intent only — vTokens stay
     /// in LP wallet and solsum/vsum are unchanged until execute_redemption.
     /// LP bears pool risk during the 60s delay.
-    pub fn request_redemption(ctx: Context<RequestRedemption>, vtoken_amount: u64) -> Result<()> {
-        let state = &ctx.accounts.housebox_state;
-        require!(!state.paused, HouseboxError::ProtocolPaused);
+    /// `execute_after` optionally schedules execution later than the minimum
+    /// delay, up to MAX_REDEMPTION_SCHEDULE_SECONDS ahead; the claim window
+    /// then counts from that timestamp.
+    pub fn request_redemption(
+        ctx: Context<RequestRedemption>,
+        vtoken_amount: u64,
+        execute_after: Option<i64>,
+    ) -> Result<()> {
         require!(vtoken_amount > 0, HouseboxError::ZeroAmount);
-        require!(state.vsum > 0, HouseboxError::NoLiquidity);
 
-        // Create redemption request (intent only — no token operations)
-        let request = &mut ctx.accounts.redemption_request;
-        request.lp = ctx.accounts.lp.key();
-        request.vtoken_amount = vtoken_amount;
-        request.requested_at = Clock::get()?.unix_timestamp;
-        request.bump = ctx.bumps.redemption_request;
+        record_redemption_request(ctx, vtoken_amount, false, execute_after)?;
 
         msg!("Redemption requested: {} vTokens (deferred burn)", vtoken_amount);
-        msg!("Ready at timestamp: {}", request.requested_at + REDEMPTION_DELAY_SECONDS);
+
+        Ok(())
+    }
+
+    /// LP requests redemption of their entire vToken balance. The amount is
+    /// read from the LP's vToken account at execution time, so balance changes
+    /// during the delay don't leave dust.
+    pub fn request_redemption_max(
+        ctx: Context<RequestRedemption>,
+        execute_after: Option<i64>,
+    ) -> Result<()> {
+        record_redemption_request(ctx, 0, true, execute_after)?;
+
+        msg!("Redemption requested: entire vToken balance (deferred burn)");
 
         Ok(())
     }
@@ -176,22 +592,22 @@ pub mod housebox {
     /// Execute a redemption request after the delay period.
     /// LP must sign (needed for vToken burn authority). Burns vTokens,
     /// computes payout at execution-time ratio, decrements solsum/vsum,
-    /// and transfers SOL to LP.
+    /// and transfers SOL to LP. With streaming configured, the LP's
+    /// redemption stream must be passed and the payout vests linearly instead.
     pub fn execute_redemption(ctx: Context<ExecuteRedemption>) -> Result<()> {
         let request = &ctx.accounts.redemption_request;
 
         // Verify delay has elapsed but claim window hasn't expired
         let now = Clock::get()?.unix_timestamp;
-        require!(
-            now >= request.requested_at + REDEMPTION_DELAY_SECONDS,
-            HouseboxError::RedemptionNotReady
-        );
-        require!(
-            now <= request.requested_at + REDEMPTION_DELAY_SECONDS + REDEMPTION_EXPIRY_SECONDS,
-            HouseboxError::RedemptionExpired
-        );
+        require!(now >= request.ready_at(), HouseboxError::RedemptionNotReady);
+        require!(now <= request.expires_at(), HouseboxError::RedemptionExpired);
 
-        let vtoken_amount = request.vtoken_amount;
+        let vtoken_amount = if request.redeem_all {
+            ctx.accounts.lp_vtoken_account.amount
+        } else {
+            request.vtoken_amount
+        };
+        require!(vtoken_amount > 0, HouseboxError::ZeroAmount);
 
         // Verify LP still has enough vTokens
         require!(
@@ -199,26 +615,41 @@ pub mod housebox {
             HouseboxError::InsufficientVtokens
         );
 
-        // Compute sol_out at execution-time ratio
-        let state = &ctx.accounts.housebox_state;
-        require!(state.vsum > 0, HouseboxError::NoLiquidity);
+        // Charge the performance fee on pool gains before pricing the redemption
+        crystallize_performance_fee(&mut ctx.accounts.housebox_state)?;
 
-        let sol_out = (vtoken_amount as u128)
-            .checked_mul(state.solsum as u128)
-            .ok_or(HouseboxError::MathOverflow)?
-            .checked_div(state.vsum as u128)
-            .ok_or(HouseboxError::MathOverflow)? as u64;
+        // Compute sol_out at execution-time ratio
+        let sol_out = redemption_sol_out(&ctx.accounts.housebox_state, vtoken_amount)?;
+
+        // Early-exit penalty stays in the pool (burned vTokens, solsum kept)
+        let penalty = if ctx.accounts.housebox_state.early_exit_penalty_bps > 0 {
+            let position = ctx.accounts.lp_position.as_ref()
+                .ok_or(HouseboxError::LpPositionRequired)?;
+            early_exit_penalty_lamports(
+                &ctx.accounts.housebox_state,
+                position,
+                vtoken_amount,
+                sol_out,
+                now,
+            )?
+        } else {
+            0
+        };
+        let sol_out = sol_out.checked_sub(penalty)
+            .ok_or(HouseboxError::MathOverflow)?;
 
-        require!(sol_out > 0, HouseboxError::AmountTooSmall);
+        if let Some(position) = ctx.accounts.lp_position.as_mut() {
+            position.vtokens = position.vtokens.saturating_sub(vtoken_amount);
+        }
 
         // Copy vault bump before mutable borrow
         let sol_vault_bump = ctx.accounts.housebox_state.sol_vault_bump;
 
         // Burn vTokens from LP
-        token::burn(
+        token_interface::burn(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                token::Burn {
+                token_interface::Burn {
                     mint: ctx.accounts.vtoken_mint.to_account_info(),
                     from: ctx.accounts.lp_vtoken_account.to_account_info(),
                     authority: ctx.accounts.lp.to_account_info(),
@@ -234,161 +665,228 @@ pub mod housebox {
         state.solsum = state.solsum.checked_sub(sol_out)
             .ok_or(HouseboxError::MathOverflow)?;
 
-        // Transfer SOL from vault to LP (PDA signer)
-        let vault_seeds = &[
-            b"sol_vault".as_ref(),
-            &[sol_vault_bump],
-        ];
-        let vault_signer_seeds = &[&vault_seeds[..]];
-
-        system_program::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.sol_vault.to_account_info(),
-                    to: ctx.accounts.lp.to_account_info(),
-                },
-                vault_signer_seeds,
-            ),
-            sol_out,
-        )?;
+        if state.redemption_stream_seconds > 0 {
+            let stream = ctx.accounts.redemption_stream.as_mut()
+                .ok_or(HouseboxError::RedemptionStreamRequired)?;
+            // Streamed payout: lamports stay in the vault, reserved for the LP
+            accrue_redemption_stream(stream, sol_out, state.redemption_stream_seconds, now)?;
+            state.redemption_streams_reserved = state.redemption_streams_reserved.checked_add(sol_out)
+                .ok_or(HouseboxError::MathOverflow)?;
+            msg!("Payout streamed until timestamp: {}", stream.end);
+        } else {
+            // Transfer SOL from vault to LP (PDA signer)
+            let vault_seeds = &[
+                b"sol_vault".as_ref(),
+                &[sol_vault_bump],
+            ];
+            let vault_signer_seeds = &[&vault_seeds[..]];
+
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.sol_vault.to_account_info(),
+                        to: ctx.accounts.lp.to_account_info(),
+                    },
+                    vault_signer_seeds,
+                ),
+                sol_out,
+            )?;
+        }
 
         // Account will be closed by Anchor's `close = lp` constraint
-        msg!("Redemption executed: {} vTokens burned, {} lamports transferred to LP", vtoken_amount, sol_out);
+        msg!("Redemption executed: {} vTokens burned, {} lamports transferred to LP (early-exit penalty: {} lamports)", vtoken_amount, sol_out, penalty);
         msg!("Solsum: {}, Vsum: {}", state.solsum, state.vsum);
 
         Ok(())
     }
 
-    /// Player deposits SOL to escrow.
-    pub fn player_deposit(ctx: Context<PlayerDeposit>, amount_lamports: u64) -> Result<()> {
-        let state = &ctx.accounts.housebox_state;
-        require!(!state.paused, HouseboxError::ProtocolPaused);
-        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
-
-        // Transfer SOL from player to vault
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.player.to_account_info(),
-                    to: ctx.accounts.sol_vault.to_account_info(),
-                },
-            ),
-            amount_lamports,
-        )?;
-
-        // Update escrow (create if first deposit)
-        let escrow = &mut ctx.accounts.player_escrow;
-        escrow.player = ctx.accounts.player.key();
-        escrow.balance = escrow.balance.checked_add(amount_lamports)
-            .ok_or(HouseboxError::MathOverflow)?;
-        escrow.bump = ctx.bumps.player_escrow;
-
-        // Set verified withdrawal address on first deposit
-        if escrow.verified_withdrawal_address == Pubkey::default() {
-            escrow.verified_withdrawal_address = ctx.accounts.player.key();
-            msg!("Verified withdrawal address set to: {}", ctx.accounts.player.key());
-        }
-
-        // solsum NOT affected — escrow is separate from LP pool
-        msg!("Player deposited {} lamports to escrow", amount_lamports);
-        msg!("Escrow balance: {}", escrow.balance);
-
-        Ok(())
-    }
-
-    /// Settle player session P&L (server-signed).
-    /// No SOL actually moves — it's all in the same vault.
-    /// Just accounting entries between escrow and LP pool.
-    pub fn player_settle(
-        ctx: Context<PlayerSettle>,
-        pnl: i64,
-        session_id: [u8; 32],
+    /// Execute many matured redemption requests in one transaction (permissionless keeper).
+    /// remaining_accounts holds triples of
+    /// [redemption_request (mut), lp (mut), lp_vtoken_account (mut)].
+    /// Each LP must have approved housebox_state as delegate for the requested
+    /// vTokens, which are burned under that delegation. Unavailable while an
+    /// early-exit penalty or redemption streaming is configured. Payouts use the same
+    /// execution-time ratio as execute_redemption; request rent returns to the LP.
+    pub fn execute_redemptions_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteRedemptionsBatch<'info>>,
     ) -> Result<()> {
-        let state = &ctx.accounts.housebox_state;
-        require!(!state.paused, HouseboxError::ProtocolPaused);
+        // Early-exit penalties need each LP's position account; execute those individually
+        require!(
+            !ctx.accounts.housebox_state.requires_lp_position(),
+            HouseboxError::LpPositionRequired
+        );
+        // Streamed payouts need each LP's stream account; execute those individually
+        require!(
+            ctx.accounts.housebox_state.redemption_stream_seconds == 0,
+            HouseboxError::RedemptionStreamRequired
+        );
 
+        let remaining = ctx.remaining_accounts;
         require!(
-            ctx.accounts.server_signer.key() == state.server_pubkey,
-            HouseboxError::InvalidServerSignature
+            !remaining.is_empty() && remaining.len().is_multiple_of(3),
+            HouseboxError::InvalidBatchAccounts
         );
 
-        let escrow = &mut ctx.accounts.player_escrow;
+        // Charge the performance fee once, before any request is priced
+        crystallize_performance_fee(&mut ctx.accounts.housebox_state)?;
 
-        if pnl < 0 {
-            // Player lost
-            let loss = (-pnl) as u64;
-            require!(escrow.balance >= loss, HouseboxError::InsufficientEscrow);
+        let now = Clock::get()?.unix_timestamp;
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[ctx.accounts.housebox_state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
 
-            escrow.balance = escrow.balance.checked_sub(loss)
-                .ok_or(HouseboxError::MathOverflow)?;
+        for chunk in remaining.chunks(3) {
+            let (request_info, lp_info, lp_vtoken_info) = (&chunk[0], &chunk[1], &chunk[2]);
+
+            let request = Account::<RedemptionRequest>::try_from(request_info)?;
+            // Asset pool requests share the account type; only ["redemption", lp] is a SOL request
+            let expected_request = Pubkey::create_program_address(
+                &[b"redemption".as_ref(), request.lp.as_ref(), &[request.bump]],
+                &crate::ID,
+            ).map_err(|_| HouseboxError::InvalidBatchAccounts)?;
+            require!(request_info.key() == expected_request, HouseboxError::InvalidBatchAccounts);
+            require!(now >= request.ready_at(), HouseboxError::RedemptionNotReady);
+            require!(now <= request.expires_at(), HouseboxError::RedemptionExpired);
+            require!(lp_info.key() == request.lp, HouseboxError::Unauthorized);
+
+            let lp_vtoken_account = InterfaceAccount::<TokenAccount>::try_from(lp_vtoken_info)?;
+            require!(
+                lp_vtoken_account.owner == request.lp
+                    && lp_vtoken_account.mint == ctx.accounts.vtoken_mint.key(),
+                HouseboxError::InvalidBatchAccounts
+            );
+
+            let vtoken_amount = if request.redeem_all {
+                lp_vtoken_account.amount
+            } else {
+                request.vtoken_amount
+            };
+            require!(vtoken_amount > 0, HouseboxError::ZeroAmount);
+            require!(
+                lp_vtoken_account.amount >= vtoken_amount,
+                HouseboxError::InsufficientVtokens
+            );
+
+            let sol_out = redemption_sol_out(&ctx.accounts.housebox_state, vtoken_amount)?;
+
+            // Burn vTokens under the LP's delegation to housebox_state
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::Burn {
+                        mint: ctx.accounts.vtoken_mint.to_account_info(),
+                        from: lp_vtoken_info.clone(),
+                        authority: ctx.accounts.housebox_state.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                vtoken_amount,
+            )?;
 
             let state = &mut ctx.accounts.housebox_state;
-            state.solsum = state.solsum.checked_add(loss)
+            state.vsum = state.vsum.checked_sub(vtoken_amount)
                 .ok_or(HouseboxError::MathOverflow)?;
-
-            msg!("Player lost {} lamports", loss);
-        } else if pnl > 0 {
-            // Player won
-            let win = pnl as u64;
-            let state_ref = &ctx.accounts.housebox_state;
-            require!(state_ref.solsum >= win, HouseboxError::HouseInsolvent);
-
-            escrow.balance = escrow.balance.checked_add(win)
+            state.solsum = state.solsum.checked_sub(sol_out)
                 .ok_or(HouseboxError::MathOverflow)?;
 
-            let state = &mut ctx.accounts.housebox_state;
-            state.solsum = state.solsum.checked_sub(win)
-                .ok_or(HouseboxError::MathOverflow)?;
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.sol_vault.to_account_info(),
+                        to: lp_info.clone(),
+                    },
+                    vault_signer_seeds,
+                ),
+                sol_out,
+            )?;
+
+            // Close the request, rent back to the LP
+            request.close(lp_info.clone())?;
 
-            msg!("Player won {} lamports", win);
+            msg!("Redemption executed for {}: {} vTokens burned, {} lamports transferred", lp_info.key(), vtoken_amount, sol_out);
         }
 
-        // Mark session as settled
-        let settled = &mut ctx.accounts.settled_session;
-        settled.session_id = session_id;
-        settled.player = ctx.accounts.player.key();
-        settled.settled_at = Clock::get()?.unix_timestamp;
+        let state = &ctx.accounts.housebox_state;
+        msg!("Batch executed {} redemptions", remaining.len() / 3);
+        msg!("Solsum: {}, Vsum: {}", state.solsum, state.vsum);
 
-        msg!("Session settled. Escrow balance: {}", escrow.balance);
-        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+        Ok(())
+    }
+
+    /// Open the caller's LP position, used to track their lock time for the
+    /// early-exit penalty.
+    pub fn open_lp_position(ctx: Context<OpenLpPosition>) -> Result<()> {
+        let position = &mut ctx.accounts.lp_position;
+        position.lp = ctx.accounts.lp.key();
+        position.vtokens = 0;
+        position.high_water_rate = 0;
+        position.last_locked_at = 0;
+        position.bump = ctx.bumps.lp_position;
+
+        msg!("LP position opened for {}", position.lp);
 
         Ok(())
     }
 
-    /// Player withdraws SOL from escrow (server-authorized).
-    /// Withdrawals require server co-signature to prevent unauthorized withdrawals
-    /// while a player has an active game session.
-    pub fn player_withdraw(ctx: Context<PlayerWithdraw>, amount_lamports: u64) -> Result<()> {
-        // Verify server signature matches configured server pubkey
-        let state = &ctx.accounts.housebox_state;
-        require!(
-            ctx.accounts.server_signer.key() == state.server_pubkey,
-            HouseboxError::InvalidServerSignature
-        );
+    /// Fold vTokens the LP received by transfer into their position so they
+    /// age like locked ones. Restarts the position's early-exit clock.
+    pub fn sync_lp_position(ctx: Context<SyncLpPosition>) -> Result<()> {
+        let balance = ctx.accounts.lp_vtoken_account.amount;
+        let position = &mut ctx.accounts.lp_position;
+        require!(balance > position.vtokens, HouseboxError::ZeroAmount);
 
-        // Note: Withdrawals always allowed, even when paused (after server approval)
-        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+        let added = balance - position.vtokens;
+        position.vtokens = balance;
+        position.last_locked_at = Clock::get()?.unix_timestamp;
 
-        let escrow = &mut ctx.accounts.player_escrow;
-        require!(escrow.balance >= amount_lamports, HouseboxError::InsufficientEscrow);
+        msg!("LP position synced: {} vTokens added, {} tracked", added, balance);
 
-        // Verify withdrawal goes to the verified withdrawal address
-        require!(
-            escrow.verified_withdrawal_address == ctx.accounts.player.key(),
-            HouseboxError::WithdrawalAddressMismatch
-        );
+        Ok(())
+    }
 
-        // Update escrow
-        escrow.balance = escrow.balance.checked_sub(amount_lamports)
+    /// Open the caller's redemption stream, which execute_redemption pays into
+    /// when streaming is configured.
+    pub fn open_redemption_stream(ctx: Context<OpenRedemptionStream>) -> Result<()> {
+        let stream = &mut ctx.accounts.redemption_stream;
+        stream.lp = ctx.accounts.lp.key();
+        stream.balance = 0;
+        stream.unlocked = 0;
+        stream.checkpoint = 0;
+        stream.end = 0;
+        stream.bump = ctx.bumps.redemption_stream;
+
+        msg!("Redemption stream opened for {}", stream.lp);
+
+        Ok(())
+    }
+
+    /// Claim the vested part of the caller's streamed redemption payout.
+    pub fn claim_redemption_stream(ctx: Context<ClaimRedemptionStream>) -> Result<()> {
+        let stream = &mut ctx.accounts.redemption_stream;
+        checkpoint_redemption_stream(stream, Clock::get()?.unix_timestamp)?;
+
+        let amount = stream.unlocked;
+        require!(amount > 0, HouseboxError::ZeroAmount);
+        stream.unlocked = 0;
+        stream.balance = stream.balance.checked_sub(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.redemption_streams_reserved = state.redemption_streams_reserved.checked_sub(amount)
             .ok_or(HouseboxError::MathOverflow)?;
 
-        // Transfer SOL from vault to player (PDA signer)
-        let sol_vault_bump = ctx.accounts.housebox_state.sol_vault_bump;
         let vault_seeds = &[
             b"sol_vault".as_ref(),
-            &[sol_vault_bump],
+            &[state.sol_vault_bump],
         ];
         let vault_signer_seeds = &[&vault_seeds[..]];
 
@@ -397,581 +895,13219 @@ pub mod housebox {
                 ctx.accounts.system_program.to_account_info(),
                 system_program::Transfer {
                     from: ctx.accounts.sol_vault.to_account_info(),
-                    to: ctx.accounts.player.to_account_info(),
+                    to: ctx.accounts.lp.to_account_info(),
                 },
                 vault_signer_seeds,
             ),
-            amount_lamports,
+            amount,
         )?;
 
-        msg!("Player withdrew {} lamports from escrow", amount_lamports);
-        msg!("Remaining escrow balance: {}", escrow.balance);
+        msg!("Claimed {} streamed redemption lamports", amount);
+        msg!("Still streaming: {}", ctx.accounts.redemption_stream.balance);
 
         Ok(())
     }
 
-    /// Pause the protocol (admin only).
-    pub fn pause(ctx: Context<AdminAction>) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.housebox_state.pause_authority,
-            HouseboxError::Unauthorized
-        );
+    /// Withdraw SOL from the LP's pre-funded deposit PDA back to the LP wallet.
+    /// The deposit PDA is a plain system account anyone can fund; lp_lock draws
+    /// from it when passed.
+    pub fn withdraw_lp_deposit(ctx: Context<WithdrawLpDeposit>, amount_lamports: u64) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
 
-        let state = &mut ctx.accounts.housebox_state;
-        state.paused = true;
+        let lp_key = ctx.accounts.lp.key();
+        let deposit_seeds = &[
+            b"lp_deposit".as_ref(),
+            lp_key.as_ref(),
+            &[ctx.bumps.lp_deposit],
+        ];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.lp_deposit.to_account_info(),
+                    to: ctx.accounts.lp.to_account_info(),
+                },
+                &[&deposit_seeds[..]],
+            ),
+            amount_lamports,
+        )?;
 
-        msg!("Protocol PAUSED");
+        msg!("Withdrew {} lamports from LP deposit account", amount_lamports);
 
         Ok(())
     }
 
-    /// Unpause the protocol (admin only).
-    pub fn unpause(ctx: Context<AdminAction>) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.housebox_state.pause_authority,
-            HouseboxError::Unauthorized
-        );
-
-        let state = &mut ctx.accounts.housebox_state;
-        state.paused = false;
+    /// Register the caller as an LP referrer (creates their stats PDA).
+    pub fn register_lp_referrer(ctx: Context<RegisterLpReferrer>) -> Result<()> {
+        let stats = &mut ctx.accounts.lp_referrer;
+        stats.referrer = ctx.accounts.referrer.key();
+        stats.referral_count = 0;
+        stats.total_referred_lamports = 0;
+        stats.total_referral_vtokens = 0;
+        stats.bump = ctx.bumps.lp_referrer;
 
-        msg!("Protocol UNPAUSED");
+        msg!("LP referrer registered: {}", stats.referrer);
 
         Ok(())
     }
 
-    /// Update server signing pubkey (authority only).
-    pub fn update_server_pubkey(
-        ctx: Context<AdminAction>,
-        new_server_pubkey: Pubkey,
-    ) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
-            HouseboxError::Unauthorized
-        );
+    /// Extend a pending redemption's claim window once by another expiry period.
+    /// Must be called before the window lapses. If a fee is configured, the LP
+    /// pays it into the vault and it accrues to the protocol treasury.
+    pub fn extend_redemption(ctx: Context<ExtendRedemption>) -> Result<()> {
+        let request = &ctx.accounts.redemption_request;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!request.extended, HouseboxError::RedemptionAlreadyExtended);
+        require!(now <= request.expires_at(), HouseboxError::RedemptionExpired);
+
+        let fee = ctx.accounts.housebox_state.redemption_extension_fee_lamports;
+        if fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.lp.to_account_info(),
+                        to: ctx.accounts.sol_vault.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
 
-        let state = &mut ctx.accounts.housebox_state;
-        let old_pubkey = state.server_pubkey;
-        state.server_pubkey = new_server_pubkey;
+            let state = &mut ctx.accounts.housebox_state;
+            state.treasury_balance = state.treasury_balance.checked_add(fee)
+                .ok_or(HouseboxError::MathOverflow)?;
+        }
+
+        let request = &mut ctx.accounts.redemption_request;
+        request.extended = true;
 
-        msg!("Server pubkey updated");
-        msg!("Old: {}", old_pubkey);
-        msg!("New: {}", new_server_pubkey);
+        msg!("Redemption extended (fee: {} lamports)", fee);
+        msg!("Expires at timestamp: {}", request.expires_at());
 
         Ok(())
     }
 
-    /// Close an expired redemption request PDA to reclaim rent.
-    /// Permissionless — anyone can call. Rent returns to the LP.
-    pub fn close_expired_redemption(ctx: Context<CloseExpiredRedemption>) -> Result<()> {
-        let request = &ctx.accounts.redemption_request;
-        let now = Clock::get()?.unix_timestamp;
+    /// Create the vToken staking vault (authority only).
+    pub fn initialize_staking(ctx: Context<InitializeStaking>) -> Result<()> {
+        ctx.accounts.log_admin_action("initialize_staking")?;
+
         require!(
-            now > request.requested_at + REDEMPTION_DELAY_SECONDS + REDEMPTION_EXPIRY_SECONDS,
-            HouseboxError::RedemptionNotExpired
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
         );
-        msg!("Closed expired redemption request, rent returned to LP");
+
+        msg!("Staking vault initialized: {}", ctx.accounts.stake_vault.key());
+
         Ok(())
     }
 
-    /// Close a settled session PDA to reclaim rent.
-    /// Only the server can call this, and only after the session is at least 1 hour old.
-    pub fn close_settled_session(
-        ctx: Context<CloseSettledSession>,
-        _session_id: [u8; 32],
-    ) -> Result<()> {
-        let now = Clock::get()?.unix_timestamp;
-        let age = now - ctx.accounts.settled_session.settled_at;
-        require!(age >= 3600, HouseboxError::SettlementTooRecent);
-        msg!("Closed settled session, rent reclaimed");
+    /// Stake vTokens to earn a boosted share of house rake.
+    /// Staked vTokens stay in vsum (still pool shares) but must be unstaked
+    /// before they can be redeemed.
+    pub fn stake_vtokens(ctx: Context<StakeVtokens>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.housebox_state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(amount > 0, HouseboxError::ZeroAmount);
+
+        let acc = ctx.accounts.housebox_state.stake_reward_per_token;
+        let position = &mut ctx.accounts.stake_position;
+        position.owner = ctx.accounts.owner.key();
+        position.bump = ctx.bumps.stake_position;
+        accrue_stake_rewards(position, acc)?;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.owner_vtoken_account.to_account_info(),
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.vtoken_mint.decimals,
+        )?;
+
+        let position = &mut ctx.accounts.stake_position;
+        position.staked_amount = position.staked_amount.checked_add(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.staked_vtokens = state.staked_vtokens.checked_add(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Staked {} vTokens (position: {})", amount, position.staked_amount);
+        msg!("Total staked: {}", state.staked_vtokens);
+
         Ok(())
     }
 
-    /// Withdraw vTokens from the protocol account (authority only).
-    /// Used to transfer protocol-held vTokens to a wallet for redemption.
-    pub fn withdraw_protocol_vtokens(
-        ctx: Context<WithdrawProtocolVtokens>,
-        amount: u64,
-    ) -> Result<()> {
-        require!(
-            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
-            HouseboxError::Unauthorized
-        );
+    /// Unstake vTokens back to the owner's vToken account. Accrued rewards are kept.
+    pub fn unstake_vtokens(ctx: Context<UnstakeVtokens>, amount: u64) -> Result<()> {
         require!(amount > 0, HouseboxError::ZeroAmount);
 
+        let acc = ctx.accounts.housebox_state.stake_reward_per_token;
+        let position = &mut ctx.accounts.stake_position;
+        accrue_stake_rewards(position, acc)?;
+        require!(position.staked_amount >= amount, HouseboxError::InsufficientStake);
+
         let seeds = &[
             b"housebox_state".as_ref(),
             &[ctx.bumps.housebox_state],
         ];
         let signer_seeds = &[&seeds[..]];
 
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
-                    from: ctx.accounts.protocol_vtoken_account.to_account_info(),
-                    to: ctx.accounts.destination_vtoken_account.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                    to: ctx.accounts.owner_vtoken_account.to_account_info(),
                     authority: ctx.accounts.housebox_state.to_account_info(),
                 },
                 signer_seeds,
             ),
             amount,
+            ctx.accounts.vtoken_mint.decimals,
         )?;
 
-        msg!("Withdrew {} vTokens from protocol account", amount);
+        let position = &mut ctx.accounts.stake_position;
+        position.staked_amount = position.staked_amount.checked_sub(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.staked_vtokens = state.staked_vtokens.checked_sub(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Unstaked {} vTokens (position: {})", amount, position.staked_amount);
+        msg!("Total staked: {}", state.staked_vtokens);
 
         Ok(())
     }
-}
 
-// ============================================
-// ACCOUNTS
-// ============================================
+    /// Claim accrued staking rewards (lamports) from the vault.
+    pub fn claim_stake_rewards(ctx: Context<ClaimStakeRewards>) -> Result<()> {
+        let acc = ctx.accounts.housebox_state.stake_reward_per_token;
+        let position = &mut ctx.accounts.stake_position;
+        accrue_stake_rewards(position, acc)?;
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        let reward = position.pending_rewards;
+        require!(reward > 0, HouseboxError::ZeroAmount);
+        position.pending_rewards = 0;
 
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + HouseboxState::INIT_SPACE,
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Box<Account<'info, HouseboxState>>,
+        let state = &mut ctx.accounts.housebox_state;
+        state.stake_rewards_reserved = state.stake_rewards_reserved.checked_sub(reward)
+            .ok_or(HouseboxError::MathOverflow)?;
 
-    /// vToken mint (LP share token) - Housebox is mint authority (9 decimals, matching SOL)
-    #[account(
-        init,
-        payer = authority,
-        mint::decimals = 9,
-        mint::authority = housebox_state,
-        seeds = [b"vtoken_mint"],
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.owner.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            reward,
+        )?;
+
+        msg!("Claimed {} lamports of staking rewards", reward);
+
+        Ok(())
+    }
+
+    /// Fund staking rewards with lamports, distributed pro rata to staked vTokens.
+    /// Rewards are held in the vault outside solsum until claimed.
+    pub fn fund_stake_rewards(ctx: Context<FundStakeRewards>, amount_lamports: u64) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.sol_vault.to_account_info(),
+                },
+            ),
+            amount_lamports,
+        )?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        distribute_stake_rewards(state, amount_lamports)?;
+
+        msg!("Funded {} lamports of staking rewards", amount_lamports);
+
+        Ok(())
+    }
+
+    /// Top up the insurance fund that absorbs player wins before the LP pool.
+    /// Held in the vault outside solsum.
+    pub fn fund_insurance(ctx: Context<FundInsurance>, amount_lamports: u64) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.sol_vault.to_account_info(),
+                },
+            ),
+            amount_lamports,
+        )?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.insurance_balance = state.insurance_balance.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Funded {} lamports of insurance", amount_lamports);
+        msg!("Insurance balance: {}", state.insurance_balance);
+
+        Ok(())
+    }
+
+    /// Player deposits SOL to escrow.
+    /// An optional `referrer` is recorded on the first deposit and earns
+    /// player_referral_bps of the rake it settles, paid by pay_player_referral.
+    /// An optional `memo` tags the deposit's source (campaign, exchange,
+    /// payment processor) in a DepositMemo event. An optional `affiliate`
+    /// campaign code is recorded on the first deposit and carried in the
+    /// player's PlayerSettled and PlayerWithdrew events for revenue share.
+    /// While a deposit-match promotion is running, passing the vToken mint,
+    /// protocol vToken account, and token program credits the match as bonus
+    /// balance.
+    pub fn player_deposit(
+        ctx: Context<PlayerDeposit>,
+        amount_lamports: u64,
+        referrer: Option<Pubkey>,
+        memo: Option<[u8; 32]>,
+        affiliate: Option<[u8; 16]>,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(!state.sunset_mode, HouseboxError::ProtocolSunset);
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        verify_deposit_attestation(
+            state,
+            &ctx.accounts.player.key(),
+            amount_lamports,
+            &ctx.accounts.attestation,
+            now,
+        )?;
+        record_player_deposit(&mut ctx.accounts.player_escrow, amount_lamports, now)?;
+
+        // Transfer SOL from player to vault
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.player.to_account_info(),
+                    to: ctx.accounts.sol_vault.to_account_info(),
+                },
+            ),
+            amount_lamports,
+        )?;
+
+        // Update escrow (create if first deposit)
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.player = ctx.accounts.player.key();
+        escrow.balance = escrow.balance.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        escrow.bump = ctx.bumps.player_escrow;
+
+        // Set verified withdrawal address on first deposit
+        if escrow.verified_withdrawal_address == Pubkey::default() {
+            escrow.verified_withdrawal_address = ctx.accounts.player.key();
+            msg!("Verified withdrawal address set to: {}", ctx.accounts.player.key());
+
+            if let Some(referrer) = referrer {
+                require!(referrer != ctx.accounts.player.key(), HouseboxError::InvalidReferrer);
+                escrow.referrer = referrer;
+                msg!("Referrer recorded: {}", referrer);
+            }
+
+            if let Some(affiliate) = affiliate {
+                escrow.affiliate = affiliate;
+                msg!("Affiliate code recorded");
+            }
+        }
+
+        if let Some(memo) = memo {
+            emit!(DepositMemo {
+                player: ctx.accounts.player.key(),
+                amount: amount_lamports,
+                memo,
+                timestamp: now,
+            });
+        }
+
+        // Deposit-match promotion, funded by redeeming protocol vTokens
+        if let (Some(vtoken_mint), Some(protocol_vtoken_account), Some(token_program)) = (
+            &ctx.accounts.vtoken_mint,
+            &ctx.accounts.protocol_vtoken_account,
+            &ctx.accounts.token_program,
+        ) {
+            if let Some((vtoken_amount, bonus)) = deposit_match_redemption(
+                &ctx.accounts.housebox_state,
+                amount_lamports,
+                protocol_vtoken_account.amount,
+            )? {
+                let seeds = &[
+                    b"housebox_state".as_ref(),
+                    &[ctx.bumps.housebox_state],
+                ];
+                let signer_seeds = &[&seeds[..]];
+
+                token_interface::burn(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        token_interface::Burn {
+                            mint: vtoken_mint.to_account_info(),
+                            from: protocol_vtoken_account.to_account_info(),
+                            authority: ctx.accounts.housebox_state.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    vtoken_amount,
+                )?;
+
+                // The redeemed SOL stays in the vault, moving from the pool to the escrow
+                let state = &mut ctx.accounts.housebox_state;
+                state.vsum = state.vsum.checked_sub(vtoken_amount)
+                    .ok_or(HouseboxError::MathOverflow)?;
+                state.solsum = state.solsum.checked_sub(bonus)
+                    .ok_or(HouseboxError::MathOverflow)?;
+                state.deposit_match_budget_lamports = state.deposit_match_budget_lamports
+                    .checked_sub(bonus)
+                    .ok_or(HouseboxError::MathOverflow)?;
+
+                escrow.balance = escrow.balance.checked_add(bonus)
+                    .ok_or(HouseboxError::MathOverflow)?;
+                escrow.bonus_balance = escrow.bonus_balance.checked_add(bonus)
+                    .ok_or(HouseboxError::MathOverflow)?;
+                let wagering = bonus.checked_mul(state.deposit_match_wagering_multiplier as u64)
+                    .ok_or(HouseboxError::MathOverflow)?;
+                escrow.wagering_required = escrow.wagering_required.checked_add(wagering)
+                    .ok_or(HouseboxError::MathOverflow)?;
+
+                msg!("Deposit matched: {} lamports bonus ({} protocol vTokens redeemed)", bonus, vtoken_amount);
+                msg!("Match budget remaining: {}", state.deposit_match_budget_lamports);
+            }
+        }
+
+        // solsum NOT affected — escrow is separate from LP pool
+        msg!("Player deposited {} lamports to escrow", amount_lamports);
+        msg!("Escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Player deposit where the operator's relayer is the transaction fee payer
+    /// and pays the escrow rent, so a new player only needs the deposit
+    /// amount itself. The player still signs the transfer of their SOL.
+    pub fn player_deposit_relayed(
+        ctx: Context<PlayerDepositRelayed>,
+        amount_lamports: u64,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(!state.sunset_mode, HouseboxError::ProtocolSunset);
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        verify_deposit_attestation(
+            state,
+            &ctx.accounts.player.key(),
+            amount_lamports,
+            &ctx.accounts.attestation,
+            now,
+        )?;
+        record_player_deposit(&mut ctx.accounts.player_escrow, amount_lamports, now)?;
+
+        // Transfer SOL from player to vault
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.player.to_account_info(),
+                    to: ctx.accounts.sol_vault.to_account_info(),
+                },
+            ),
+            amount_lamports,
+        )?;
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.player = ctx.accounts.player.key();
+        escrow.balance = escrow.balance.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        escrow.bump = ctx.bumps.player_escrow;
+
+        if escrow.verified_withdrawal_address == Pubkey::default() {
+            escrow.verified_withdrawal_address = ctx.accounts.player.key();
+            msg!("Verified withdrawal address set to: {}", ctx.accounts.player.key());
+
+            if let Some(referrer) = referrer {
+                require!(referrer != ctx.accounts.player.key(), HouseboxError::InvalidReferrer);
+                escrow.referrer = referrer;
+                msg!("Referrer recorded: {}", referrer);
+            }
+        }
+
+        msg!("Player deposited {} lamports to escrow (relayed by {})",
+            amount_lamports, ctx.accounts.relayer.key());
+        msg!("Escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Credit a player's escrow with lamports paid by another signer. This is
+    /// the CPI entry point for bridges (e.g. the CHIPS lockbox burning a
+    /// player's CHIPS and funding their escrow from its PDA in the same
+    /// transaction).
+    pub fn player_deposit_for(ctx: Context<PlayerDepositFor>, amount_lamports: u64) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(!state.sunset_mode, HouseboxError::ProtocolSunset);
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        verify_deposit_attestation(
+            state,
+            &ctx.accounts.player.key(),
+            amount_lamports,
+            &ctx.accounts.attestation,
+            now,
+        )?;
+        record_player_deposit(&mut ctx.accounts.player_escrow, amount_lamports, now)?;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.sol_vault.to_account_info(),
+                },
+            ),
+            amount_lamports,
+        )?;
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.player = ctx.accounts.player.key();
+        escrow.balance = escrow.balance.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        escrow.bump = ctx.bumps.player_escrow;
+
+        // The verified address is always the player, never the funder
+        if escrow.verified_withdrawal_address == Pubkey::default() {
+            escrow.verified_withdrawal_address = ctx.accounts.player.key();
+            msg!("Verified withdrawal address set to: {}", ctx.accounts.player.key());
+        }
+
+        msg!("{} deposited {} lamports to escrow of {}",
+            ctx.accounts.funder.key(), amount_lamports, ctx.accounts.player.key());
+        msg!("Escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Set the player's own daily deposit and loss limits (0 = no limit).
+    /// Tighter limits apply immediately; looser ones only after
+    /// PLAYER_LIMIT_COOLDOWN_SECONDS.
+    pub fn set_player_limits(
+        ctx: Context<PlayerEscrowAction>,
+        daily_deposit_limit: u64,
+        daily_loss_limit: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let escrow = &mut ctx.accounts.player_escrow;
+        roll_player_limits(escrow, now);
+
+        // A new request replaces any pending loosening
+        escrow.pending_limits_at = 0;
+
+        let loosens = limit_is_looser(daily_deposit_limit, escrow.daily_deposit_limit)
+            || limit_is_looser(daily_loss_limit, escrow.daily_loss_limit);
+
+        if !limit_is_looser(daily_deposit_limit, escrow.daily_deposit_limit) {
+            escrow.daily_deposit_limit = daily_deposit_limit;
+        }
+        if !limit_is_looser(daily_loss_limit, escrow.daily_loss_limit) {
+            escrow.daily_loss_limit = daily_loss_limit;
+        }
+
+        if loosens {
+            escrow.pending_deposit_limit = daily_deposit_limit;
+            escrow.pending_loss_limit = daily_loss_limit;
+            escrow.pending_limits_at = now + PLAYER_LIMIT_COOLDOWN_SECONDS;
+            msg!("Looser limits take effect at timestamp: {}", escrow.pending_limits_at);
+        }
+
+        msg!("Daily deposit limit: {} lamports", escrow.daily_deposit_limit);
+        msg!("Daily loss limit: {} lamports", escrow.daily_loss_limit);
+
+        Ok(())
+    }
+
+    /// Player excludes themselves from play for `duration_seconds`. Deposits,
+    /// new sessions, and settlements are blocked until it expires; withdrawals
+    /// stay open. An exclusion can only be extended, never shortened.
+    pub fn self_exclude(ctx: Context<PlayerEscrowAction>, duration_seconds: i64) -> Result<()> {
+        require!(duration_seconds > 0, HouseboxError::InvalidExclusionDuration);
+
+        let now = Clock::get()?.unix_timestamp;
+        let until = now.checked_add(duration_seconds)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.excluded_until = escrow.excluded_until.max(until);
+
+        msg!("Player self-excluded until timestamp: {}", escrow.excluded_until);
+
+        Ok(())
+    }
+
+    /// Opt idle escrow lamports into escrow yield. The amount is set aside
+    /// from play and withdrawal (counted in reserved) and earns
+    /// escrow_yield_bps of settlement rake pro rata with other opted-in
+    /// balances.
+    pub fn opt_in_escrow_yield(ctx: Context<EscrowYieldAction>, amount_lamports: u64) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let acc = ctx.accounts.housebox_state.escrow_yield_per_lamport;
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        accrue_escrow_yield(escrow, acc)?;
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+
+        escrow.yield_balance = escrow.yield_balance.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        escrow.reserved = escrow.reserved.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.escrow_yield_staked = state.escrow_yield_staked.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Opted {} lamports into escrow yield (position: {})", amount_lamports, escrow.yield_balance);
+
+        Ok(())
+    }
+
+    /// Release opted-in lamports back to the playable escrow balance.
+    /// Accrued yield is kept.
+    pub fn opt_out_escrow_yield(ctx: Context<EscrowYieldAction>, amount_lamports: u64) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let acc = ctx.accounts.housebox_state.escrow_yield_per_lamport;
+        let escrow = &mut ctx.accounts.player_escrow;
+        accrue_escrow_yield(escrow, acc)?;
+        require!(escrow.yield_balance >= amount_lamports, HouseboxError::InsufficientEscrow);
+
+        escrow.yield_balance -= amount_lamports;
+        escrow.reserved = escrow.reserved.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.escrow_yield_staked = state.escrow_yield_staked.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Opted {} lamports out of escrow yield (position: {})", amount_lamports, escrow.yield_balance);
+
+        Ok(())
+    }
+
+    /// Claim accrued escrow yield into the escrow balance.
+    pub fn claim_escrow_yield(ctx: Context<EscrowYieldAction>) -> Result<()> {
+        let acc = ctx.accounts.housebox_state.escrow_yield_per_lamport;
+        let escrow = &mut ctx.accounts.player_escrow;
+        accrue_escrow_yield(escrow, acc)?;
+
+        let reward = escrow.pending_yield;
+        require!(reward > 0, HouseboxError::ZeroAmount);
+        escrow.pending_yield = 0;
+        escrow.balance = escrow.balance.checked_add(reward)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.escrow_yield_reserved = state.escrow_yield_reserved.checked_sub(reward)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Claimed {} lamports of escrow yield", reward);
+        msg!("Escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Pay a player's referrer the referral credit their escrow accrued from
+    /// settled rake (server only). Paid out of treasury_balance, up to what
+    /// the treasury currently holds.
+    pub fn pay_player_referral(ctx: Context<PayPlayerReferral>) -> Result<()> {
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(
+            escrow.referrer != Pubkey::default() && escrow.referrer == ctx.accounts.referrer.key(),
+            HouseboxError::InvalidReferrer
+        );
+
+        let share = escrow.referral_credit.min(state.treasury_balance);
+        require!(share > 0, HouseboxError::ZeroAmount);
+
+        escrow.referral_credit -= share;
+        state.treasury_balance -= share;
+
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.referrer.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            share,
+        )?;
+
+        msg!("Paid referrer {} lamports ({} credit left)", share, escrow.referral_credit);
+        msg!("Treasury balance: {}", state.treasury_balance);
+
+        Ok(())
+    }
+
+    /// Place an even-money coin-flip bet against the pool, no server involved.
+    /// The wager is reserved in the escrow. `commitment` is sha256 of a
+    /// player secret; the flip combines that secret with the hash of the slot
+    /// after the bet, which nobody knows when the bet is placed.
+    pub fn place_bet(
+        ctx: Context<PlaceBet>,
+        wager_lamports: u64,
+        commitment: [u8; 32],
+        heads: bool,
+    ) -> Result<()> {
+        require!(!ctx.accounts.housebox_state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(wager_lamports > 0, HouseboxError::ZeroAmount);
+
+        let clock = Clock::get()?;
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(!escrow.is_excluded(clock.unix_timestamp), HouseboxError::PlayerSelfExcluded);
+        require!(escrow.available() >= wager_lamports, HouseboxError::InsufficientEscrow);
+        escrow.reserved = escrow.reserved.checked_add(wager_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let bet = &mut ctx.accounts.coin_flip_bet;
+        bet.player = ctx.accounts.player.key();
+        bet.wager = wager_lamports;
+        bet.commitment = commitment;
+        bet.heads = heads;
+        bet.placed_slot = clock.slot;
+        bet.bump = ctx.bumps.coin_flip_bet;
+
+        msg!("Coin flip bet placed: {} lamports on {}", wager_lamports, if heads { "heads" } else { "tails" });
+
+        Ok(())
+    }
+
+    /// Reveal a coin-flip bet's secret and settle it (permissionless). Must
+    /// land after the slot following the bet and within
+    /// COIN_FLIP_REVEAL_WINDOW_SLOTS, while that slot's hash is still in the
+    /// SlotHashes sysvar. The win or loss is applied like any settlement.
+    pub fn reveal_bet(ctx: Context<ResolveBet>, secret: [u8; 32]) -> Result<()> {
+        let bet = &ctx.accounts.coin_flip_bet;
+        require!(
+            hashv(&[&secret]).to_bytes() == bet.commitment,
+            HouseboxError::InvalidReveal
+        );
+
+        let target_slot = bet.placed_slot + 1;
+        let slot_hash = slot_hash_at(&ctx.accounts.slot_hashes, target_slot)
+            .ok_or(HouseboxError::BetNotResolvable)?;
+
+        let flip = hashv(&[&secret, &slot_hash, bet.player.as_ref()]).to_bytes();
+        let heads = flip[0] & 1 == 1;
+        let won = heads == bet.heads;
+        let pnl = if won { bet.wager as i64 } else { -(bet.wager as i64) };
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.reserved = escrow.reserved.checked_sub(bet.wager)
+            .ok_or(HouseboxError::MathOverflow)?;
+        apply_settlement_pnl(&mut ctx.accounts.housebox_state, escrow, pnl)?;
+
+        msg!("Coin flip: {} — player {}", if heads { "heads" } else { "tails" }, if won { "won" } else { "lost" });
+        msg!("Escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Settle an unrevealed coin-flip bet as a loss once its reveal window
+    /// has passed (permissionless).
+    pub fn forfeit_bet(ctx: Context<ResolveBet>) -> Result<()> {
+        let bet = &ctx.accounts.coin_flip_bet;
+        require!(
+            Clock::get()?.slot > bet.placed_slot + COIN_FLIP_REVEAL_WINDOW_SLOTS,
+            HouseboxError::BetNotResolvable
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.reserved = escrow.reserved.checked_sub(bet.wager)
+            .ok_or(HouseboxError::MathOverflow)?;
+        apply_settlement_pnl(&mut ctx.accounts.housebox_state, escrow, -(bet.wager as i64))?;
+
+        msg!("Coin flip forfeited, {} lamports lost", bet.wager);
+
+        Ok(())
+    }
+
+    /// Open a game session, reserving part of the player's escrow for it so
+    /// concurrent sessions and withdrawals can't spend the same balance.
+    /// The escrow's available balance must be at least
+    /// min_session_escrow_lamports, so games aren't opened against empty
+    /// escrows whose losses can't be collected. Co-signed by the player and
+    /// an authorized server key, so only sessions the game server is running
+    /// can lock up escrow.
+    pub fn open_session(
+        ctx: Context<OpenSession>,
+        session_id: [u8; 32],
+        amount_lamports: u64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(
+            !escrow.is_excluded(Clock::get()?.unix_timestamp),
+            HouseboxError::PlayerSelfExcluded
+        );
+        require!(
+            escrow.available() >= state.min_session_escrow_lamports,
+            HouseboxError::EscrowBelowSessionMinimum
+        );
+        require!(escrow.available() >= amount_lamports, HouseboxError::InsufficientEscrow);
+        escrow.reserved = escrow.reserved.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let session = &mut ctx.accounts.game_session;
+        session.session_id = session_id;
+        session.player = ctx.accounts.player.key();
+        session.reserved_amount = amount_lamports;
+        session.opened_at = Clock::get()?.unix_timestamp;
+        session.bump = ctx.bumps.game_session;
+
+        msg!("Session opened, {} lamports reserved", amount_lamports);
+        msg!("Escrow balance: {}, reserved: {}", escrow.balance, escrow.reserved);
+
+        Ok(())
+    }
+
+    /// Close an open session without settling it (server-signed), releasing
+    /// its escrow reservation. Rent returns to the player.
+    pub fn close_session(ctx: Context<CloseSession>, _session_id: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let reserved = ctx.accounts.game_session.reserved_amount;
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.reserved = escrow.reserved.checked_sub(reserved)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Session closed, {} lamports released", reserved);
+
+        Ok(())
+    }
+
+    /// Commit to the session's outcome seed before play (server-signed):
+    /// `commitment` is sha256 of the seed. Settlement then requires the seed
+    /// to have been revealed with reveal_seed.
+    pub fn commit_seed(ctx: Context<SessionSeed>, _session_id: [u8; 32], commitment: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(commitment != [0u8; 32], HouseboxError::InvalidReveal);
+
+        let session = &mut ctx.accounts.game_session;
+        require!(session.seed_commitment == [0u8; 32], HouseboxError::SeedAlreadyCommitted);
+        session.seed_commitment = commitment;
+
+        msg!("Seed committed for session");
+
+        Ok(())
+    }
+
+    /// Reveal the committed seed (server-signed). It is checked against the
+    /// commitment and carried into the SettledSession at settlement, so
+    /// players can verify outcomes against a seed fixed before play.
+    pub fn reveal_seed(ctx: Context<SessionSeed>, _session_id: [u8; 32], seed: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let session = &mut ctx.accounts.game_session;
+        require!(session.seed_commitment != [0u8; 32], HouseboxError::InvalidReveal);
+        require!(!session.seed_revealed, HouseboxError::SeedAlreadyCommitted);
+        require!(
+            hashv(&[&seed]).to_bytes() == session.seed_commitment,
+            HouseboxError::InvalidReveal
+        );
+        session.revealed_seed = seed;
+        session.seed_revealed = true;
+
+        msg!("Seed revealed for session");
+
+        Ok(())
+    }
+
+    /// Bind a Switchboard randomness account to a session (server-signed).
+    /// The account must have been committed in the previous slot and not yet
+    /// revealed, so its value is unknown to everyone when it is bound.
+    pub fn request_randomness(ctx: Context<RequestRandomness>, session_id: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let (seed_slot, reveal_slot, _) =
+            read_switchboard_randomness(&ctx.accounts.randomness_account)?;
+        let clock = Clock::get()?;
+        require!(
+            seed_slot == clock.slot.saturating_sub(1) && reveal_slot == 0,
+            HouseboxError::RandomnessNotReady
+        );
+
+        let randomness = &mut ctx.accounts.session_randomness;
+        randomness.session_id = session_id;
+        randomness.randomness_account = ctx.accounts.randomness_account.key();
+        randomness.seed_slot = seed_slot;
+        randomness.value = [0u8; 32];
+        randomness.consumed = false;
+        randomness.bump = ctx.bumps.session_randomness;
+
+        msg!("Randomness requested for session (seed slot {})", seed_slot);
+
+        Ok(())
+    }
+
+    /// Consume the revealed Switchboard value into the session's randomness
+    /// account. Permissionless: the value is only accepted in its reveal slot
+    /// and only for the commitment bound by request_randomness.
+    pub fn consume_randomness(ctx: Context<ConsumeRandomness>, _session_id: [u8; 32]) -> Result<()> {
+        let randomness = &mut ctx.accounts.session_randomness;
+        require!(!randomness.consumed, HouseboxError::RandomnessNotReady);
+
+        let (seed_slot, reveal_slot, value) =
+            read_switchboard_randomness(&ctx.accounts.randomness_account)?;
+        require!(seed_slot == randomness.seed_slot, HouseboxError::InvalidRandomnessAccount);
+        require!(
+            reveal_slot != 0 && reveal_slot == Clock::get()?.slot,
+            HouseboxError::RandomnessNotReady
+        );
+
+        randomness.value = value;
+        randomness.consumed = true;
+
+        msg!("Randomness consumed for session (reveal slot {})", reveal_slot);
+
+        Ok(())
+    }
+
+    /// Apply an intra-session partial settlement (server-signed). PnL
+    /// accumulates on the open GameSession; losses draw down its reservation.
+    /// The session is closed out later by finalize_session. The play is
+    /// recorded against the game's exposure and the player's bonus wagering.
+    /// Rejected after `expires_at`.
+    pub fn settle_partial(
+        ctx: Context<SettlePartial>,
+        _session_id: [u8; 32],
+        pnl: i64,
+        game_type: u16,
+        total_wagered: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::SettlementExpired
+        );
+
+        let session = &mut ctx.accounts.game_session;
+        let escrow = &mut ctx.accounts.player_escrow;
+        if pnl < 0 {
+            let loss = pnl.unsigned_abs();
+            require!(loss <= session.reserved_amount, HouseboxError::LossExceedsReservation);
+            // The loss consumes reserved balance
+            session.reserved_amount -= loss;
+            escrow.reserved = escrow.reserved.checked_sub(loss)
+                .ok_or(HouseboxError::MathOverflow)?;
+        }
+
+        let split = apply_settlement_pnl(&mut ctx.accounts.housebox_state, escrow, pnl)?;
+        record_settlement_play(
+            &mut ctx.accounts.housebox_state,
+            escrow,
+            game_type,
+            pnl,
+            total_wagered,
+        )?;
+
+        session.cumulative_pnl = session.cumulative_pnl.checked_add(pnl)
+            .ok_or(HouseboxError::MathOverflow)?;
+        session.split = session.split.combine(&split)?;
+        session.last_settled_at = Clock::get()?.unix_timestamp;
+
+        msg!("Partial settlement: {} (cumulative: {})", pnl, session.cumulative_pnl);
+        msg!("Escrow balance: {}, reserved: {}", escrow.balance, escrow.reserved);
+
+        Ok(())
+    }
+
+    /// Finalize a partially settled session (server-signed): release what is
+    /// left of its reservation, record the cumulative PnL in a SettledSession,
+    /// and close the GameSession.
+    pub fn finalize_session(ctx: Context<FinalizeSession>, session_id: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let session = &ctx.accounts.game_session;
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.reserved = escrow.reserved.checked_sub(session.reserved_amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = session_id;
+        settled.player = ctx.accounts.player.key();
+        settled.settled_at = Clock::get()?.unix_timestamp;
+        settled.pnl = session.cumulative_pnl;
+        settled.split = session.split;
+        settled.escrow_balance = escrow.balance;
+        record_session_seed(session, settled)?;
+
+        msg!("Session finalized (cumulative PnL: {})", session.cumulative_pnl);
+        msg!("Escrow balance: {}, reserved: {}", escrow.balance, escrow.reserved);
+
+        Ok(())
+    }
+
+    /// Close out a stale session (permissionless). Once the session has been
+    /// idle longer than session_timeout_seconds, its reservation is released
+    /// and it is marked settled with its cumulative partial PnL and split, so
+    /// the server can't settle it later. No further PnL is applied.
+    pub fn expire_session(ctx: Context<ExpireSession>, session_id: [u8; 32]) -> Result<()> {
+        let timeout = ctx.accounts.housebox_state.session_timeout_seconds;
+        require!(timeout > 0, HouseboxError::SessionNotExpired);
+
+        let now = Clock::get()?.unix_timestamp;
+        let session = &ctx.accounts.game_session;
+        let last_activity = session.opened_at.max(session.last_settled_at);
+        require!(now >= last_activity + timeout, HouseboxError::SessionNotExpired);
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.reserved = escrow.reserved.checked_sub(session.reserved_amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        // Mark session as settled with the partial settlements already applied
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = session_id;
+        settled.player = ctx.accounts.player.key();
+        settled.settled_at = now;
+        settled.pnl = session.cumulative_pnl;
+        settled.split = session.split;
+        settled.escrow_balance = escrow.balance;
+
+        msg!(
+            "Session expired at cumulative PnL {}, {} lamports released",
+            session.cumulative_pnl,
+            session.reserved_amount
+        );
+
+        Ok(())
+    }
+
+    /// Settle player session P&L (server-signed).
+    /// No SOL actually moves — it's all in the same vault.
+    /// Just accounting entries between escrow and LP pool.
+    /// If the session was opened with open_session, its account must be passed:
+    /// the loss is capped at the reservation, which is released, and the session closed.
+    /// Game type, wager count, and total wagered are recorded on the
+    /// SettledSession for indexers and auditors. The settlement is rejected
+    /// after `expires_at`, so a stale or leaked signed settlement can't be
+    /// replayed later.
+    pub fn player_settle(
+        ctx: Context<PlayerSettle>,
+        pnl: i64,
+        session_id: [u8; 32],
+        game_type: u16,
+        wager_count: u32,
+        total_wagered: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= expires_at, HouseboxError::SettlementExpired);
+
+        // Settle against the session's reservation (closed by Anchor's `close = player`)
+        let escrow = &mut ctx.accounts.player_escrow;
+        let split = settle_player_pnl(
+            &mut ctx.accounts.housebox_state,
+            escrow,
+            ctx.accounts.game_session.as_deref(),
+            pnl,
+            game_type,
+            total_wagered,
+        )?;
+
+        // Mark session as settled
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = session_id;
+        settled.player = ctx.accounts.player.key();
+        settled.settled_at = now;
+        settled.pnl = pnl;
+        settled.split = split;
+        settled.escrow_balance = escrow.balance;
+        settled.game_type = game_type;
+        settled.wager_count = wager_count;
+        settled.total_wagered = total_wagered;
+        if let Some(session) = &ctx.accounts.game_session {
+            record_session_seed(session, settled)?;
+        }
+
+        emit!(PlayerSettled {
+            player: escrow.player,
+            session_id,
+            pnl,
+            escrow_balance: escrow.balance,
+            affiliate: escrow.affiliate,
+            timestamp: now,
+        });
+
+        msg!("Session settled. Escrow balance: {}", escrow.balance);
+        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+
+        Ok(())
+    }
+
+    /// Settle player P&L against the escrow's settlement nonce instead of a
+    /// per-session SettledSession PDA (server-signed). The nonce must equal
+    /// escrow.settlement_nonce and is then incremented, so each settlement
+    /// applies once without rent or cleanup. Nonce settlements can't be disputed.
+    /// An open session is settled and closed as in player_settle. Rejected
+    /// after `expires_at`.
+    pub fn player_settle_nonce(
+        ctx: Context<PlayerSettleNonce>,
+        pnl: i64,
+        nonce: u64,
+        game_type: u16,
+        total_wagered: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::SettlementExpired
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(nonce == escrow.settlement_nonce, HouseboxError::InvalidSettlementNonce);
+        escrow.settlement_nonce = escrow.settlement_nonce.checked_add(1)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        settle_player_pnl(
+            &mut ctx.accounts.housebox_state,
+            escrow,
+            ctx.accounts.game_session.as_deref(),
+            pnl,
+            game_type,
+            total_wagered,
+        )?;
+
+        msg!("Settled nonce {}. Escrow balance: {}", nonce, escrow.balance);
+        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+
+        Ok(())
+    }
+
+    /// Player flags a SOL settlement as disputed, within dispute_window_seconds
+    /// of it being settled. The dispute authority then confirms or reverses it.
+    pub fn dispute_settlement(ctx: Context<DisputeSettlement>, _session_id: [u8; 32]) -> Result<()> {
+        let window = ctx.accounts.housebox_state.dispute_window_seconds;
+        let now = Clock::get()?.unix_timestamp;
+
+        let settled = &mut ctx.accounts.settled_session;
+        require!(settled.player == ctx.accounts.player.key(), HouseboxError::Unauthorized);
+        require!(settled.asset_mint == Pubkey::default(), HouseboxError::DisputeNotAllowed);
+        require!(!settled.disputed, HouseboxError::SettlementDisputed);
+        require!(
+            window > 0 && now <= settled.settled_at + window,
+            HouseboxError::DisputeWindowClosed
+        );
+
+        settled.disputed = true;
+
+        msg!("Settlement disputed (PnL: {})", settled.pnl);
+
+        Ok(())
+    }
+
+    /// Resolve a disputed settlement (dispute authority only). With `reverse`,
+    /// the settled PnL is undone bucket by bucket from its recorded split; otherwise
+    /// it stands.
+    pub fn resolve_settlement_dispute(
+        ctx: Context<ResolveSettlementDispute>,
+        _session_id: [u8; 32],
+        reverse: bool,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("resolve_settlement_dispute")?;
+
+        require!(
+            ctx.accounts.dispute_authority.key() == ctx.accounts.housebox_state.dispute_authority,
+            HouseboxError::Unauthorized
+        );
+
+        let settled = &mut ctx.accounts.settled_session;
+        require!(settled.disputed && !settled.dispute_resolved, HouseboxError::SettlementNotDisputed);
+        settled.dispute_resolved = true;
+
+        if reverse {
+            let escrow = &mut ctx.accounts.player_escrow;
+            reverse_settlement(&mut ctx.accounts.housebox_state, escrow, settled)?;
+
+            msg!("Settlement reversed (PnL: {})", settled.pnl);
+            msg!("Escrow balance: {}", escrow.balance);
+        } else {
+            msg!("Settlement confirmed (PnL: {})", settled.pnl);
+        }
+
+        Ok(())
+    }
+
+    /// Propose clawing back a SOL settlement win (authority only), e.g. for a
+    /// proven exploit. The clawback can only execute after
+    /// CLAWBACK_TIMELOCK_SECONDS, during which the player can dispute it.
+    pub fn propose_clawback(ctx: Context<SettlementClawback>, session_id: [u8; 32]) -> Result<()> {
+        ctx.accounts.log_admin_action("propose_clawback")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let settled = &mut ctx.accounts.settled_session;
+        require!(
+            settled.asset_mint == Pubkey::default()
+                && settled.player != Pubkey::default()
+                && settled.pnl > 0
+                && !settled.reversed,
+            HouseboxError::ClawbackNotAllowed
+        );
+        require!(settled.clawback_at == 0, HouseboxError::ClawbackPending);
+
+        let now = Clock::get()?.unix_timestamp;
+        settled.clawback_at = now + CLAWBACK_TIMELOCK_SECONDS;
+        settled.clawback_disputed = false;
+
+        emit!(ClawbackProposed {
+            session_id,
+            player: settled.player,
+            amount: settled.pnl as u64,
+            executable_at: settled.clawback_at,
+        });
+        msg!("Clawback of {} lamports proposed, executable at {}", settled.pnl, settled.clawback_at);
+
+        Ok(())
+    }
+
+    /// Player disputes a proposed clawback before its timelock ends. The
+    /// dispute authority then decides it with resolve_clawback_dispute.
+    pub fn dispute_clawback(ctx: Context<DisputeSettlement>, _session_id: [u8; 32]) -> Result<()> {
+        let settled = &mut ctx.accounts.settled_session;
+        require!(settled.player == ctx.accounts.player.key(), HouseboxError::Unauthorized);
+        require!(settled.clawback_at != 0, HouseboxError::ClawbackNotPending);
+        require!(!settled.clawback_disputed, HouseboxError::SettlementDisputed);
+        require!(
+            Clock::get()?.unix_timestamp < settled.clawback_at,
+            HouseboxError::DisputeWindowClosed
+        );
+
+        settled.clawback_disputed = true;
+
+        msg!("Clawback disputed");
+
+        Ok(())
+    }
+
+    /// Execute an undisputed clawback once its timelock has passed (authority
+    /// only). The win is moved from the escrow back to the pool and insurance
+    /// fund that paid it.
+    pub fn execute_clawback(ctx: Context<SettlementClawback>, _session_id: [u8; 32]) -> Result<()> {
+        ctx.accounts.log_admin_action("execute_clawback")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let settled = &mut ctx.accounts.settled_session;
+        require!(settled.clawback_at != 0, HouseboxError::ClawbackNotPending);
+        require!(!settled.clawback_disputed, HouseboxError::SettlementDisputed);
+        require!(
+            Clock::get()?.unix_timestamp >= settled.clawback_at,
+            HouseboxError::ClawbackTimelocked
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        reverse_settlement(&mut ctx.accounts.housebox_state, escrow, settled)?;
+        settled.clawback_at = 0;
+
+        msg!("Clawback executed ({} lamports)", settled.pnl);
+        msg!("Escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Decide a disputed clawback (dispute authority only). With `uphold`,
+    /// the clawback executes immediately; otherwise it is cancelled.
+    pub fn resolve_clawback_dispute(
+        ctx: Context<ResolveSettlementDispute>,
+        _session_id: [u8; 32],
+        uphold: bool,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("resolve_clawback_dispute")?;
+
+        require!(
+            ctx.accounts.dispute_authority.key() == ctx.accounts.housebox_state.dispute_authority,
+            HouseboxError::Unauthorized
+        );
+
+        let settled = &mut ctx.accounts.settled_session;
+        require!(
+            settled.clawback_at != 0 && settled.clawback_disputed,
+            HouseboxError::SettlementNotDisputed
+        );
+
+        if uphold {
+            let escrow = &mut ctx.accounts.player_escrow;
+            reverse_settlement(&mut ctx.accounts.housebox_state, escrow, settled)?;
+            msg!("Clawback upheld ({} lamports)", settled.pnl);
+        } else {
+            msg!("Clawback cancelled");
+        }
+        settled.clawback_at = 0;
+        settled.clawback_disputed = false;
+
+        Ok(())
+    }
+
+    /// Post a Merkle root of (player, pnl, session_id, expires_at) settlements (server-signed).
+    /// Each settlement is applied later by claim_settlement with its proof, so
+    /// one transaction covers any number of sessions.
+    pub fn post_settlement_root(ctx: Context<PostSettlementRoot>, root: [u8; 32]) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let settlement_root = &mut ctx.accounts.settlement_root;
+        settlement_root.root = root;
+        settlement_root.posted_at = Clock::get()?.unix_timestamp;
+        settlement_root.claimed_count = 0;
+        settlement_root.bump = ctx.bumps.settlement_root;
+
+        msg!("Settlement root posted");
+
+        Ok(())
+    }
+
+    /// Settle a whole game round across many players in one transaction
+    /// (server-signed). remaining_accounts holds the players' escrows (mut), in
+    /// the same order as `pnls`. After applying every PnL, the sum of escrow
+    /// deltas must exactly offset the change in house balances (solsum,
+    /// treasury, jackpot, insurance). Each player's play is recorded against
+    /// `game_type`'s exposure and their bonus wagering, with `wagered` giving
+    /// the players' volumes in the same order. Round losses come from
+    /// unreserved escrow, like any settlement without a session. The round id
+    /// is marked settled like a session, so a round applies once.
+    pub fn settle_round<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleRound<'info>>,
+        round_id: [u8; 32],
+        game_type: u16,
+        pnls: Vec<i64>,
+        wagered: Vec<u64>,
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::SettlementExpired
+        );
+
+        let remaining = ctx.remaining_accounts;
+        require!(
+            !pnls.is_empty()
+                && pnls.len() <= MAX_ROUND_SETTLEMENTS
+                && wagered.len() == pnls.len()
+                && remaining.len() == pnls.len(),
+            HouseboxError::InvalidBatchAccounts
+        );
+        for (i, info) in remaining.iter().enumerate() {
+            require!(
+                info.is_writable && remaining[..i].iter().all(|other| other.key() != info.key()),
+                HouseboxError::InvalidBatchAccounts
+            );
+        }
+
+        let house_before = house_balance(&ctx.accounts.housebox_state);
+        let mut escrow_delta: i128 = 0;
+        let mut net_pnl: i64 = 0;
+
+        for ((escrow_info, pnl), total_wagered) in remaining.iter()
+            .zip(pnls.iter().copied())
+            .zip(wagered.iter().copied())
+        {
+            let mut escrow = Account::<PlayerEscrow>::try_from(escrow_info)?;
+            let expected = Pubkey::create_program_address(
+                &[b"escrow", escrow.player.as_ref(), &[escrow.bump]],
+                &crate::ID,
+            ).map_err(|_| HouseboxError::InvalidBatchAccounts)?;
+            require!(escrow_info.key() == expected, HouseboxError::InvalidBatchAccounts);
+
+            let balance_before = escrow.balance;
+            settle_player_pnl(
+                &mut ctx.accounts.housebox_state,
+                &mut escrow,
+                None,
+                pnl,
+                game_type,
+                total_wagered,
+            )?;
+            escrow_delta += escrow.balance as i128 - balance_before as i128;
+            net_pnl = net_pnl.checked_add(pnl)
+                .ok_or(HouseboxError::MathOverflow)?;
+
+            escrow.exit(&crate::ID)?;
+        }
+
+        // Conservation: whatever escrows gained, the house lost, and vice versa
+        let house_after = house_balance(&ctx.accounts.housebox_state);
+        require!(
+            escrow_delta == house_before - house_after && escrow_delta == net_pnl as i128,
+            HouseboxError::RoundNotConserved
+        );
+
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = round_id;
+        settled.player = Pubkey::default();
+        settled.settled_at = Clock::get()?.unix_timestamp;
+        settled.pnl = net_pnl;
+        settled.game_type = game_type;
+        settled.wager_count = pnls.len() as u32;
+        settled.total_wagered = wagered.iter().try_fold(0u64, |sum, amount| sum.checked_add(*amount))
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Round settled: {} players, net PnL {}", pnls.len(), net_pnl);
+        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+
+        Ok(())
+    }
+
+    /// Apply one settlement from a posted Merkle root (permissionless: the
+    /// player or a keeper submits the proof and pays the SettledSession rent).
+    /// Shares the settled-session PDA with player_settle, so a session can
+    /// only ever be settled once. The session PDA is always passed, and an
+    /// open session is settled and closed as in player_settle, so a keeper
+    /// can't skip its reservation. The game type and wager figures are part
+    /// of the leaf.
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim_settlement(
+        ctx: Context<ClaimSettlement>,
+        pnl: i64,
+        session_id: [u8; 32],
+        game_type: u16,
+        wager_count: u32,
+        total_wagered: u64,
+        proof: Vec<[u8; 32]>,
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, HouseboxError::InvalidMerkleProof);
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::SettlementExpired
+        );
+
+        let leaf = settlement_leaf(
+            &ctx.accounts.player.key(),
+            pnl,
+            &session_id,
+            game_type,
+            wager_count,
+            total_wagered,
+            expires_at,
+        );
+        require!(
+            verify_merkle_proof(&proof, &ctx.accounts.settlement_root.root, leaf),
+            HouseboxError::InvalidMerkleProof
+        );
+
+        let game_session = load_open_session(&ctx.accounts.game_session)?;
+        let escrow = &mut ctx.accounts.player_escrow;
+        let split = settle_player_pnl(
+            &mut ctx.accounts.housebox_state,
+            escrow,
+            game_session.as_ref(),
+            pnl,
+            game_type,
+            total_wagered,
+        )?;
+
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = session_id;
+        settled.player = ctx.accounts.player.key();
+        settled.settled_at = Clock::get()?.unix_timestamp;
+        settled.pnl = pnl;
+        settled.split = split;
+        settled.escrow_balance = escrow.balance;
+        settled.game_type = game_type;
+        settled.wager_count = wager_count;
+        settled.total_wagered = total_wagered;
+        if let Some(session) = &game_session {
+            record_session_seed(session, settled)?;
+            close_program_account(&ctx.accounts.game_session, &ctx.accounts.player)?;
+        }
+
+        let settlement_root = &mut ctx.accounts.settlement_root;
+        settlement_root.claimed_count = settlement_root.claimed_count.checked_add(1)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Session settled from root. Escrow balance: {}", escrow.balance);
+        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+
+        Ok(())
+    }
+
+    /// Player withdraws SOL from escrow (server-authorized).
+    /// Withdrawals require server co-signature to prevent unauthorized withdrawals
+    /// while a player has an active game session.
+    pub fn player_withdraw(ctx: Context<PlayerWithdraw>, amount_lamports: u64) -> Result<()> {
+        // Verify server signature matches configured server pubkey
+        let state = &ctx.accounts.housebox_state;
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        // Note: Withdrawals always allowed, even when paused (after server approval)
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+        record_player_withdrawal(state, escrow, amount_lamports, Clock::get()?.unix_timestamp)?;
+
+        // Verify withdrawal goes to the verified withdrawal address
+        let destination = withdrawal_destination(
+            ctx.accounts.player.as_ref(),
+            &ctx.accounts.withdrawal_destination,
+        );
+        require!(
+            escrow.verified_withdrawal_address == destination.key(),
+            HouseboxError::WithdrawalAddressMismatch
+        );
+
+        // Update escrow
+        escrow.balance = escrow.balance.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        emit_if_low_balance(escrow, amount_lamports)?;
+
+        // Transfer SOL from vault to player (PDA signer)
+        let sol_vault_bump = ctx.accounts.housebox_state.sol_vault_bump;
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: destination,
+                },
+                vault_signer_seeds,
+            ),
+            amount_lamports,
+        )?;
+
+        msg!("Player withdrew {} lamports from escrow", amount_lamports);
+        msg!("Remaining escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Withdraw the escrow's whole withdrawable balance (server-authorized).
+    /// The amount is read at execution time, so a settlement landing after the
+    /// balance was queried can't make a fixed-amount withdrawal fail or leave dust.
+    pub fn player_withdraw_all(ctx: Context<PlayerWithdraw>) -> Result<()> {
+        let amount_lamports = ctx.accounts.player_escrow.withdrawable();
+        player_withdraw(ctx, amount_lamports)
+    }
+
+    /// Withdraw escrow SOL to an arbitrary destination (e.g. an exchange
+    /// deposit address). Requires both the player and the server to sign;
+    /// player_withdraw stays locked to the verified withdrawal address.
+    pub fn player_withdraw_to(ctx: Context<PlayerWithdrawTo>, amount_lamports: u64) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+        record_player_withdrawal(state, escrow, amount_lamports, Clock::get()?.unix_timestamp)?;
+
+        escrow.balance = escrow.balance.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        emit_if_low_balance(escrow, amount_lamports)?;
+
+        let sol_vault_bump = ctx.accounts.housebox_state.sol_vault_bump;
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            amount_lamports,
+        )?;
+
+        msg!("Player withdrew {} lamports to {}", amount_lamports, ctx.accounts.destination.key());
+        msg!("Remaining escrow balance: {}", escrow.balance);
+
+        Ok(())
+    }
+
+    /// Queue a withdrawal for server approval (player-signed). The request
+    /// is an on-chain record of the pending withdrawal; one may be open per
+    /// player, and the balance is re-checked when it is approved.
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount_lamports: u64) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let escrow = &ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+
+        let request = &mut ctx.accounts.withdrawal_request;
+        request.player = ctx.accounts.player.key();
+        request.amount_lamports = amount_lamports;
+        request.requested_at = Clock::get()?.unix_timestamp;
+        request.bump = ctx.bumps.withdrawal_request;
+
+        msg!("Withdrawal of {} lamports requested", amount_lamports);
+
+        Ok(())
+    }
+
+    /// Cancel the player's pending withdrawal request, refunding its rent.
+    pub fn cancel_withdrawal_request(_ctx: Context<CancelWithdrawalRequest>) -> Result<()> {
+        msg!("Withdrawal request cancelled");
+        Ok(())
+    }
+
+    /// Approve many queued withdrawals in one transaction (server-signed).
+    /// remaining_accounts holds triples of
+    /// [withdrawal_request (mut), player_escrow (mut), destination (mut)],
+    /// where destination is the escrow's verified withdrawal address. Each
+    /// request is paid from the vault like player_withdraw and closed, with
+    /// its rent returned to the destination.
+    pub fn approve_withdrawals_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ApproveWithdrawalsBatch<'info>>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let remaining = ctx.remaining_accounts;
+        require!(
+            !remaining.is_empty() && remaining.len().is_multiple_of(3),
+            HouseboxError::InvalidBatchAccounts
+        );
+
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[ctx.accounts.housebox_state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+        let now = Clock::get()?.unix_timestamp;
+
+        for (i, chunk) in remaining.chunks(3).enumerate() {
+            let (request_info, escrow_info, destination_info) = (&chunk[0], &chunk[1], &chunk[2]);
+            require!(
+                remaining[..i * 3].iter().step_by(3).all(|other| other.key() != request_info.key()),
+                HouseboxError::InvalidBatchAccounts
+            );
+
+            let request = Account::<WithdrawalRequest>::try_from(request_info)?;
+            let mut escrow = Account::<PlayerEscrow>::try_from(escrow_info)?;
+            let expected = Pubkey::create_program_address(
+                &[b"escrow", request.player.as_ref(), &[escrow.bump]],
+                &crate::ID,
+            ).map_err(|_| HouseboxError::InvalidBatchAccounts)?;
+            require!(
+                escrow_info.key() == expected && escrow.player == request.player,
+                HouseboxError::InvalidBatchAccounts
+            );
+            require!(
+                escrow.verified_withdrawal_address == destination_info.key(),
+                HouseboxError::WithdrawalAddressMismatch
+            );
+
+            let amount_lamports = request.amount_lamports;
+            require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+            require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+            record_player_withdrawal(&ctx.accounts.housebox_state, &mut escrow, amount_lamports, now)?;
+
+            escrow.balance = escrow.balance.checked_sub(amount_lamports)
+                .ok_or(HouseboxError::MathOverflow)?;
+            emit_if_low_balance(&escrow, amount_lamports)?;
+            escrow.exit(&crate::ID)?;
+
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.sol_vault.to_account_info(),
+                        to: destination_info.clone(),
+                    },
+                    vault_signer_seeds,
+                ),
+                amount_lamports,
+            )?;
+
+            request.close(destination_info.clone())?;
+
+            msg!("Withdrawal approved for {}: {} lamports", request.player, amount_lamports);
+        }
+
+        msg!("Batch approved {} withdrawals", remaining.len() / 3);
+
+        Ok(())
+    }
+
+    /// Settle a player session from a server-signed ed25519 voucher.
+    /// The transaction must carry an Ed25519 program instruction, immediately
+    /// before this one, verifying the server key's signature over the
+    /// settlement message; anyone can submit it, so the server key need not sign.
+    /// The voucher is rejected after its signed `expires_at`. It also signs
+    /// the game type and wager figures. The session PDA is always passed, and
+    /// an open session is settled and closed as in player_settle.
+    pub fn player_settle_voucher(
+        ctx: Context<PlayerSettleVoucher>,
+        pnl: i64,
+        session_id: [u8; 32],
+        game_type: u16,
+        wager_count: u32,
+        total_wagered: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+
+        let message = settle_voucher_message(
+            &ctx.accounts.player.key(),
+            pnl,
+            &session_id,
+            game_type,
+            wager_count,
+            total_wagered,
+            expires_at,
+        );
+        verify_server_voucher(&ctx.accounts.instructions, state, &message)?;
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::SettlementExpired
+        );
+
+        let game_session = load_open_session(&ctx.accounts.game_session)?;
+        let escrow = &mut ctx.accounts.player_escrow;
+        let split = settle_player_pnl(
+            &mut ctx.accounts.housebox_state,
+            escrow,
+            game_session.as_ref(),
+            pnl,
+            game_type,
+            total_wagered,
+        )?;
+
+        // Mark session as settled
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = session_id;
+        settled.player = ctx.accounts.player.key();
+        settled.settled_at = Clock::get()?.unix_timestamp;
+        settled.pnl = pnl;
+        settled.split = split;
+        settled.escrow_balance = escrow.balance;
+        settled.game_type = game_type;
+        settled.wager_count = wager_count;
+        settled.total_wagered = total_wagered;
+        if let Some(session) = &game_session {
+            record_session_seed(session, settled)?;
+            close_program_account(&ctx.accounts.game_session, &ctx.accounts.player)?;
+        }
+
+        msg!("Session settled by voucher. Escrow balance: {}", escrow.balance);
+        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+
+        Ok(())
+    }
+
+    /// Settle a player session signed by both the server and the player, for
+    /// high-stakes tables where neither side may settle unilaterally. The
+    /// transaction must carry two Ed25519 program instructions immediately
+    /// before this one: the server key's signature, then the player's, both
+    /// over the same co-signed settlement message, which also covers the game
+    /// type and wager figures. Anyone can submit it until the signed
+    /// `expires_at`; the session PDA is always passed, and an open session is
+    /// settled and closed as in player_settle.
+    pub fn player_settle_cosigned(
+        ctx: Context<PlayerSettleVoucher>,
+        pnl: i64,
+        session_id: [u8; 32],
+        game_type: u16,
+        wager_count: u32,
+        total_wagered: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+
+        let player = ctx.accounts.player.key();
+        let message = cosigned_settle_message(
+            &player,
+            pnl,
+            &session_id,
+            game_type,
+            wager_count,
+            total_wagered,
+            expires_at,
+        );
+
+        let (server_key, server_message) = ed25519_signature_before(&ctx.accounts.instructions, 2)?;
+        require!(
+            state.is_server_key(&server_key) && server_message == message,
+            HouseboxError::InvalidServerSignature
+        );
+        let (player_key, player_message) = ed25519_signature_before(&ctx.accounts.instructions, 1)?;
+        require!(
+            player_key == player && player_message == message,
+            HouseboxError::InvalidPlayerSignature
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::SettlementExpired
+        );
+
+        let game_session = load_open_session(&ctx.accounts.game_session)?;
+        let escrow = &mut ctx.accounts.player_escrow;
+        let split = settle_player_pnl(
+            &mut ctx.accounts.housebox_state,
+            escrow,
+            game_session.as_ref(),
+            pnl,
+            game_type,
+            total_wagered,
+        )?;
+
+        // Mark session as settled
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = session_id;
+        settled.player = player;
+        settled.settled_at = Clock::get()?.unix_timestamp;
+        settled.pnl = pnl;
+        settled.split = split;
+        settled.escrow_balance = escrow.balance;
+        settled.game_type = game_type;
+        settled.wager_count = wager_count;
+        settled.total_wagered = total_wagered;
+        if let Some(session) = &game_session {
+            record_session_seed(session, settled)?;
+            close_program_account(&ctx.accounts.game_session, &ctx.accounts.player)?;
+        }
+
+        msg!("Session settled with player co-signature. Escrow balance: {}", escrow.balance);
+        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+
+        Ok(())
+    }
+
+    /// Withdraw from escrow with a server-signed ed25519 voucher instead of a
+    /// live server co-signature. The voucher covers the escrow's current
+    /// withdrawal nonce, which is then incremented so it can't be replayed,
+    /// and is rejected after its signed `expires_at`, since a closed and
+    /// re-created escrow starts again at nonce 0.
+    /// Like player_withdraw, allowed even when paused.
+    pub fn player_withdraw_voucher(
+        ctx: Context<PlayerWithdrawVoucher>,
+        amount_lamports: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let escrow = &ctx.accounts.player_escrow;
+        let message = withdraw_voucher_message(
+            &ctx.accounts.player.key(),
+            amount_lamports,
+            escrow.withdrawal_nonce,
+            expires_at,
+        );
+        verify_server_voucher(
+            &ctx.accounts.instructions,
+            &ctx.accounts.housebox_state,
+            &message,
+        )?;
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::VoucherExpired
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+        record_player_withdrawal(
+            &ctx.accounts.housebox_state,
+            escrow,
+            amount_lamports,
+            Clock::get()?.unix_timestamp,
+        )?;
+        let destination = withdrawal_destination(
+            ctx.accounts.player.as_ref(),
+            &ctx.accounts.withdrawal_destination,
+        );
+        require!(
+            escrow.verified_withdrawal_address == destination.key(),
+            HouseboxError::WithdrawalAddressMismatch
+        );
+
+        escrow.balance = escrow.balance.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        emit_if_low_balance(escrow, amount_lamports)?;
+        escrow.withdrawal_nonce = escrow.withdrawal_nonce.checked_add(1)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        // Transfer SOL from vault to player (PDA signer)
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[ctx.accounts.housebox_state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: destination,
+                },
+                vault_signer_seeds,
+            ),
+            amount_lamports,
+        )?;
+
+        msg!("Player withdrew {} lamports from escrow by voucher", amount_lamports);
+        msg!("Remaining escrow balance: {}", ctx.accounts.player_escrow.balance);
+
+        Ok(())
+    }
+
+    /// Player requests a withdrawal without server approval (escape hatch).
+    /// Executable after UNASSISTED_WITHDRAWAL_DELAY_SECONDS unless the server
+    /// objects first. Allowed even when paused.
+    pub fn request_unassisted_withdrawal(
+        ctx: Context<RequestUnassistedWithdrawal>,
+        amount_lamports: u64,
+    ) -> Result<()> {
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+        let escrow = &ctx.accounts.player_escrow;
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+
+        let request = &mut ctx.accounts.unassisted_withdrawal;
+        request.player = ctx.accounts.player.key();
+        request.amount = amount_lamports;
+        request.requested_at = Clock::get()?.unix_timestamp;
+        request.bump = ctx.bumps.unassisted_withdrawal;
+
+        msg!("Unassisted withdrawal requested: {} lamports", amount_lamports);
+        msg!("Executable at timestamp: {}", request.requested_at + UNASSISTED_WITHDRAWAL_DELAY_SECONDS);
+
+        Ok(())
+    }
+
+    /// Server objects to a pending unassisted withdrawal (server-signed).
+    /// Closes the request; rent returns to the player.
+    pub fn object_unassisted_withdrawal(ctx: Context<ObjectUnassistedWithdrawal>) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        msg!("Unassisted withdrawal for {} rejected by server", ctx.accounts.unassisted_withdrawal.player);
+
+        Ok(())
+    }
+
+    /// Execute an unassisted withdrawal after the delay (player-signed).
+    /// Pays to the verified withdrawal address; closes the request. Larger
+    /// amounts than max_withdrawal_lamports need several requests.
+    pub fn execute_unassisted_withdrawal(ctx: Context<ExecuteUnassistedWithdrawal>) -> Result<()> {
+        let request = &ctx.accounts.unassisted_withdrawal;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= request.requested_at + UNASSISTED_WITHDRAWAL_DELAY_SECONDS,
+            HouseboxError::UnassistedWithdrawalNotReady
+        );
+        let amount_lamports = request.amount;
+        check_max_withdrawal(&ctx.accounts.housebox_state, amount_lamports)?;
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+        let destination = withdrawal_destination(
+            ctx.accounts.player.as_ref(),
+            &ctx.accounts.withdrawal_destination,
+        );
+        require!(
+            escrow.verified_withdrawal_address == destination.key(),
+            HouseboxError::WithdrawalAddressMismatch
+        );
+
+        escrow.balance = escrow.balance.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        emit_if_low_balance(escrow, amount_lamports)?;
+
+        // Transfer SOL from vault to player (PDA signer)
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[ctx.accounts.housebox_state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: destination,
+                },
+                vault_signer_seeds,
+            ),
+            amount_lamports,
+        )?;
+
+        // Account will be closed by Anchor's `close = player` constraint
+        msg!("Unassisted withdrawal executed: {} lamports", amount_lamports);
+        msg!("Remaining escrow balance: {}", ctx.accounts.player_escrow.balance);
+
+        Ok(())
+    }
+
+    /// Player requests a new verified withdrawal address (player-signed).
+    /// Takes effect via confirm_withdrawal_address_change after the delay.
+    pub fn request_withdrawal_address_change(
+        ctx: Context<RequestWithdrawalAddressChange>,
+        new_address: Pubkey,
+    ) -> Result<()> {
+        require!(new_address != Pubkey::default(), HouseboxError::InvalidWithdrawalAddress);
+
+        let request = &mut ctx.accounts.address_change;
+        request.player = ctx.accounts.player.key();
+        request.new_address = new_address;
+        request.requested_at = Clock::get()?.unix_timestamp;
+        request.bump = ctx.bumps.address_change;
+
+        msg!("Withdrawal address change requested: {}", new_address);
+        msg!("Confirmable at timestamp: {}", request.requested_at + WITHDRAWAL_ADDRESS_CHANGE_DELAY_SECONDS);
+
+        Ok(())
+    }
+
+    /// Confirm a pending withdrawal address change after the delay.
+    /// Requires both the player and the server signer; closes the request.
+    pub fn confirm_withdrawal_address_change(
+        ctx: Context<ConfirmWithdrawalAddressChange>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let request = &ctx.accounts.address_change;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= request.requested_at + WITHDRAWAL_ADDRESS_CHANGE_DELAY_SECONDS,
+            HouseboxError::AddressChangeNotReady
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        let old_address = escrow.verified_withdrawal_address;
+        escrow.verified_withdrawal_address = request.new_address;
+
+        msg!("Withdrawal address changed");
+        msg!("Old: {}", old_address);
+        msg!("New: {}", escrow.verified_withdrawal_address);
+
+        Ok(())
+    }
+
+    /// Set the escrow balance below which settlements and withdrawals emit
+    /// LowEscrowBalance, so the operator can prompt a top-up (player-signed).
+    /// Zero turns the notification off.
+    pub fn set_low_balance_threshold(ctx: Context<PlayerEscrowAction>, threshold_lamports: u64) -> Result<()> {
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.low_balance_threshold = threshold_lamports;
+
+        msg!("Low balance threshold set to {} lamports", threshold_lamports);
+
+        Ok(())
+    }
+
+    /// Pre-authorize the server to sweep up to `allowance_lamports` of escrow
+    /// into `destination` (e.g. an operational account for off-chain
+    /// tournament buy-ins) until `expires_at` (player-signed). Replaces any
+    /// earlier authorization; a zero allowance revokes it.
+    pub fn authorize_sweep(
+        ctx: Context<PlayerEscrowAction>,
+        destination: Pubkey,
+        allowance_lamports: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            allowance_lamports == 0 || expires_at > Clock::get()?.unix_timestamp,
+            HouseboxError::InvalidSweepAuthorization
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.sweep_destination = destination;
+        escrow.sweep_allowance = allowance_lamports;
+        escrow.sweep_expires_at = expires_at;
+
+        msg!("Sweep of up to {} lamports to {} authorized until {}", allowance_lamports, destination, expires_at);
+
+        Ok(())
+    }
+
+    /// Sweep escrow SOL into the player's pre-authorized operational account
+    /// (server-signed). Draws down the authorization and counts against the
+    /// withdrawal caps; the verified withdrawal address doesn't apply.
+    pub fn sweep_escrow(ctx: Context<SweepEscrow>, amount_lamports: u64) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(
+            Clock::get()?.unix_timestamp <= escrow.sweep_expires_at,
+            HouseboxError::SweepNotAuthorized
+        );
+        require!(amount_lamports <= escrow.sweep_allowance, HouseboxError::SweepNotAuthorized);
+        require!(escrow.withdrawable() >= amount_lamports, HouseboxError::InsufficientEscrow);
+        record_player_withdrawal(
+            &ctx.accounts.housebox_state,
+            escrow,
+            amount_lamports,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        escrow.sweep_allowance -= amount_lamports;
+        escrow.balance = escrow.balance.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        emit_if_low_balance(escrow, amount_lamports)?;
+
+        let sol_vault_bump = ctx.accounts.housebox_state.sol_vault_bump;
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.sweep_destination.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            amount_lamports,
+        )?;
+
+        msg!("Swept {} lamports from escrow", amount_lamports);
+        msg!("Remaining sweep allowance: {}", escrow.sweep_allowance);
+
+        Ok(())
+    }
+
+    /// Register (or replace) the escrow's recovery key (player-signed), e.g. in
+    /// the same transaction as the first deposit. If the player's key is
+    /// lost, the recovery key can move the verified withdrawal address after
+    /// RECOVERY_DELAY_SECONDS. Pubkey::default() removes it.
+    pub fn set_recovery_key(ctx: Context<PlayerEscrowAction>, recovery_key: Pubkey) -> Result<()> {
+        require!(
+            recovery_key != ctx.accounts.player.key(),
+            HouseboxError::InvalidRecoveryKey
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.recovery_key = recovery_key;
+
+        msg!("Recovery key set to: {}", recovery_key);
+
+        Ok(())
+    }
+
+    /// Start moving the verified withdrawal address with the escrow's
+    /// recovery key. Confirmable by the recovery key after
+    /// RECOVERY_DELAY_SECONDS; the player can cancel it in the meantime.
+    pub fn request_escrow_recovery(
+        ctx: Context<RequestEscrowRecovery>,
+        new_address: Pubkey,
+    ) -> Result<()> {
+        require!(new_address != Pubkey::default(), HouseboxError::InvalidWithdrawalAddress);
+
+        let recovery = &mut ctx.accounts.escrow_recovery;
+        recovery.player = ctx.accounts.player.key();
+        recovery.new_address = new_address;
+        recovery.requested_at = Clock::get()?.unix_timestamp;
+        recovery.bump = ctx.bumps.escrow_recovery;
+
+        msg!("Escrow recovery requested, new withdrawal address: {}", new_address);
+        msg!("Confirmable at timestamp: {}", recovery.requested_at + RECOVERY_DELAY_SECONDS);
+
+        Ok(())
+    }
+
+    /// Confirm a recovery after the delay (recovery key signs); closes the
+    /// request, rent returned to the recovery key.
+    pub fn confirm_escrow_recovery(ctx: Context<ConfirmEscrowRecovery>) -> Result<()> {
+        let recovery = &ctx.accounts.escrow_recovery;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= recovery.requested_at + RECOVERY_DELAY_SECONDS,
+            HouseboxError::AddressChangeNotReady
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        let old_address = escrow.verified_withdrawal_address;
+        escrow.verified_withdrawal_address = recovery.new_address;
+
+        msg!("Withdrawal address recovered");
+        msg!("Old: {}", old_address);
+        msg!("New: {}", escrow.verified_withdrawal_address);
+
+        Ok(())
+    }
+
+    /// Cancel a pending recovery (player-signed), closing the request.
+    pub fn cancel_escrow_recovery(_ctx: Context<CancelEscrowRecovery>) -> Result<()> {
+        msg!("Escrow recovery cancelled");
+        Ok(())
+    }
+
+    /// Close an empty player escrow to reclaim rent (player and server sign).
+    /// The SOL and asset balances, session reservations, unclaimed yield, and
+    /// unpaid referral credit must all be zero. A later deposit re-creates
+    /// the escrow at withdrawal nonce 0; withdrawal vouchers expire, so ones
+    /// signed for the closed escrow can't be replayed against it.
+    pub fn close_player_escrow(ctx: Context<ClosePlayerEscrow>) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(ctx.accounts.player_escrow.is_empty(), HouseboxError::EscrowNotEmpty);
+
+        msg!("Closed player escrow, rent returned to player");
+
+        Ok(())
+    }
+
+    /// Move a player's escrow, balance and history included, to a new wallet
+    /// (old and new wallets sign, server co-signs). The escrow must have no
+    /// open sessions and the new wallet no escrow of its own. A verified
+    /// withdrawal address equal to the old wallet moves to the new one.
+    /// The old escrow is closed and may be re-created at withdrawal nonce 0;
+    /// vouchers signed for it expire, so they can't be replayed there.
+    pub fn transfer_escrow_ownership(ctx: Context<TransferEscrowOwnership>) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+
+        let old_player = ctx.accounts.old_player.key();
+        let new_player = ctx.accounts.new_player.key();
+        let old_escrow = &ctx.accounts.old_escrow;
+        require!(!old_escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(old_escrow.reserved == 0, HouseboxError::EscrowNotEmpty);
+
+        let mut escrow = (**old_escrow).clone();
+        escrow.player = new_player;
+        escrow.bump = ctx.bumps.new_escrow;
+        if escrow.verified_withdrawal_address == old_player {
+            escrow.verified_withdrawal_address = new_player;
+        }
+        ctx.accounts.new_escrow.set_inner(escrow);
+
+        msg!("Escrow moved from {} to {}", old_player, new_player);
+        msg!("Escrow balance: {}", ctx.accounts.new_escrow.balance);
+
+        Ok(())
+    }
+
+    /// Grant a player bonus credit funded by the LP pool (server only), up to
+    /// the governance-set bonus budget. The bonus can be played immediately
+    /// but only becomes withdrawable once `wagering_requirement` more
+    /// lamports have been wagered in settled sessions.
+    pub fn grant_bonus(
+        ctx: Context<CreditPlayerEscrow>,
+        amount_lamports: u64,
+        wagering_requirement: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+        require!(
+            amount_lamports <= state.bonus_budget_lamports,
+            HouseboxError::BonusBudgetExceeded
+        );
+        require!(state.liquid_solsum() >= amount_lamports, HouseboxError::InsufficientLiquidity);
+
+        state.solsum = state.solsum.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.bonus_budget_lamports = state.bonus_budget_lamports.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        escrow.balance = escrow.balance.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        escrow.bonus_balance = escrow.bonus_balance.checked_add(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+        escrow.wagering_required = escrow.wagering_required.checked_add(wagering_requirement)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Bonus of {} lamports granted ({} lamports wagering required)",
+            amount_lamports, escrow.wagering_required);
+        msg!("Bonus budget remaining: {}", state.bonus_budget_lamports);
+
+        Ok(())
+    }
+
+    /// Award the whole jackpot to a player's escrow (server only). The pot's
+    /// lamports are already in the vault, so this is accounting only.
+    pub fn award_jackpot(ctx: Context<CreditPlayerEscrow>) -> Result<()> {
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+
+        let amount = state.jackpot_balance;
+        require!(amount > 0, HouseboxError::ZeroAmount);
+        state.jackpot_balance = 0;
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        escrow.balance = escrow.balance.checked_add(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        emit!(JackpotAwarded {
+            player: escrow.player,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Jackpot of {} lamports awarded to {}", amount, escrow.player);
+
+        Ok(())
+    }
+
+    /// Freeze a player's escrow pending investigation (authority or server).
+    /// Settlements and withdrawals are blocked until unfrozen.
+    pub fn freeze_player_escrow(ctx: Context<FreezePlayerEscrow>) -> Result<()> {
+        ctx.accounts.log_admin_action("freeze_player_escrow")?;
+
+        let state = &ctx.accounts.housebox_state;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_server_key(&signer),
+            HouseboxError::Unauthorized
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.frozen = true;
+
+        emit!(EscrowFrozen {
+            player: escrow.player,
+            frozen_by: signer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Escrow frozen: {}", escrow.player);
+
+        Ok(())
+    }
+
+    /// Unfreeze a player's escrow (authority or server).
+    pub fn unfreeze_player_escrow(ctx: Context<FreezePlayerEscrow>) -> Result<()> {
+        ctx.accounts.log_admin_action("unfreeze_player_escrow")?;
+
+        let state = &ctx.accounts.housebox_state;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_server_key(&signer),
+            HouseboxError::Unauthorized
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.frozen = false;
+
+        emit!(EscrowUnfrozen {
+            player: escrow.player,
+            unfrozen_by: signer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Escrow unfrozen: {}", escrow.player);
+
+        Ok(())
+    }
+
+    /// Pause the protocol. Requires the pause authority, the operator role, or
+    /// the guardian. A guardian pause lapses after guardian_pause_seconds
+    /// unless the authority confirms it with confirm_guardian_pause.
+    pub fn pause(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.log_admin_action("pause")?;
+
+        let signer = ctx.accounts.authority.key();
+        let state = &mut ctx.accounts.housebox_state;
+        let full_pause = signer == state.pause_authority
+            || has_role(state, &signer, ctx.accounts.role.as_deref(), RoleKind::Operator);
+        require!(
+            full_pause || (state.guardian != Pubkey::default() && signer == state.guardian),
+            HouseboxError::Unauthorized
+        );
+
+        let expires_at = if full_pause || state.is_pause_confirmed() {
+            0
+        } else {
+            let duration = if state.guardian_pause_seconds == 0 {
+                DEFAULT_GUARDIAN_PAUSE_SECONDS
+            } else {
+                state.guardian_pause_seconds
+            };
+            Clock::get()?.unix_timestamp.checked_add(duration)
+                .ok_or(HouseboxError::MathOverflow)?
+        };
+
+        emit_parameter_change(signer, "paused", state.pause_in_effect(), true)?;
+        emit_parameter_change(
+            signer,
+            "guardian_pause_expires_at",
+            state.guardian_pause_expires_at,
+            expires_at,
+        )?;
+        state.paused = true;
+        state.guardian_pause_expires_at = expires_at;
+
+        if expires_at == 0 {
+            msg!("Protocol PAUSED");
+        } else {
+            msg!("Protocol PAUSED by guardian until {}", expires_at);
+        }
+
+        Ok(())
+    }
+
+    /// Confirm a pending guardian pause (authority only) so it no longer lapses.
+    pub fn confirm_guardian_pause(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.log_admin_action("confirm_guardian_pause")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            state.guardian_pause_expires_at != 0 && state.pause_in_effect(),
+            HouseboxError::GuardianPauseNotPending
+        );
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "guardian_pause_expires_at",
+            state.guardian_pause_expires_at,
+            0,
+        )?;
+        state.guardian_pause_expires_at = 0;
+
+        msg!("Guardian pause confirmed");
+
+        Ok(())
+    }
+
+    /// Update how long an unconfirmed guardian pause lasts (authority only).
+    pub fn update_guardian_pause_seconds(ctx: Context<AdminAction>, pause_seconds: i64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_guardian_pause_seconds")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(pause_seconds > 0, HouseboxError::InvalidGuardianPause);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_seconds = state.guardian_pause_seconds;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "guardian_pause_seconds",
+            old_seconds,
+            pause_seconds,
+        )?;
+        state.guardian_pause_seconds = pause_seconds;
+
+        msg!("Guardian pause duration updated");
+        msg!("Old: {}s", old_seconds);
+        msg!("New: {}s", pause_seconds);
+
+        Ok(())
+    }
+
+    /// Announce a maintenance window. Requires the pause authority or the
+    /// operator role. The protocol is paused from `start` until `end`, then
+    /// resumes on its own without an unpause. `end` = 0 cancels a pending
+    /// window.
+    pub fn schedule_pause(ctx: Context<AdminAction>, start: i64, end: i64) -> Result<()> {
+        ctx.accounts.log_admin_action("schedule_pause")?;
+
+        let signer = ctx.accounts.authority.key();
+        require!(
+            signer == ctx.accounts.housebox_state.pause_authority
+                || has_role(
+                    &ctx.accounts.housebox_state,
+                    &signer,
+                    ctx.accounts.role.as_deref(),
+                    RoleKind::Operator,
+                ),
+            HouseboxError::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let (start, end) = if end == 0 { (0, 0) } else { (start, end) };
+        if end != 0 {
+            require!(
+                start < end && end > now && end - start <= MAX_MAINTENANCE_WINDOW_SECONDS,
+                HouseboxError::InvalidMaintenanceWindow
+            );
+        }
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(signer, "maintenance_start", state.maintenance_start, start)?;
+        emit_parameter_change(signer, "maintenance_end", state.maintenance_end, end)?;
+        state.maintenance_start = start;
+        state.maintenance_end = end;
+
+        emit!(MaintenanceScheduled {
+            actor: signer,
+            start,
+            end,
+            timestamp: now,
+        });
+        if end == 0 {
+            msg!("Scheduled maintenance cancelled");
+        } else {
+            msg!("Maintenance pause scheduled: {} -> {}", start, end);
+        }
+
+        Ok(())
+    }
+
+    /// Reassign the pause authority (authority only), e.g. to a guardian
+    /// multisig. Emits PauseAuthorityUpdated.
+    pub fn update_pause_authority(ctx: Context<AdminAction>, new_pause_authority: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("update_pause_authority")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_pause_authority = state.pause_authority;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "pause_authority",
+            state.pause_authority,
+            new_pause_authority,
+        )?;
+        state.pause_authority = new_pause_authority;
+
+        emit!(PauseAuthorityUpdated {
+            old_pause_authority,
+            new_pause_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Pause authority updated");
+        msg!("Old: {}", old_pause_authority);
+        msg!("New: {}", new_pause_authority);
+
+        Ok(())
+    }
+
+    /// Permanently lock the parameter groups in `flags` (FINALIZE_* bits,
+    /// authority only), so the protocol can credibly commit to them for LPs.
+    /// Irreversible. Server key rotation is never locked.
+    pub fn finalize_config(ctx: Context<AdminAction>, flags: u32) -> Result<()> {
+        ctx.accounts.log_admin_action("finalize_config")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(flags != 0 && flags & !FINALIZE_ALL == 0, HouseboxError::InvalidFinalizeFlags);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let finalized_params = state.finalized_params | flags;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "finalized_params",
+            state.finalized_params,
+            finalized_params,
+        )?;
+        state.finalized_params = finalized_params;
+
+        msg!("Parameters finalized: {:#06b}", state.finalized_params);
+
+        Ok(())
+    }
+
+    /// Set the guardian (authority only): a low-trust key, e.g. automated
+    /// monitoring, that can pause but not unpause or change parameters.
+    /// Pubkey::default() removes it.
+    pub fn update_guardian(ctx: Context<AdminAction>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("update_guardian")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_guardian = state.guardian;
+        emit_parameter_change(ctx.accounts.authority.key(), "guardian", state.guardian, guardian)?;
+        state.guardian = guardian;
+
+        msg!("Guardian updated");
+        msg!("Old: {}", old_guardian);
+        msg!("New: {}", guardian);
+
+        Ok(())
+    }
+
+    /// Set the cold recovery address an emergency evacuation pays out to
+    /// (authority only). Can't change while an evacuation is pending.
+    pub fn update_evacuation_address(ctx: Context<AdminAction>, evacuation_address: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("update_evacuation_address")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(state.evacuation_requested_at == 0, HouseboxError::EvacuationPending);
+        let old_address = state.evacuation_address;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "evacuation_address",
+            state.evacuation_address,
+            evacuation_address,
+        )?;
+        state.evacuation_address = evacuation_address;
+
+        msg!("Evacuation address updated");
+        msg!("Old: {}", old_address);
+        msg!("New: {}", evacuation_address);
+
+        Ok(())
+    }
+
+    /// Start the emergency evacuation timelock (authority only, while
+    /// paused). Emits EvacuationRequested so LPs and players see it
+    /// EVACUATION_TIMELOCK_SECONDS before funds can move.
+    pub fn request_evacuation(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.log_admin_action("request_evacuation")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(state.is_pause_confirmed(), HouseboxError::EvacuationRequiresPause);
+        require!(state.evacuation_address != Pubkey::default(), HouseboxError::InvalidWithdrawalAddress);
+        require!(state.evacuation_requested_at == 0, HouseboxError::EvacuationPending);
+
+        let now = Clock::get()?.unix_timestamp;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "evacuation_requested_at",
+            state.evacuation_requested_at,
+            now,
+        )?;
+        state.evacuation_requested_at = now;
+
+        emit!(EvacuationRequested {
+            evacuation_address: state.evacuation_address,
+            executable_at: now + EVACUATION_TIMELOCK_SECONDS,
+            timestamp: now,
+        });
+        msg!("Evacuation requested to {}", state.evacuation_address);
+
+        Ok(())
+    }
+
+    /// Cancel a pending evacuation (authority only).
+    pub fn cancel_evacuation(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.log_admin_action("cancel_evacuation")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(state.evacuation_requested_at != 0, HouseboxError::EvacuationNotPending);
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "evacuation_requested_at",
+            state.evacuation_requested_at,
+            0,
+        )?;
+        state.evacuation_requested_at = 0;
+
+        msg!("Evacuation cancelled");
+
+        Ok(())
+    }
+
+    /// Last resort: move all SOL in the vault to the evacuation address once
+    /// the timelock has passed (authority only, while paused). Escrow and LP
+    /// accounting is left in place as the record of what is owed.
+    pub fn execute_evacuation(ctx: Context<ExecuteEvacuation>) -> Result<()> {
+        ctx.accounts.log_admin_action("execute_evacuation")?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            ctx.accounts.authority.key() == state.authority,
+            HouseboxError::Unauthorized
+        );
+        require_upgrade_authority(&ctx.accounts.program_data, &state.authority)?;
+        require!(state.is_pause_confirmed(), HouseboxError::EvacuationRequiresPause);
+        require!(state.evacuation_requested_at != 0, HouseboxError::EvacuationNotPending);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= state.evacuation_requested_at + EVACUATION_TIMELOCK_SECONDS,
+            HouseboxError::TimelockNotElapsed
+        );
+        state.evacuation_requested_at = 0;
+
+        let amount = ctx.accounts.sol_vault.lamports();
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.evacuation_address.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(VaultEvacuated {
+            evacuation_address: state.evacuation_address,
+            amount,
+            timestamp: now,
+        });
+        msg!("Evacuated {} lamports to {}", amount, state.evacuation_address);
+
+        Ok(())
+    }
+
+    /// Unpause the protocol. Requires the pause authority or the operator role.
+    /// A migrated pool stays paused.
+    pub fn unpause(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.log_admin_action("unpause")?;
+
+        let signer = ctx.accounts.authority.key();
+        require!(
+            signer == ctx.accounts.housebox_state.pause_authority
+                || has_role(
+                    &ctx.accounts.housebox_state,
+                    &signer,
+                    ctx.accounts.role.as_deref(),
+                    RoleKind::Operator,
+                ),
+            HouseboxError::Unauthorized
+        );
+        require!(
+            ctx.accounts.housebox_state.migrated_to == Pubkey::default(),
+            HouseboxError::PoolMigrated
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(ctx.accounts.authority.key(), "paused", state.paused, false)?;
+        state.paused = false;
+        state.guardian_pause_expires_at = 0;
+
+        msg!("Protocol UNPAUSED");
+
+        Ok(())
+    }
+
+    /// Enable or disable exodus mode. Requires the authority or the risk
+    /// manager role. While paused with exodus mode on, LPs can still request
+    /// and execute redemptions; locks stay blocked by the pause.
+    pub fn update_exodus_mode(ctx: Context<AdminAction>, enabled: bool) -> Result<()> {
+        ctx.accounts.log_admin_action("update_exodus_mode")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "exodus_mode",
+            state.exodus_mode,
+            enabled,
+        )?;
+        state.exodus_mode = enabled;
+
+        msg!("Exodus mode {}", if enabled { "ENABLED" } else { "DISABLED" });
+
+        Ok(())
+    }
+
+    /// Enable or disable sunset mode (authority only). While on, LP locks and
+    /// player deposits are rejected; redemptions, settlements and
+    /// withdrawals keep working so the protocol can wind down in order.
+    pub fn update_sunset_mode(ctx: Context<AdminAction>, enabled: bool) -> Result<()> {
+        ctx.accounts.log_admin_action("update_sunset_mode")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "sunset_mode",
+            state.sunset_mode,
+            enabled,
+        )?;
+        state.sunset_mode = enabled;
+
+        msg!("Sunset mode {}", if enabled { "ENABLED" } else { "DISABLED" });
+
+        Ok(())
+    }
+
+    /// Update the minimum available escrow balance needed to open a session.
+    /// Requires the authority or the operator role. Zero disables the minimum.
+    pub fn update_min_session_escrow(
+        ctx: Context<AdminAction>,
+        min_session_escrow_lamports: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_min_session_escrow")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old = state.min_session_escrow_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "min_session_escrow_lamports",
+            state.min_session_escrow_lamports,
+            min_session_escrow_lamports,
+        )?;
+        state.min_session_escrow_lamports = min_session_escrow_lamports;
+
+        msg!("Min session escrow updated");
+        msg!("Old: {} lamports", old);
+        msg!("New: {} lamports", min_session_escrow_lamports);
+
+        Ok(())
+    }
+
+    /// Allow or block settlements while paused. Requires the authority or the
+    /// operator role. With this on, outstanding sessions can still be settled
+    /// during a pause so no stale liabilities are left behind; deposits, locks,
+    /// and new sessions stay blocked.
+    pub fn update_settle_while_paused(ctx: Context<AdminAction>, enabled: bool) -> Result<()> {
+        ctx.accounts.log_admin_action("update_settle_while_paused")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "settle_while_paused",
+            state.settle_while_paused,
+            enabled,
+        )?;
+        state.settle_while_paused = enabled;
+
+        msg!("Settlements while paused {}", if enabled { "ALLOWED" } else { "BLOCKED" });
+
+        Ok(())
+    }
+
+    /// Propose a new program authority (authority only), e.g. a multisig.
+    /// Nothing changes until the proposed key calls accept_authority, so a
+    /// mistyped key can't take over. Pubkey::default() cancels a proposal.
+    pub fn propose_authority(ctx: Context<AdminAction>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("propose_authority")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "pending_authority",
+            state.pending_authority,
+            new_authority,
+        )?;
+        state.pending_authority = new_authority;
+
+        msg!("Authority transfer proposed to: {}", new_authority);
+
+        Ok(())
+    }
+
+    /// Accept a proposed authority transfer (signed by the pending authority).
+    pub fn accept_authority(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.log_admin_action("accept_authority")?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            state.pending_authority != Pubkey::default()
+                && ctx.accounts.authority.key() == state.pending_authority,
+            HouseboxError::Unauthorized
+        );
+
+        let old_authority = state.authority;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "authority",
+            state.authority,
+            state.pending_authority,
+        )?;
+        state.authority = state.pending_authority;
+        state.pending_authority = Pubkey::default();
+
+        msg!("Authority transferred");
+        msg!("Old: {}", old_authority);
+        msg!("New: {}", state.authority);
+
+        Ok(())
+    }
+
+    /// Grant `holder` a role (authority only). Role holders may run the
+    /// privileged instructions delegated to that role by passing their Role
+    /// PDA; the admin role covers every delegated instruction.
+    pub fn grant_role(ctx: Context<GrantRole>, role: RoleKind, holder: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("grant_role")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let role_account = &mut ctx.accounts.role_account;
+        role_account.holder = holder;
+        role_account.role = role;
+        role_account.granted_at = now;
+        role_account.bump = ctx.bumps.role_account;
+
+        emit!(RoleGranted {
+            actor: ctx.accounts.authority.key(),
+            holder,
+            role,
+            timestamp: now,
+        });
+        msg!("Role {:?} granted to {}", role, holder);
+
+        Ok(())
+    }
+
+    /// Revoke a role (authority only), closing its PDA.
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: RoleKind, holder: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("revoke_role")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        emit!(RoleRevoked {
+            actor: ctx.accounts.authority.key(),
+            holder,
+            role,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Role {:?} revoked from {}", role, holder);
+
+        Ok(())
+    }
+
+    /// Create the admin action log (authority only). From then on every
+    /// AdminAction instruction must pass it and appends an entry; the log's
+    /// hash chain makes rewriting earlier entries detectable.
+    pub fn initialize_admin_log(ctx: Context<InitializeAdminLog>) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let log = &mut ctx.accounts.admin_log;
+        log.entries = [AdminLogEntry::default(); ADMIN_LOG_CAPACITY];
+        log.next_index = 0;
+        log.total_actions = 0;
+        log.head_hash = [0u8; 32];
+        log.bump = ctx.bumps.admin_log;
+
+        ctx.accounts.housebox_state.admin_log_enabled = true;
+
+        msg!("Admin action log initialized ({} entries)", ADMIN_LOG_CAPACITY);
+
+        Ok(())
+    }
+
+    /// Hand sensitive admin operations (server key update, add and removal,
+    /// lp_percent change, treasury withdrawal, fee recipient) to an M-of-N
+    /// council (authority only). From then on they only run through council
+    /// proposals reaching `threshold` approvals; the authority's direct instructions for them are disabled.
+    pub fn initialize_admin_council(
+        ctx: Context<InitializeAdminCouncil>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("initialize_admin_council")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(
+            !members.is_empty() && members.len() <= MAX_COUNCIL_MEMBERS,
+            HouseboxError::InvalidCouncil
+        );
+        require!(
+            threshold > 0 && threshold as usize <= members.len(),
+            HouseboxError::InvalidCouncil
+        );
+        for (i, member) in members.iter().enumerate() {
+            require!(
+                *member != Pubkey::default() && !members[..i].contains(member),
+                HouseboxError::InvalidCouncil
+            );
+        }
+
+        let council = &mut ctx.accounts.admin_council;
+        council.members = [Pubkey::default(); MAX_COUNCIL_MEMBERS];
+        council.members[..members.len()].copy_from_slice(&members);
+        council.threshold = threshold;
+        council.proposal_count = 0;
+        council.bump = ctx.bumps.admin_council;
+
+        ctx.accounts.housebox_state.council_enabled = true;
+
+        msg!("Admin council initialized: {}-of-{}", threshold, members.len());
+
+        Ok(())
+    }
+
+    /// Propose a sensitive admin action (council member only). The proposal
+    /// counts the proposer's approval.
+    pub fn propose_council_action(ctx: Context<ProposeCouncilAction>, action: CouncilAction) -> Result<()> {
+        let council = &mut ctx.accounts.admin_council;
+        let index = council.member_index(&ctx.accounts.member.key())
+            .ok_or(HouseboxError::NotCouncilMember)?;
+
+        let proposal = &mut ctx.accounts.council_proposal;
+        proposal.id = council.proposal_count;
+        proposal.action = action;
+        proposal.proposer = ctx.accounts.member.key();
+        proposal.approvals = 1 << index;
+        proposal.executed = false;
+        proposal.created_at = Clock::get()?.unix_timestamp;
+        proposal.bump = ctx.bumps.council_proposal;
+
+        council.proposal_count = council.proposal_count.checked_add(1)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Council proposal {} created", proposal.id);
+
+        Ok(())
+    }
+
+    /// Approve a pending council proposal (council member only).
+    pub fn approve_council_proposal(ctx: Context<ApproveCouncilProposal>, _proposal_id: u64) -> Result<()> {
+        let index = ctx.accounts.admin_council.member_index(&ctx.accounts.member.key())
+            .ok_or(HouseboxError::NotCouncilMember)?;
+
+        let proposal = &mut ctx.accounts.council_proposal;
+        require!(!proposal.executed, HouseboxError::ProposalAlreadyExecuted);
+        proposal.approvals |= 1 << index;
+
+        msg!("Council proposal {} approved ({} approvals)", proposal.id, proposal.approvals.count_ones());
+
+        Ok(())
+    }
+
+    /// Execute a council proposal that has reached the threshold
+    /// (permissionless). Treasury withdrawals pay the destination named in
+    /// the proposal.
+    pub fn execute_council_proposal(ctx: Context<ExecuteCouncilProposal>, _proposal_id: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("execute_council_proposal")?;
+
+        let proposal = &mut ctx.accounts.council_proposal;
+        require!(!proposal.executed, HouseboxError::ProposalAlreadyExecuted);
+        require!(
+            proposal.approvals.count_ones() >= ctx.accounts.admin_council.threshold as u32,
+            HouseboxError::ProposalNotApproved
+        );
+        proposal.executed = true;
+
+        let state = &mut ctx.accounts.housebox_state;
+        match proposal.action {
+            CouncilAction::UpdateServerPubkey { server_pubkey } => {
+                rotate_server_pubkey(
+                    state,
+                    ctx.accounts.executor.key(),
+                    server_pubkey,
+                    Clock::get()?.unix_timestamp,
+                )?;
+            }
+            CouncilAction::AddServerKey { server_key } => {
+                add_server_key_to(state, ctx.accounts.executor.key(), server_key)?;
+            }
+            CouncilAction::RemoveServerKey { server_key } => {
+                remove_server_key_from(state, ctx.accounts.executor.key(), server_key)?;
+            }
+            CouncilAction::UpdateServerKeyGrace { grace_seconds } => {
+                state.require_not_finalized(FINALIZE_CONFIG)?;
+                set_server_key_grace(state, ctx.accounts.executor.key(), grace_seconds)?;
+            }
+            CouncilAction::UpdateFeeRecipient { fee_recipient } => {
+                set_fee_recipient(state, ctx.accounts.executor.key(), fee_recipient)?;
+            }
+            CouncilAction::UpdateLpPercent { lp_percent } => {
+                state.require_not_finalized(FINALIZE_LP_PERCENT)?;
+                require!(lp_percent > 0 && lp_percent <= 100, HouseboxError::InvalidLpPercent);
+                let old = state.lp_percent;
+                emit_parameter_change(
+                    ctx.accounts.executor.key(),
+                    "lp_percent",
+                    state.lp_percent,
+                    lp_percent,
+                )?;
+                state.lp_percent = lp_percent;
+
+                msg!("LP percent updated");
+                msg!("Old: {}%", old);
+                msg!("New: {}%", lp_percent);
+            }
+            CouncilAction::WithdrawTreasury { amount_lamports, destination } => {
+                let destination_account = ctx.accounts.destination.as_ref()
+                    .filter(|account| account.key() == destination)
+                    .ok_or(HouseboxError::InvalidWithdrawalAddress)?;
+                pay_from_treasury(
+                    &ctx.accounts.treasury_vault.to_account_info(),
+                    &destination_account.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    ctx.bumps.treasury_vault,
+                    amount_lamports,
+                )?;
+
+                emit!(TreasuryWithdrawn {
+                    actor: ctx.accounts.executor.key(),
+                    destination,
+                    amount: amount_lamports,
+                    timestamp: Clock::get()?.unix_timestamp,
+                });
+                msg!("Withdrew {} lamports of treasury", amount_lamports);
+            }
+        }
+
+        msg!("Council proposal {} executed", proposal.id);
+
+        Ok(())
+    }
+
+    /// Lengthen the parameter-change timelock (authority only). While it is
+    /// nonzero, server key (primary and additional), lp_percent, rake, and
+    /// settlement fee changes must be scheduled with schedule_parameter_change
+    /// and wait it out, giving LPs time to exit. Shortening it is itself a scheduled change.
+    pub fn update_parameter_timelock(ctx: Context<AdminAction>, timelock_seconds: i64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_parameter_timelock")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            timelock_seconds >= state.parameter_timelock_seconds,
+            HouseboxError::TimelockRequired
+        );
+        let old = state.parameter_timelock_seconds;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "parameter_timelock_seconds",
+            state.parameter_timelock_seconds,
+            timelock_seconds,
+        )?;
+        state.parameter_timelock_seconds = timelock_seconds;
+
+        msg!("Parameter timelock updated");
+        msg!("Old: {} seconds", old);
+        msg!("New: {} seconds", timelock_seconds);
+
+        Ok(())
+    }
+
+    /// Queue a parameter change to apply after parameter_timelock_seconds
+    /// (authority only). Server key and lp_percent changes go through the
+    /// admin council instead when one is set up.
+    pub fn schedule_parameter_change(
+        ctx: Context<ScheduleParameterChange>,
+        change: ParameterChange,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("schedule_parameter_change")?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            ctx.accounts.authority.key() == state.authority,
+            HouseboxError::Unauthorized
+        );
+        validate_parameter_change(state, &change)?;
+
+        let queued = &mut ctx.accounts.queued_change;
+        queued.id = state.parameter_change_count;
+        queued.change = change;
+        queued.execute_after = Clock::get()?.unix_timestamp
+            .checked_add(state.parameter_timelock_seconds)
+            .ok_or(HouseboxError::MathOverflow)?;
+        queued.bump = ctx.bumps.queued_change;
+
+        state.parameter_change_count = state.parameter_change_count.checked_add(1)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Parameter change {} scheduled, executable at {}", queued.id, queued.execute_after);
+
+        Ok(())
+    }
+
+    /// Apply a scheduled parameter change once its timelock has passed
+    /// (permissionless). Rent returns to the authority.
+    pub fn execute_parameter_change(ctx: Context<ExecuteParameterChange>, _change_id: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("execute_parameter_change")?;
+
+        let queued = &ctx.accounts.queued_change;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= queued.execute_after, HouseboxError::TimelockNotElapsed);
+
+        let state = &mut ctx.accounts.housebox_state;
+        validate_parameter_change(state, &queued.change)?;
+        match queued.change {
+            ParameterChange::ServerPubkey { server_pubkey } => {
+                rotate_server_pubkey(state, ctx.accounts.executor.key(), server_pubkey, now)?;
+            }
+            ParameterChange::AddServerKey { server_key } => {
+                add_server_key_to(state, ctx.accounts.executor.key(), server_key)?;
+            }
+            ParameterChange::RemoveServerKey { server_key } => {
+                remove_server_key_from(state, ctx.accounts.executor.key(), server_key)?;
+            }
+            ParameterChange::ServerKeyGrace { grace_seconds } => {
+                set_server_key_grace(state, ctx.accounts.executor.key(), grace_seconds)?;
+            }
+            ParameterChange::FeeRecipient { fee_recipient } => {
+                set_fee_recipient(state, ctx.accounts.executor.key(), fee_recipient)?;
+            }
+            ParameterChange::LpPercent { lp_percent } => {
+                let old = state.lp_percent;
+                emit_parameter_change(
+                    ctx.accounts.executor.key(),
+                    "lp_percent",
+                    state.lp_percent,
+                    lp_percent,
+                )?;
+                state.lp_percent = lp_percent;
+
+                msg!("LP percent updated");
+                msg!("Old: {}%", old);
+                msg!("New: {}%", lp_percent);
+            }
+            ParameterChange::RakeBps { rake_bps } => {
+                let old_bps = state.rake_bps;
+                emit_parameter_change(
+                    ctx.accounts.executor.key(),
+                    "rake_bps",
+                    state.rake_bps,
+                    rake_bps,
+                )?;
+                state.rake_bps = rake_bps;
+
+                msg!("Rake updated");
+                msg!("Old: {} bps", old_bps);
+                msg!("New: {} bps", rake_bps);
+            }
+            ParameterChange::SettlementFee { fee_lamports } => {
+                let old_fee = state.settlement_fee_lamports;
+                emit_parameter_change(
+                    ctx.accounts.executor.key(),
+                    "settlement_fee_lamports",
+                    state.settlement_fee_lamports,
+                    fee_lamports,
+                )?;
+                state.settlement_fee_lamports = fee_lamports;
+
+                msg!("Settlement fee updated");
+                msg!("Old: {} lamports", old_fee);
+                msg!("New: {} lamports", fee_lamports);
+            }
+            ParameterChange::Timelock { timelock_seconds } => {
+                let old = state.parameter_timelock_seconds;
+                emit_parameter_change(
+                    ctx.accounts.executor.key(),
+                    "parameter_timelock_seconds",
+                    state.parameter_timelock_seconds,
+                    timelock_seconds,
+                )?;
+                state.parameter_timelock_seconds = timelock_seconds;
+
+                msg!("Parameter timelock updated");
+                msg!("Old: {} seconds", old);
+                msg!("New: {} seconds", timelock_seconds);
+            }
+        }
+
+        msg!("Parameter change {} executed", queued.id);
+
+        Ok(())
+    }
+
+    /// Cancel a scheduled parameter change (authority only).
+    pub fn cancel_parameter_change(ctx: Context<CancelParameterChange>, _change_id: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("cancel_parameter_change")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        msg!("Parameter change {} cancelled", ctx.accounts.queued_change.id);
+
+        Ok(())
+    }
+
+    /// Create the HouseboxConfig account from the current tunables
+    /// (authority only). From then on update_config is the only way to
+    /// change them and the individual update instructions are disabled.
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        ctx.accounts.log_admin_action("initialize_config")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let config = &mut ctx.accounts.housebox_config;
+        config.params = ConfigParams::from_state(&ctx.accounts.housebox_state);
+        config.updated_at = Clock::get()?.unix_timestamp;
+        config.bump = ctx.bumps.housebox_config;
+
+        ctx.accounts.housebox_state.config_enabled = true;
+
+        msg!("Config initialized");
+
+        Ok(())
+    }
+
+    /// Replace the operational tunables (authority only). The new values are
+    /// validated, stored in HouseboxConfig, and copied into HouseboxState for
+    /// the instructions that read them there; ConfigUpdated records the
+    /// old and new values.
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        ctx.accounts.log_admin_action("update_config")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        params.validate(&ctx.accounts.housebox_state)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.housebox_config;
+        let old = config.params;
+        config.params = params;
+        config.updated_at = now;
+        // Gains so far are charged at the fee in force when they accrued
+        crystallize_performance_fee(&mut ctx.accounts.housebox_state)?;
+        params.apply(&mut ctx.accounts.housebox_state);
+
+        emit!(ConfigUpdated {
+            actor: ctx.accounts.authority.key(),
+            old,
+            new: params,
+            timestamp: now,
+        });
+        msg!("Config updated");
+
+        Ok(())
+    }
+
+    /// Update server signing pubkey. Requires the authority or the operator
+    /// role. With a nonzero server_key_grace_seconds, the old key stays valid
+    /// for that long so in-flight signed settlements and withdrawals still
+    /// land.
+    pub fn update_server_pubkey(
+        ctx: Context<AdminAction>,
+        new_server_pubkey: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_server_pubkey")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+        require!(!ctx.accounts.housebox_state.council_enabled, HouseboxError::CouncilRequired);
+        require!(
+            ctx.accounts.housebox_state.parameter_timelock_seconds == 0,
+            HouseboxError::TimelockRequired
+        );
+
+        rotate_server_pubkey(
+            &mut ctx.accounts.housebox_state,
+            ctx.accounts.authority.key(),
+            new_server_pubkey,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Redirect swept protocol SOL fees to `fee_recipient` (authority only),
+    /// e.g. a new treasury or revenue-share program; default restores the
+    /// treasury vault. Goes through the council or timelock when enabled.
+    pub fn update_fee_recipient(ctx: Context<AdminAction>, fee_recipient: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("update_fee_recipient")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(!ctx.accounts.housebox_state.council_enabled, HouseboxError::CouncilRequired);
+        require!(
+            ctx.accounts.housebox_state.parameter_timelock_seconds == 0,
+            HouseboxError::TimelockRequired
+        );
+
+        set_fee_recipient(
+            &mut ctx.accounts.housebox_state,
+            ctx.accounts.authority.key(),
+            fee_recipient,
+        )
+    }
+
+    /// Update how long a rotated-out server key stays valid. Requires the
+    /// authority or the operator role. Zero makes rotation take effect
+    /// immediately. It keeps old server keys alive, so it goes through the
+    /// council or timelock when enabled.
+    pub fn update_server_key_grace(ctx: Context<AdminAction>, grace_seconds: i64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_server_key_grace")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+        require!(!ctx.accounts.housebox_state.council_enabled, HouseboxError::CouncilRequired);
+        require!(
+            ctx.accounts.housebox_state.parameter_timelock_seconds == 0,
+            HouseboxError::TimelockRequired
+        );
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        set_server_key_grace(
+            &mut ctx.accounts.housebox_state,
+            ctx.accounts.authority.key(),
+            grace_seconds,
+        )
+    }
+
+    /// Authorize an additional server key, e.g. for a redundant settlement
+    /// service or overlapping key maintenance. Requires the authority or the
+    /// operator role.
+    pub fn add_server_key(ctx: Context<AdminAction>, server_key: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("add_server_key")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+        require!(!ctx.accounts.housebox_state.council_enabled, HouseboxError::CouncilRequired);
+        require!(
+            ctx.accounts.housebox_state.parameter_timelock_seconds == 0,
+            HouseboxError::TimelockRequired
+        );
+
+        add_server_key_to(
+            &mut ctx.accounts.housebox_state,
+            ctx.accounts.authority.key(),
+            server_key,
+        )
+    }
+
+    /// Revoke an additional server key. Requires the authority or the operator
+    /// role. The primary server_pubkey is changed with update_server_pubkey.
+    pub fn remove_server_key(ctx: Context<AdminAction>, server_key: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("remove_server_key")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+        require!(!ctx.accounts.housebox_state.council_enabled, HouseboxError::CouncilRequired);
+        require!(
+            ctx.accounts.housebox_state.parameter_timelock_seconds == 0,
+            HouseboxError::TimelockRequired
+        );
+
+        remove_server_key_from(
+            &mut ctx.accounts.housebox_state,
+            ctx.accounts.authority.key(),
+            server_key,
+        )
+    }
+
+    /// Track a game type's exposure and PnL, with a cap on its exposure.
+    /// Requires the authority or the risk manager role. player_settle calls
+    /// carrying the game type then update the bucket, and a win that would push
+    /// the pool's net payout on that game past `max_exposure_lamports` is
+    /// rejected, so one game can't consume the whole pool's risk budget. Zero
+    /// tracks without a cap.
+    pub fn set_game_type_limit(
+        ctx: Context<AdminAction>,
+        game_type: u16,
+        max_exposure_lamports: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("set_game_type_limit")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(game_type != 0, HouseboxError::InvalidGameType);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let index = state.game_types.iter()
+            .position(|bucket| bucket.game_type == game_type)
+            .or_else(|| state.game_types.iter().position(|bucket| bucket.game_type == 0))
+            .ok_or(HouseboxError::GameTypeListFull)?;
+        let bucket = &mut state.game_types[index];
+        let old = bucket.max_exposure;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            &format!("game_types[{}].max_exposure", game_type),
+            old,
+            max_exposure_lamports,
+        )?;
+        bucket.game_type = game_type;
+        bucket.max_exposure = max_exposure_lamports;
+
+        msg!("Game type {} max exposure updated", game_type);
+        msg!("Old: {} lamports", old);
+        msg!("New: {} lamports", max_exposure_lamports);
+
+        Ok(())
+    }
+
+    /// Stop tracking a game type and drop its accounting. Requires the
+    /// authority or the risk manager role.
+    pub fn remove_game_type(ctx: Context<AdminAction>, game_type: u16) -> Result<()> {
+        ctx.accounts.log_admin_action("remove_game_type")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(game_type != 0, HouseboxError::InvalidGameType);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let bucket = state.game_types.iter_mut()
+            .find(|bucket| bucket.game_type == game_type)
+            .ok_or(HouseboxError::InvalidGameType)?;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            &format!("game_types[{}].max_exposure", game_type),
+            bucket.max_exposure,
+            0,
+        )?;
+        *bucket = GameTypeBucket::default();
+
+        msg!("Game type {} removed", game_type);
+
+        Ok(())
+    }
+
+    /// Update the minimum lp_lock amount. Requires the authority or the risk
+    /// manager role. Zero disables the minimum.
+    pub fn update_min_lp_lock(
+        ctx: Context<AdminAction>,
+        min_lp_lock_lamports: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_min_lp_lock")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_min = state.min_lp_lock_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "min_lp_lock_lamports",
+            state.min_lp_lock_lamports,
+            min_lp_lock_lamports,
+        )?;
+        state.min_lp_lock_lamports = min_lp_lock_lamports;
+
+        msg!("Min LP lock updated");
+        msg!("Old: {} lamports", old_min);
+        msg!("New: {} lamports", min_lp_lock_lamports);
+
+        Ok(())
+    }
+
+    /// Whitelist a liquid-staking token for lp_lock_lst (authority only).
+    /// Creates the LST config and the program-owned LST vault. The stake pool
+    /// is the SPL stake pool whose exchange rate values the LST.
+    pub fn whitelist_lst(ctx: Context<WhitelistLst>) -> Result<()> {
+        ctx.accounts.log_admin_action("whitelist_lst")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        // Validate the oracle up front so a bad stake pool can't be whitelisted
+        stake_pool_lamports_for(&ctx.accounts.stake_pool, &ctx.accounts.lst_mint.key(), 0)?;
+
+        let lst_config = &mut ctx.accounts.lst_config;
+        lst_config.lst_mint = ctx.accounts.lst_mint.key();
+        lst_config.stake_pool = ctx.accounts.stake_pool.key();
+        lst_config.lst_vault = ctx.accounts.lst_vault.key();
+        lst_config.enabled = true;
+        lst_config.total_locked = 0;
+        lst_config.bump = ctx.bumps.lst_config;
+        lst_config.sol_value = 0;
+
+        msg!("LST whitelisted: {}", lst_config.lst_mint);
+        msg!("Stake pool: {}", lst_config.stake_pool);
+
+        Ok(())
+    }
+
+    /// Enable or disable lp_lock_lst for a whitelisted LST (authority only).
+    pub fn update_lst_enabled(ctx: Context<UpdateLstConfig>, enabled: bool) -> Result<()> {
+        ctx.accounts.log_admin_action("update_lst_enabled")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let lst_config = &mut ctx.accounts.lst_config;
+        lst_config.enabled = enabled;
+
+        msg!("LST {} {}", lst_config.lst_mint, if enabled { "ENABLED" } else { "DISABLED" });
+
+        Ok(())
+    }
+
+    /// Re-mark a whitelisted LST's locked balance at its stake pool's current
+    /// exchange rate (permissionless crank). Staking rewards, or slashing,
+    /// since the last mark flow into solsum and so into the vToken rate.
+    pub fn mark_lst_value(ctx: Context<MarkLstValue>) -> Result<()> {
+        let old_value = ctx.accounts.lst_config.sol_value;
+        let value = remark_lst_value(
+            &mut ctx.accounts.housebox_state,
+            &mut ctx.accounts.lst_config,
+            &ctx.accounts.stake_pool,
+        )?;
+
+        msg!("LST {} marked at {} lamports (was {})", ctx.accounts.lst_config.lst_mint, value, old_value);
+        msg!("Solsum: {}", ctx.accounts.housebox_state.solsum);
+
+        Ok(())
+    }
+
+    /// Unstake locked LST back to SOL through the stake pool's WithdrawSol
+    /// (authority or treasurer role). The LST is re-marked first; the lamports
+    /// received land in the SOL vault and replace its marked value in solsum,
+    /// so the pool bears any withdrawal fee. Reverts if fewer than
+    /// `min_lamports_out` arrive.
+    pub fn unwind_lst(ctx: Context<UnwindLst>, amount_lst: u64, min_lamports_out: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("unwind_lst")?;
+
+        require!(
+            has_role(
+                &ctx.accounts.housebox_state,
+                &ctx.accounts.authority.key(),
+                ctx.accounts.role.as_deref(),
+                RoleKind::Treasurer,
+            ),
+            HouseboxError::Unauthorized
+        );
+        require!(amount_lst > 0, HouseboxError::ZeroAmount);
+        require!(
+            amount_lst <= ctx.accounts.lst_config.total_locked,
+            HouseboxError::InsufficientLst
+        );
+
+        remark_lst_value(
+            &mut ctx.accounts.housebox_state,
+            &mut ctx.accounts.lst_config,
+            &ctx.accounts.stake_pool,
+        )?;
+        let lst_config = &ctx.accounts.lst_config;
+        let book_value = ((lst_config.sol_value as u128)
+            .checked_mul(amount_lst as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            / lst_config.total_locked as u128) as u64;
+
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let mut data = vec![SPL_STAKE_POOL_WITHDRAW_SOL];
+        data.extend_from_slice(&amount_lst.to_le_bytes());
+        let ix = Instruction {
+            program_id: spl_stake_pool_program::ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.stake_pool.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.stake_pool_withdraw_authority.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.housebox_state.key(), true),
+                AccountMeta::new(ctx.accounts.lst_vault.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_stake.key(), false),
+                AccountMeta::new(ctx.accounts.sol_vault.key(), false),
+                AccountMeta::new(ctx.accounts.manager_fee_account.key(), false),
+                AccountMeta::new(ctx.accounts.lst_mint.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.clock.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.stake_history.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.stake_program.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lst_token_program.key(), false),
+            ],
+            data,
+        };
+
+        let lamports_before = ctx.accounts.sol_vault.lamports();
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+                ctx.accounts.housebox_state.to_account_info(),
+                ctx.accounts.lst_vault.to_account_info(),
+                ctx.accounts.reserve_stake.to_account_info(),
+                ctx.accounts.sol_vault.to_account_info(),
+                ctx.accounts.manager_fee_account.to_account_info(),
+                ctx.accounts.lst_mint.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.stake_history.to_account_info(),
+                ctx.accounts.stake_program.to_account_info(),
+                ctx.accounts.lst_token_program.to_account_info(),
+                ctx.accounts.stake_pool_program.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+        let received = ctx.accounts.sol_vault.lamports().checked_sub(lamports_before)
+            .ok_or(HouseboxError::MathOverflow)?;
+        require!(received >= min_lamports_out, HouseboxError::LstUnwindSlippage);
+
+        let lst_config = &mut ctx.accounts.lst_config;
+        lst_config.total_locked = lst_config.total_locked.checked_sub(amount_lst)
+            .ok_or(HouseboxError::MathOverflow)?;
+        lst_config.sol_value = lst_config.sol_value.checked_sub(book_value)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.lst_sol_value = state.lst_sol_value.checked_sub(book_value)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.solsum = state.solsum.checked_sub(book_value)
+            .and_then(|solsum| solsum.checked_add(received))
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Unwound {} LST: {} lamports received (marked at {})", amount_lst, received, book_value);
+        msg!("Solsum: {}, LST value: {}", state.solsum, state.lst_sol_value);
+
+        Ok(())
+    }
+
+    /// Set the vToken mint's Metaplex metadata (authority only).
+    /// Creates the metadata account on first call and updates it afterwards.
+    /// housebox_state is both mint authority and metadata update authority.
+    pub fn set_vtoken_metadata(
+        ctx: Context<SetVtokenMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("set_vtoken_metadata")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(
+            name.len() <= MAX_METADATA_NAME_LEN
+                && symbol.len() <= MAX_METADATA_SYMBOL_LEN
+                && uri.len() <= MAX_METADATA_URI_LEN,
+            HouseboxError::MetadataTooLong
+        );
+
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let metadata = &ctx.accounts.metadata;
+        let state_key = ctx.accounts.housebox_state.key();
+        let creating = metadata.data_is_empty();
+
+        let ix = if creating {
+            Instruction {
+                program_id: token_metadata_program::ID,
+                accounts: vec![
+                    AccountMeta::new(metadata.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.vtoken_mint.key(), false),
+                    AccountMeta::new_readonly(state_key, true),
+                    AccountMeta::new(ctx.accounts.authority.key(), true),
+                    AccountMeta::new_readonly(state_key, true),
+                    AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                ],
+                data: metadata_instruction_data(
+                    METADATA_CREATE_V3_DISCRIMINATOR,
+                    &name,
+                    &symbol,
+                    &uri,
+                )?,
+            }
+        } else {
+            Instruction {
+                program_id: token_metadata_program::ID,
+                accounts: vec![
+                    AccountMeta::new(metadata.key(), false),
+                    AccountMeta::new_readonly(state_key, true),
+                ],
+                data: metadata_instruction_data(
+                    METADATA_UPDATE_V2_DISCRIMINATOR,
+                    &name,
+                    &symbol,
+                    &uri,
+                )?,
+            }
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                metadata.to_account_info(),
+                ctx.accounts.vtoken_mint.to_account_info(),
+                ctx.accounts.housebox_state.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        msg!("vToken metadata {}", if creating { "created" } else { "updated" });
+        msg!("Name: {}, Symbol: {}", name, symbol);
+        msg!("URI: {}", uri);
+
+        Ok(())
+    }
+
+    /// Sync the Token-2022 vToken interest rate to the pool exchange rate.
+    /// Permissionless crank. Sets the annualized rate implied by the exchange
+    /// rate's growth since the previous sync, so wallet-displayed balances
+    /// track solsum/vsum (lagging by one sync period).
+    pub fn sync_vtoken_rate(ctx: Context<SyncVtokenRate>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &ctx.accounts.housebox_state;
+        require!(state.vsum > 0, HouseboxError::NoLiquidity);
+
+        let exchange_rate = exchange_rate_of(state)?;
+        let elapsed = now - state.rate_synced_at;
+
+        let rate_bps: i16 = if state.rate_synced_at == 0 || state.rate_sync_exchange_rate == 0 {
+            // First sync only records the baseline
+            0
+        } else {
+            require!(
+                elapsed >= RATE_SYNC_MIN_INTERVAL_SECONDS,
+                HouseboxError::RateSyncTooSoon
+            );
+            // Continuously compounded: ln(growth) * year / elapsed, in bps, rounded
+            let growth_ln = ln_ratio(exchange_rate, state.rate_sync_exchange_rate)?;
+            let numerator = growth_ln
+                .checked_mul(SECONDS_PER_YEAR as i128 * BPS_DENOMINATOR as i128)
+                .ok_or(HouseboxError::MathOverflow)?;
+            let denominator = elapsed as i128 * LN_SCALE as i128;
+            let annualized = (numerator + numerator.signum() * (denominator / 2)) / denominator;
+            annualized.clamp(i16::MIN as i128, i16::MAX as i128) as i16
+        };
+
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        invoke_signed(
+            &spl_token_2022::extension::interest_bearing_mint::instruction::update_rate(
+                &token_2022::ID,
+                &ctx.accounts.vtoken_mint.key(),
+                &ctx.accounts.housebox_state.key(),
+                &[],
+                rate_bps,
+            )?,
+            &[
+                ctx.accounts.vtoken_mint.to_account_info(),
+                ctx.accounts.housebox_state.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.rate_sync_exchange_rate = exchange_rate;
+        state.rate_synced_at = now;
+
+        msg!("vToken rate synced: {} bps", rate_bps);
+        msg!("Exchange rate: {} (scaled 1e9)", exchange_rate);
+
+        Ok(())
+    }
+
+    /// Update the lamport fee for extend_redemption. Requires the authority or
+    /// the treasurer role. Zero makes extensions free.
+    pub fn update_redemption_extension_fee(
+        ctx: Context<AdminAction>,
+        fee_lamports: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_redemption_extension_fee")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_fee = state.redemption_extension_fee_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "redemption_extension_fee_lamports",
+            state.redemption_extension_fee_lamports,
+            fee_lamports,
+        )?;
+        state.redemption_extension_fee_lamports = fee_lamports;
+
+        msg!("Redemption extension fee updated");
+        msg!("Old: {} lamports", old_fee);
+        msg!("New: {} lamports", fee_lamports);
+
+        Ok(())
+    }
+
+    /// Enable or disable permissioned LP mode. Requires the authority or the
+    /// risk manager role. When enabled, lp_lock and lp_lock_lst require the
+    /// LP's LpWhitelist PDA.
+    pub fn update_lp_whitelist_enabled(ctx: Context<AdminAction>, enabled: bool) -> Result<()> {
+        ctx.accounts.log_admin_action("update_lp_whitelist_enabled")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "lp_whitelist_enabled",
+            state.lp_whitelist_enabled,
+            enabled,
+        )?;
+        state.lp_whitelist_enabled = enabled;
+
+        msg!("LP whitelist {}", if enabled { "ENABLED" } else { "DISABLED" });
+
+        Ok(())
+    }
+
+    /// Approve an LP for permissioned mode (authority only).
+    pub fn add_lp_whitelist(ctx: Context<AddLpWhitelist>, lp: Pubkey) -> Result<()> {
+        ctx.accounts.log_admin_action("add_lp_whitelist")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let entry = &mut ctx.accounts.lp_whitelist;
+        entry.lp = lp;
+        entry.added_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.lp_whitelist;
+
+        msg!("LP whitelisted: {}", lp);
+
+        Ok(())
+    }
+
+    /// Revoke an LP's whitelist entry (authority only). Rent returns to the authority.
+    /// Existing vTokens are unaffected; the LP just can't lock more.
+    pub fn remove_lp_whitelist(ctx: Context<RemoveLpWhitelist>) -> Result<()> {
+        ctx.accounts.log_admin_action("remove_lp_whitelist")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        msg!("LP removed from whitelist: {}", ctx.accounts.lp_whitelist.lp);
+
+        Ok(())
+    }
+
+    /// Create the protocol vesting vault and enable vesting (authority only).
+    /// While vesting is enabled, lp_lock mints the protocol haircut into the
+    /// vault and it unlocks linearly over `period_seconds`.
+    pub fn initialize_protocol_vesting(
+        ctx: Context<InitializeProtocolVesting>,
+        period_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("initialize_protocol_vesting")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(period_seconds > 0, HouseboxError::InvalidVestingPeriod);
+
+        let state = &mut ctx.accounts.housebox_state;
+        state.protocol_vesting_period_seconds = period_seconds;
+        state.protocol_vesting_checkpoint = Clock::get()?.unix_timestamp;
+
+        msg!("Protocol vesting initialized: {}", ctx.accounts.protocol_vesting_vault.key());
+        msg!("Vesting period: {} seconds", period_seconds);
+
+        Ok(())
+    }
+
+    /// Update the protocol vesting period. Requires the authority or the
+    /// treasurer role. Zero disables vesting for new locks; already-locked
+    /// vTokens keep their schedule.
+    pub fn update_protocol_vesting_period(
+        ctx: Context<AdminAction>,
+        period_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_protocol_vesting_period")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        require!(period_seconds >= 0, HouseboxError::InvalidVestingPeriod);
+
+        let state = &mut ctx.accounts.housebox_state;
+        checkpoint_protocol_vesting(state, Clock::get()?.unix_timestamp)?;
+        let old_period = state.protocol_vesting_period_seconds;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "protocol_vesting_period_seconds",
+            state.protocol_vesting_period_seconds,
+            period_seconds,
+        )?;
+        state.protocol_vesting_period_seconds = period_seconds;
+
+        msg!("Protocol vesting period updated");
+        msg!("Old: {} seconds", old_period);
+        msg!("New: {} seconds", period_seconds);
+
+        Ok(())
+    }
+
+    /// Move vested protocol vTokens from the vesting vault to the protocol account.
+    /// Permissionless — the destination is fixed.
+    pub fn claim_vested_protocol_vtokens(ctx: Context<ClaimVestedProtocolVtokens>) -> Result<()> {
+        let state = &mut ctx.accounts.housebox_state;
+        checkpoint_protocol_vesting(state, Clock::get()?.unix_timestamp)?;
+
+        let amount = state.protocol_vesting_unlocked;
+        require!(amount > 0, HouseboxError::ZeroAmount);
+        state.protocol_vesting_unlocked = 0;
+        state.protocol_vesting_balance = state.protocol_vesting_balance.checked_sub(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.protocol_vesting_vault.to_account_info(),
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                    to: ctx.accounts.protocol_vtoken_account.to_account_info(),
+                    authority: ctx.accounts.housebox_state.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.vtoken_mint.decimals,
+        )?;
+
+        msg!("Claimed {} vested protocol vTokens", amount);
+        msg!("Still vesting: {}", ctx.accounts.housebox_state.protocol_vesting_balance);
+
+        Ok(())
+    }
+
+    /// Update the referrer's share of the protocol haircut in bps. Requires the
+    /// authority or the treasurer role.
+    pub fn update_lp_referral_bps(ctx: Context<AdminAction>, referral_bps: u16) -> Result<()> {
+        ctx.accounts.log_admin_action("update_lp_referral_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        require!(referral_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_bps = state.lp_referral_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "lp_referral_bps",
+            state.lp_referral_bps,
+            referral_bps,
+        )?;
+        state.lp_referral_bps = referral_bps;
+
+        msg!("LP referral share updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", referral_bps);
+
+        Ok(())
+    }
+
+    /// Configure the utilization-dependent haircut. Requires the authority or
+    /// the risk manager role. Below `tvl_target_lamports` of solsum, new LPs
+    /// get up to `max_lp_percent`, decaying linearly to lp_percent as the pool
+    /// fills. A zero target disables it.
+    pub fn update_dynamic_haircut(
+        ctx: Context<AdminAction>,
+        max_lp_percent: u8,
+        tvl_target_lamports: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_dynamic_haircut")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_LP_PERCENT)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            max_lp_percent >= state.lp_percent && max_lp_percent <= 100,
+            HouseboxError::InvalidLpPercent
+        );
+
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "dynamic_lp_percent_max",
+            state.dynamic_lp_percent_max,
+            max_lp_percent,
+        )?;
+        state.dynamic_lp_percent_max = max_lp_percent;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "dynamic_tvl_target_lamports",
+            state.dynamic_tvl_target_lamports,
+            tvl_target_lamports,
+        )?;
+        state.dynamic_tvl_target_lamports = tvl_target_lamports;
+
+        msg!("Dynamic haircut updated");
+        msg!("Max LP percent: {}%", max_lp_percent);
+        msg!("TVL target: {} lamports", tvl_target_lamports);
+
+        Ok(())
+    }
+
+    /// Update the performance fee on pool gains above the high-water rate.
+    /// Requires the authority or the treasurer role. Gains accrued so far are
+    /// charged at the old fee first.
+    pub fn update_performance_fee_bps(ctx: Context<AdminAction>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.log_admin_action("update_performance_fee_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+        require!(fee_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+
+        let state = &mut ctx.accounts.housebox_state;
+        crystallize_performance_fee(state)?;
+        let old_bps = state.performance_fee_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "performance_fee_bps",
+            state.performance_fee_bps,
+            fee_bps,
+        )?;
+        state.performance_fee_bps = fee_bps;
+
+        msg!("Performance fee updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", fee_bps);
+
+        Ok(())
+    }
+
+    /// Configure the early-exit redemption penalty. Requires the authority or
+    /// the risk manager role. Redeeming right after a lock costs `penalty_bps`
+    /// of the payout, decaying linearly to zero over `decay_seconds`; the
+    /// penalty stays in the pool. While non-zero, locks and redemptions require
+    /// the LP's position account.
+    pub fn update_early_exit_penalty(
+        ctx: Context<AdminAction>,
+        penalty_bps: u16,
+        decay_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_early_exit_penalty")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(penalty_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+        require!(
+            decay_seconds > 0 || penalty_bps == 0,
+            HouseboxError::InvalidPenaltyDecay
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "early_exit_penalty_bps",
+            state.early_exit_penalty_bps,
+            penalty_bps,
+        )?;
+        state.early_exit_penalty_bps = penalty_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "early_exit_decay_seconds",
+            state.early_exit_decay_seconds,
+            decay_seconds,
+        )?;
+        state.early_exit_decay_seconds = decay_seconds;
+
+        msg!("Early-exit penalty updated");
+        msg!("Penalty: {} bps, decaying over {} seconds", penalty_bps, decay_seconds);
+
+        Ok(())
+    }
+
+    /// Update the streaming redemption window in seconds. Requires the
+    /// authority or the risk manager role. Zero pays every redemption as a lump
+    /// sum; existing streams keep their schedule.
+    pub fn update_redemption_stream_seconds(
+        ctx: Context<AdminAction>,
+        stream_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_redemption_stream_seconds")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(stream_seconds >= 0, HouseboxError::InvalidVestingPeriod);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_seconds = state.redemption_stream_seconds;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "redemption_stream_seconds",
+            state.redemption_stream_seconds,
+            stream_seconds,
+        )?;
+        state.redemption_stream_seconds = stream_seconds;
+
+        msg!("Redemption stream window updated");
+        msg!("Old: {} seconds", old_seconds);
+        msg!("New: {} seconds", stream_seconds);
+
+        Ok(())
+    }
+
+    /// Update the per-session house risk limit on player wins. Requires the
+    /// authority or the risk manager role. Applies to every settlement path.
+    /// Zero removes the limit.
+    pub fn update_max_settlement_pnl(
+        ctx: Context<AdminAction>,
+        max_settlement_pnl: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_max_settlement_pnl")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_max = state.max_settlement_pnl;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "max_settlement_pnl",
+            state.max_settlement_pnl,
+            max_settlement_pnl,
+        )?;
+        state.max_settlement_pnl = max_settlement_pnl;
+
+        msg!("Max settlement PnL updated");
+        msg!("Old: {} lamports", old_max);
+        msg!("New: {} lamports", max_settlement_pnl);
+
+        Ok(())
+    }
+
+    /// Update the rake taken from player losses into the treasury, in bps.
+    /// Requires the authority or the treasurer role.
+    pub fn update_rake_bps(ctx: Context<AdminAction>, rake_bps: u16) -> Result<()> {
+        ctx.accounts.log_admin_action("update_rake_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_RAKE)?;
+        require!(rake_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+        require!(
+            ctx.accounts.housebox_state.parameter_timelock_seconds == 0,
+            HouseboxError::TimelockRequired
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_bps = state.rake_bps;
+        emit_parameter_change(ctx.accounts.authority.key(), "rake_bps", state.rake_bps, rake_bps)?;
+        state.rake_bps = rake_bps;
+
+        msg!("Rake updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", rake_bps);
+
+        Ok(())
+    }
+
+    /// Configure the deposit-match promotion (authority or treasurer role). Deposits of at
+    /// least `min_deposit_lamports` receive `match_bps` of the deposit as bonus
+    /// balance, paid by redeeming protocol-held vTokens, until
+    /// `budget_lamports` is spent. The bonus must be wagered
+    /// `wagering_multiplier` times before it can be withdrawn. A zero
+    /// match_bps ends the promotion.
+    pub fn configure_deposit_match(
+        ctx: Context<AdminAction>,
+        match_bps: u16,
+        budget_lamports: u64,
+        min_deposit_lamports: u64,
+        wagering_multiplier: u16,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("configure_deposit_match")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        require!(match_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "deposit_match_bps",
+            state.deposit_match_bps,
+            match_bps,
+        )?;
+        state.deposit_match_bps = match_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "deposit_match_budget_lamports",
+            state.deposit_match_budget_lamports,
+            budget_lamports,
+        )?;
+        state.deposit_match_budget_lamports = budget_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "deposit_match_min_lamports",
+            state.deposit_match_min_lamports,
+            min_deposit_lamports,
+        )?;
+        state.deposit_match_min_lamports = min_deposit_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "deposit_match_wagering_multiplier",
+            state.deposit_match_wagering_multiplier,
+            wagering_multiplier,
+        )?;
+        state.deposit_match_wagering_multiplier = wagering_multiplier;
+
+        msg!("Deposit match configured");
+        msg!("Match: {} bps, budget: {} lamports", match_bps, budget_lamports);
+        msg!("Min deposit: {} lamports, wagering: {}x", min_deposit_lamports, wagering_multiplier);
+
+        Ok(())
+    }
+
+    /// Set the pool-funded budget grant_bonus draws from. Requires the
+    /// authority or the treasurer role. Each grant spends it down; zero stops
+    /// new bonuses.
+    pub fn update_bonus_budget(ctx: Context<AdminAction>, budget_lamports: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_bonus_budget")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_budget = state.bonus_budget_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "bonus_budget_lamports",
+            old_budget,
+            budget_lamports,
+        )?;
+        state.bonus_budget_lamports = budget_lamports;
+
+        msg!("Bonus budget updated");
+        msg!("Old: {} lamports", old_budget);
+        msg!("New: {} lamports", budget_lamports);
+
+        Ok(())
+    }
+
+    /// Update the per-settlement operator fee in lamports. Requires the
+    /// authority or the treasurer role. Zero disables the fee.
+    pub fn update_settlement_fee(ctx: Context<AdminAction>, fee_lamports: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_settlement_fee")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_SETTLEMENT_FEE)?;
+        require!(
+            ctx.accounts.housebox_state.parameter_timelock_seconds == 0,
+            HouseboxError::TimelockRequired
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_fee = state.settlement_fee_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "settlement_fee_lamports",
+            state.settlement_fee_lamports,
+            fee_lamports,
+        )?;
+        state.settlement_fee_lamports = fee_lamports;
+
+        msg!("Settlement fee updated");
+        msg!("Old: {} lamports", old_fee);
+        msg!("New: {} lamports", fee_lamports);
+
+        Ok(())
+    }
+
+    /// Update the share of rake that feeds the jackpot, in bps. Requires the
+    /// authority or the treasurer role.
+    pub fn update_jackpot_contribution_bps(
+        ctx: Context<AdminAction>,
+        contribution_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_jackpot_contribution_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_RAKE)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            contribution_bps as u64
+                + state.insurance_contribution_bps as u64
+                + state.escrow_yield_bps as u64
+                + state.stake_rake_bps as u64
+                <= BPS_DENOMINATOR,
+            HouseboxError::InvalidBps
+        );
+        let old_bps = state.jackpot_contribution_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "jackpot_contribution_bps",
+            state.jackpot_contribution_bps,
+            contribution_bps,
+        )?;
+        state.jackpot_contribution_bps = contribution_bps;
+
+        msg!("Jackpot contribution updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", contribution_bps);
+
+        Ok(())
+    }
+
+    /// Update the share of rake that feeds the insurance fund, in bps. Requires
+    /// the authority or the treasurer role. Together with the jackpot share it
+    /// can't exceed 100%.
+    pub fn update_insurance_contribution_bps(
+        ctx: Context<AdminAction>,
+        contribution_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_insurance_contribution_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_RAKE)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            contribution_bps as u64
+                + state.jackpot_contribution_bps as u64
+                + state.escrow_yield_bps as u64
+                + state.stake_rake_bps as u64
+                <= BPS_DENOMINATOR,
+            HouseboxError::InvalidBps
+        );
+        let old_bps = state.insurance_contribution_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "insurance_contribution_bps",
+            state.insurance_contribution_bps,
+            contribution_bps,
+        )?;
+        state.insurance_contribution_bps = contribution_bps;
+
+        msg!("Insurance contribution updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", contribution_bps);
+
+        Ok(())
+    }
+
+    /// Update the share of rake (bps) paid as yield to opted-in escrow
+    /// balances. Requires the authority or the treasurer role. Shares of rake
+    /// for the jackpot, insurance fund, escrow yield, and stakers can't exceed
+    /// 100% combined.
+    pub fn update_escrow_yield_bps(ctx: Context<AdminAction>, yield_bps: u16) -> Result<()> {
+        ctx.accounts.log_admin_action("update_escrow_yield_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_RAKE)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            yield_bps as u64
+                + state.jackpot_contribution_bps as u64
+                + state.insurance_contribution_bps as u64
+                + state.stake_rake_bps as u64
+                <= BPS_DENOMINATOR,
+            HouseboxError::InvalidBps
+        );
+        let old_bps = state.escrow_yield_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "escrow_yield_bps",
+            state.escrow_yield_bps,
+            yield_bps,
+        )?;
+        state.escrow_yield_bps = yield_bps;
+
+        msg!("Escrow yield share updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", yield_bps);
+
+        Ok(())
+    }
+
+    /// Update the share of rake (bps) boosted to vToken stakers through the
+    /// staking reward accumulator. Requires the authority or the treasurer
+    /// role. Shares of rake for the jackpot, insurance fund, escrow yield, and
+    /// stakers can't exceed 100% combined.
+    pub fn update_stake_rake_bps(ctx: Context<AdminAction>, stake_bps: u16) -> Result<()> {
+        ctx.accounts.log_admin_action("update_stake_rake_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_RAKE)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            stake_bps as u64
+                + state.jackpot_contribution_bps as u64
+                + state.insurance_contribution_bps as u64
+                + state.escrow_yield_bps as u64
+                <= BPS_DENOMINATOR,
+            HouseboxError::InvalidBps
+        );
+        let old_bps = state.stake_rake_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "stake_rake_bps",
+            state.stake_rake_bps,
+            stake_bps,
+        )?;
+        state.stake_rake_bps = stake_bps;
+
+        msg!("Staking rake share updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", stake_bps);
+
+        Ok(())
+    }
+
+    /// Update the referrer's share of player rake in bps. Requires the
+    /// authority or the treasurer role.
+    pub fn update_player_referral_bps(ctx: Context<AdminAction>, referral_bps: u16) -> Result<()> {
+        ctx.accounts.log_admin_action("update_player_referral_bps")?;
+
+        ctx.accounts.require_role(RoleKind::Treasurer)?;
+        require!(referral_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_bps = state.player_referral_bps;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "player_referral_bps",
+            state.player_referral_bps,
+            referral_bps,
+        )?;
+        state.player_referral_bps = referral_bps;
+
+        msg!("Player referral share updated");
+        msg!("Old: {} bps", old_bps);
+        msg!("New: {} bps", referral_bps);
+
+        Ok(())
+    }
+
+    /// Update the settlement dispute window and the key that resolves disputes.
+    /// Requires the authority or the operator role. A zero window disables
+    /// disputes.
+    pub fn update_dispute_config(
+        ctx: Context<AdminAction>,
+        window_seconds: i64,
+        dispute_authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_dispute_config")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+        require!(window_seconds >= 0, HouseboxError::InvalidDisputeWindow);
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "dispute_window_seconds",
+            state.dispute_window_seconds,
+            window_seconds,
+        )?;
+        state.dispute_window_seconds = window_seconds;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "dispute_authority",
+            state.dispute_authority,
+            dispute_authority,
+        )?;
+        state.dispute_authority = dispute_authority;
+
+        msg!("Dispute window: {} seconds", window_seconds);
+        msg!("Dispute authority: {}", dispute_authority);
+
+        Ok(())
+    }
+
+    /// Update the solsum floor below which a settlement pauses the protocol.
+    /// Requires the authority or the risk manager role. Zero disables the
+    /// circuit breaker.
+    pub fn update_solvency_floor(ctx: Context<AdminAction>, floor_lamports: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_solvency_floor")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_floor = state.solvency_floor_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "solvency_floor_lamports",
+            state.solvency_floor_lamports,
+            floor_lamports,
+        )?;
+        state.solvency_floor_lamports = floor_lamports;
+
+        msg!("Solvency floor updated");
+        msg!("Old: {} lamports", old_floor);
+        msg!("New: {} lamports", floor_lamports);
+
+        Ok(())
+    }
+
+    /// Update the rolling 24h cap on server-approved withdrawals per escrow.
+    /// Requires the authority or the risk manager role. Zero disables the cap.
+    pub fn update_daily_withdrawal_limit(ctx: Context<AdminAction>, limit_lamports: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_daily_withdrawal_limit")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_limit = state.daily_withdrawal_limit_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "daily_withdrawal_limit_lamports",
+            state.daily_withdrawal_limit_lamports,
+            limit_lamports,
+        )?;
+        state.daily_withdrawal_limit_lamports = limit_lamports;
+
+        msg!("Daily withdrawal limit updated");
+        msg!("Old: {} lamports", old_limit);
+        msg!("New: {} lamports", limit_lamports);
+
+        Ok(())
+    }
+
+    /// Configure the deposit attestation gate (authority or operator role). Deposits above
+    /// `threshold_lamports` must carry an active gateway token from
+    /// `gatekeeper_network` issued to the player. A zero threshold turns
+    /// compliance mode off.
+    pub fn update_attestation_config(
+        ctx: Context<AdminAction>,
+        threshold_lamports: u64,
+        gatekeeper_network: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("update_attestation_config")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "attestation_threshold_lamports",
+            state.attestation_threshold_lamports,
+            threshold_lamports,
+        )?;
+        state.attestation_threshold_lamports = threshold_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "attestation_gatekeeper_network",
+            state.attestation_gatekeeper_network,
+            gatekeeper_network,
+        )?;
+        state.attestation_gatekeeper_network = gatekeeper_network;
+
+        msg!("Attestation config updated");
+        msg!("Threshold: {} lamports", threshold_lamports);
+        msg!("Gatekeeper network: {}", gatekeeper_network);
+
+        Ok(())
+    }
+
+    /// Update the cap on a single server-approved withdrawal. Requires the
+    /// authority or the risk manager role. Zero disables the cap.
+    pub fn update_max_withdrawal(ctx: Context<AdminAction>, max_lamports: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_max_withdrawal")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_max = state.max_withdrawal_lamports;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "max_withdrawal_lamports",
+            state.max_withdrawal_lamports,
+            max_lamports,
+        )?;
+        state.max_withdrawal_lamports = max_lamports;
+
+        msg!("Max withdrawal updated");
+        msg!("Old: {} lamports", old_max);
+        msg!("New: {} lamports", max_lamports);
+
+        Ok(())
+    }
+
+    /// Update how long a session may stay open before anyone can expire it at
+    /// its cumulative partial PnL. Requires the authority or the operator role.
+    /// Zero disables expiry.
+    pub fn update_session_timeout(ctx: Context<AdminAction>, timeout_seconds: i64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_session_timeout")?;
+
+        ctx.accounts.require_role(RoleKind::Operator)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+        require!(timeout_seconds >= 0, HouseboxError::InvalidSessionTimeout);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_timeout = state.session_timeout_seconds;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "session_timeout_seconds",
+            state.session_timeout_seconds,
+            timeout_seconds,
+        )?;
+        state.session_timeout_seconds = timeout_seconds;
+
+        msg!("Session timeout updated");
+        msg!("Old: {} seconds", old_timeout);
+        msg!("New: {} seconds", timeout_seconds);
+
+        Ok(())
+    }
+
+    /// Update the cap on total outstanding vTokens. Requires the authority or
+    /// the risk manager role. Zero removes the cap. Lowering it below vsum only
+    /// blocks new locks.
+    pub fn update_max_vsum(ctx: Context<AdminAction>, max_vsum: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("update_max_vsum")?;
+
+        ctx.accounts.require_role(RoleKind::RiskManager)?;
+        require!(!ctx.accounts.housebox_state.config_enabled, HouseboxError::ConfigManaged);
+        ctx.accounts.housebox_state.require_not_finalized(FINALIZE_CONFIG)?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        let old_max = state.max_vsum;
+        emit_parameter_change(ctx.accounts.authority.key(), "max_vsum", state.max_vsum, max_vsum)?;
+        state.max_vsum = max_vsum;
+
+        msg!("Max vsum updated");
+        msg!("Old: {}", old_max);
+        msg!("New: {}", max_vsum);
+
+        Ok(())
+    }
+
+    /// Create the exchange-rate TWAP account (authority only).
+    pub fn initialize_rate_twap(ctx: Context<InitializeRateTwap>) -> Result<()> {
+        ctx.accounts.log_admin_action("initialize_rate_twap")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let twap = &mut ctx.accounts.rate_twap;
+        twap.cumulative_rate = 0;
+        twap.last_rate = current_exchange_rate(&ctx.accounts.housebox_state)?;
+        twap.last_updated_at = now;
+        twap.observation_index = 0;
+        twap.observations = [TwapObservation::default(); TWAP_OBSERVATIONS];
+        twap.observations[0] = TwapObservation { timestamp: now, cumulative_rate: 0 };
+        twap.bump = ctx.bumps.rate_twap;
+
+        msg!("Rate TWAP initialized at rate {} (scaled 1e9)", twap.last_rate);
+
+        Ok(())
+    }
+
+    /// Accumulate the time-weighted exchange rate (permissionless crank).
+    /// The rate held since the previous update is weighted by elapsed time,
+    /// so a same-slot solsum/vsum manipulation only affects future intervals.
+    pub fn update_rate_twap(ctx: Context<UpdateRateTwap>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let twap = &mut ctx.accounts.rate_twap;
+        let elapsed = now - twap.last_updated_at;
+        require!(elapsed >= TWAP_MIN_INTERVAL_SECONDS, HouseboxError::TwapUpdateTooSoon);
+
+        twap.cumulative_rate = twap.cumulative_rate
+            .checked_add(
+                (twap.last_rate as u128)
+                    .checked_mul(elapsed as u128)
+                    .ok_or(HouseboxError::MathOverflow)?,
+            )
+            .ok_or(HouseboxError::MathOverflow)?;
+        twap.last_rate = current_exchange_rate(&ctx.accounts.housebox_state)?;
+        twap.last_updated_at = now;
+
+        let index = (twap.observation_index as usize + 1) % TWAP_OBSERVATIONS;
+        twap.observations[index] = TwapObservation {
+            timestamp: now,
+            cumulative_rate: twap.cumulative_rate,
+        };
+        twap.observation_index = index as u8;
+
+        msg!("Rate TWAP updated: rate {} (scaled 1e9), cumulative {}", twap.last_rate, twap.cumulative_rate);
+
+        Ok(())
+    }
+
+    /// View: an LP's share of the pool (bps of vsum) and the SOL value of their
+    /// vToken balance at the current ratio, returned via return data.
+    pub fn get_lp_share(ctx: Context<GetLpShare>) -> Result<LpShare> {
+        let state = &ctx.accounts.housebox_state;
+        let vtokens = ctx.accounts.lp_vtoken_account.amount;
+
+        let (share_bps, sol_value) = if state.vsum == 0 {
+            (0, 0)
+        } else {
+            let share_bps = (vtokens as u128)
+                .checked_mul(BPS_DENOMINATOR as u128)
+                .ok_or(HouseboxError::MathOverflow)?
+                / state.vsum as u128;
+            let sol_value = (vtokens as u128)
+                .checked_mul(state.solsum as u128)
+                .ok_or(HouseboxError::MathOverflow)?
+                / state.vsum as u128;
+            (share_bps as u64, sol_value as u64)
+        };
+
+        msg!("LP share: {} vTokens, {} bps, {} lamports", vtokens, share_bps, sol_value);
+
+        Ok(LpShare { vtokens, share_bps, sol_value })
+    }
+
+    /// View: a settled session's PnL and the escrow balance it left, plus its
+    /// dispute state, returned via return data.
+    pub fn get_settlement(ctx: Context<GetSettlement>, _session_id: [u8; 32]) -> Result<SettlementRecord> {
+        let settled = &ctx.accounts.settled_session;
+
+        msg!("Settlement: PnL {}, escrow balance {}", settled.pnl, settled.escrow_balance);
+
+        Ok(SettlementRecord {
+            player: settled.player,
+            settled_at: settled.settled_at,
+            pnl: settled.pnl,
+            escrow_balance: settled.escrow_balance,
+            asset_mint: settled.asset_mint,
+            disputed: settled.disputed,
+            reversed: settled.reversed,
+        })
+    }
+
+    /// View: whether a player_settle of `pnl` for this player would pass
+    /// right now (escrow sufficient, house solvent, player and game limits
+    /// respected), returned via return data, so the server can validate a
+    /// batch before paying for transactions that would fail.
+    pub fn check_settlement(
+        ctx: Context<CheckSettlement>,
+        pnl: i64,
+        game_type: u16,
+    ) -> Result<SettlementCheck> {
+        let now = Clock::get()?.unix_timestamp;
+        let mut escrow = (*ctx.accounts.player_escrow).clone();
+        roll_player_limits(&mut escrow, now);
+
+        let error_code = settlement_check_error(&ctx.accounts.housebox_state, &escrow, pnl, game_type, now)
+            .map_or(0, u32::from);
+
+        msg!("Settlement check: PnL {}, error code {}", pnl, error_code);
+
+        Ok(SettlementCheck {
+            ok: error_code == 0,
+            error_code,
+        })
+    }
+
+    /// LP cancels their pending redemption request before it executes,
+    /// reclaiming its rent. No tokens or pool accounting move.
+    pub fn cancel_redemption(ctx: Context<CancelRedemption>) -> Result<()> {
+        let request = &ctx.accounts.redemption_request;
+        msg!("Redemption request cancelled (was ready at {})", request.ready_at());
+        Ok(())
+    }
+
+    /// Close an expired redemption request PDA to reclaim rent.
+    /// Permissionless — anyone can call. Rent returns to the LP.
+    pub fn close_expired_redemption(ctx: Context<CloseExpiredRedemption>) -> Result<()> {
+        let request = &ctx.accounts.redemption_request;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now > request.expires_at(), HouseboxError::RedemptionNotExpired);
+        msg!("Closed expired redemption request, rent returned to LP");
+        Ok(())
+    }
+
+    /// Close a settled session PDA to reclaim rent.
+    /// Only the server can call this, and only after the session is at least 1 hour old.
+    pub fn close_settled_session(
+        ctx: Context<CloseSettledSession>,
+        _session_id: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        check_settled_session_closable(
+            &ctx.accounts.housebox_state,
+            &ctx.accounts.settled_session,
+            now,
+        )?;
+        msg!("Closed settled session, rent reclaimed");
+        Ok(())
+    }
+
+    /// Close many aged settled session PDAs in one transaction (server only),
+    /// passed as remaining_accounts (mut). Each must pass the same checks as
+    /// close_settled_session; rent goes to the server signer.
+    pub fn close_settled_sessions_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseSettledSessionsBatch<'info>>,
+    ) -> Result<()> {
+        let remaining = ctx.remaining_accounts;
+        require!(!remaining.is_empty(), HouseboxError::InvalidBatchAccounts);
+
+        let now = Clock::get()?.unix_timestamp;
+        for settled_info in remaining {
+            let settled = Account::<SettledSession>::try_from(settled_info)?;
+            check_settled_session_closable(&ctx.accounts.housebox_state, &settled, now)?;
+            settled.close(ctx.accounts.server_signer.to_account_info())?;
+        }
+
+        msg!("Closed {} settled sessions, rent reclaimed", remaining.len());
+        Ok(())
+    }
+
+    /// Move the protocol's accrued SOL fees (treasury_balance: rake and
+    /// redemption extension fees) out of the LP vault to the configured
+    /// fee_recipient, or the treasury vault PDA when none is set.
+    /// Permissionless crank.
+    pub fn sweep_treasury(ctx: Context<SweepTreasury>) -> Result<()> {
+        let state = &mut ctx.accounts.housebox_state;
+        let amount = state.treasury_balance;
+        require!(amount > 0, HouseboxError::ZeroAmount);
+
+        let destination = if state.fee_recipient == Pubkey::default() {
+            ctx.accounts.treasury_vault.to_account_info()
+        } else {
+            let recipient = ctx.accounts.fee_recipient.as_ref()
+                .ok_or(HouseboxError::InvalidFeeRecipient)?;
+            require!(recipient.key() == state.fee_recipient, HouseboxError::InvalidFeeRecipient);
+            recipient.to_account_info()
+        };
+        // A fresh account needs at least its rent-exempt minimum
+        require!(
+            destination.lamports() + amount >= Rent::get()?.minimum_balance(0),
+            HouseboxError::InsufficientTreasury
+        );
+        state.treasury_balance = 0;
+
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: destination.clone(),
+                },
+                vault_signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(TreasurySwept {
+            destination: destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Swept {} lamports to {}", amount, destination.key());
+
+        Ok(())
+    }
+
+    /// Withdraw SOL from the treasury vault (authority only).
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasuryVault>, amount_lamports: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("withdraw_treasury")?;
+
+        let state = &ctx.accounts.housebox_state;
+        require!(
+            has_role(
+                state,
+                &ctx.accounts.authority.key(),
+                ctx.accounts.role.as_deref(),
+                RoleKind::Treasurer,
+            ),
+            HouseboxError::Unauthorized
+        );
+        require!(!state.council_enabled, HouseboxError::CouncilRequired);
+
+        pay_from_treasury(
+            &ctx.accounts.treasury_vault.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.bumps.treasury_vault,
+            amount_lamports,
+        )?;
+
+        emit!(TreasuryWithdrawn {
+            actor: ctx.accounts.authority.key(),
+            destination: ctx.accounts.destination.key(),
+            amount: amount_lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Withdrew {} lamports of treasury", amount_lamports);
+        msg!("Treasury vault balance: {}", ctx.accounts.treasury_vault.lamports());
+
+        Ok(())
+    }
+
+    /// Withdraw accumulated settlement fees to the operator (authority only).
+    pub fn withdraw_operator_fees(ctx: Context<WithdrawTreasury>, amount_lamports: u64) -> Result<()> {
+        ctx.accounts.log_admin_action("withdraw_operator_fees")?;
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(
+            ctx.accounts.authority.key() == state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(amount_lamports > 0, HouseboxError::ZeroAmount);
+        require!(state.operator_fee_balance >= amount_lamports, HouseboxError::InsufficientTreasury);
+
+        state.operator_fee_balance = state.operator_fee_balance.checked_sub(amount_lamports)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        let vault_seeds = &[
+            b"sol_vault".as_ref(),
+            &[state.sol_vault_bump],
+        ];
+        let vault_signer_seeds = &[&vault_seeds[..]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sol_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                },
+                vault_signer_seeds,
+            ),
+            amount_lamports,
+        )?;
+
+        msg!("Withdrew {} lamports of operator fees", amount_lamports);
+        msg!("Operator fee balance: {}", state.operator_fee_balance);
+
+        Ok(())
+    }
+
+    /// Withdraw vTokens from the protocol account (authority only).
+    /// Used to transfer protocol-held vTokens to a wallet for redemption.
+    pub fn withdraw_protocol_vtokens(
+        ctx: Context<WithdrawProtocolVtokens>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("withdraw_protocol_vtokens")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(amount > 0, HouseboxError::ZeroAmount);
+
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.protocol_vtoken_account.to_account_info(),
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                    to: ctx.accounts.destination_vtoken_account.to_account_info(),
+                    authority: ctx.accounts.housebox_state.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.vtoken_mint.decimals,
+        )?;
+
+        msg!("Withdrew {} vTokens from protocol account", amount);
+
+        Ok(())
+    }
+
+    /// Sweep lamports sent straight to the housebox_state or vtoken_mint PDAs
+    /// (authority only). Only the balance above each account's rent-exempt
+    /// minimum is moved; the mint's is withdrawn through Token-2022.
+    pub fn rescue_lamports(ctx: Context<RescueLamports>) -> Result<()> {
+        ctx.accounts.log_admin_action("rescue_lamports")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let rent = Rent::get()?;
+        let now = Clock::get()?.unix_timestamp;
+        let recovery = ctx.accounts.recovery.key();
+
+        let state_info = ctx.accounts.housebox_state.to_account_info();
+        let state_excess = state_info.lamports()
+            .saturating_sub(rent.minimum_balance(state_info.data_len()));
+        if state_excess > 0 {
+            ctx.accounts.housebox_state.sub_lamports(state_excess)?;
+            ctx.accounts.recovery.add_lamports(state_excess)?;
+            emit!(LamportsRescued {
+                source: state_info.key(),
+                destination: recovery,
+                amount: state_excess,
+                timestamp: now,
+            });
+        }
+
+        let mint_info = ctx.accounts.vtoken_mint.to_account_info();
+        let mint_excess = mint_info.lamports()
+            .saturating_sub(rent.minimum_balance(mint_info.data_len()));
+        if mint_excess > 0 {
+            let seeds = &[
+                b"housebox_state".as_ref(),
+                &[ctx.bumps.housebox_state],
+            ];
+            let signer_seeds = &[&seeds[..]];
+
+            invoke_signed(
+                &spl_token_2022::instruction::withdraw_excess_lamports(
+                    &token_2022::ID,
+                    &mint_info.key(),
+                    &recovery,
+                    &ctx.accounts.housebox_state.key(),
+                    &[],
+                )?,
+                &[
+                    mint_info.clone(),
+                    ctx.accounts.recovery.to_account_info(),
+                    ctx.accounts.housebox_state.to_account_info(),
+                ],
+                signer_seeds,
+            )?;
+            emit!(LamportsRescued {
+                source: mint_info.key(),
+                destination: recovery,
+                amount: mint_excess,
+                timestamp: now,
+            });
+        }
+
+        require!(state_excess > 0 || mint_excess > 0, HouseboxError::ZeroAmount);
+
+        msg!("Rescued {} lamports from state, {} from vToken mint", state_excess, mint_excess);
+
+        Ok(())
+    }
+
+    /// Sweep SPL tokens sent to the housebox_state or vtoken_mint PDA's
+    /// associated token account (authority only). The protocol's own token
+    /// accounts are all program PDAs, never ATAs, so these balances are
+    /// unaccounted.
+    pub fn rescue_tokens(ctx: Context<RescueTokens>) -> Result<()> {
+        ctx.accounts.log_admin_action("rescue_tokens")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let amount = ctx.accounts.stray_token_account.amount;
+        require!(amount > 0, HouseboxError::ZeroAmount);
+
+        let state_seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let mint_seeds = &[
+            b"vtoken_mint".as_ref(),
+            &[ctx.bumps.vtoken_mint],
+        ];
+        let from_state = ctx.accounts.stray_owner.key() == ctx.accounts.housebox_state.key();
+        let (owner, seeds) = if from_state {
+            (ctx.accounts.housebox_state.to_account_info(), &state_seeds[..])
+        } else {
+            (ctx.accounts.vtoken_mint.to_account_info(), &mint_seeds[..])
+        };
+        let signer_seeds = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.stray_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.recovery_token_account.to_account_info(),
+                    authority: owner,
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit!(TokensRescued {
+            source: ctx.accounts.stray_token_account.key(),
+            mint: ctx.accounts.mint.key(),
+            destination: ctx.accounts.recovery_token_account.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Rescued {} tokens of mint {}", amount, ctx.accounts.mint.key());
+
+        Ok(())
+    }
+
+    /// Move the protocol's vToken holdings to a new account and make it the
+    /// protocol vToken account (authority only), e.g. to put haircut revenue
+    /// under DAO control. withdraw_protocol_vtokens and the deposit match
+    /// only work while housebox_state owns the account.
+    pub fn rotate_protocol_vtoken_account(ctx: Context<RotateProtocolVtokenAccount>) -> Result<()> {
+        ctx.accounts.log_admin_action("rotate_protocol_vtoken_account")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let amount = ctx.accounts.protocol_vtoken_account.amount;
+        if amount > 0 {
+            let seeds = &[
+                b"housebox_state".as_ref(),
+                &[ctx.bumps.housebox_state],
+            ];
+            let signer_seeds = &[&seeds[..]];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: ctx.accounts.protocol_vtoken_account.to_account_info(),
+                        mint: ctx.accounts.vtoken_mint.to_account_info(),
+                        to: ctx.accounts.new_protocol_vtoken_account.to_account_info(),
+                        authority: ctx.accounts.housebox_state.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                ctx.accounts.vtoken_mint.decimals,
+            )?;
+        }
+
+        let new_account = ctx.accounts.new_protocol_vtoken_account.key();
+        let state = &mut ctx.accounts.housebox_state;
+        let old_account = state.protocol_vtoken_account;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "protocol_vtoken_account",
+            old_account,
+            new_account,
+        )?;
+        state.protocol_vtoken_account = new_account;
+
+        msg!("Protocol vToken account rotated ({} vTokens moved)", amount);
+        msg!("Old: {}", old_account);
+        msg!("New: {}", new_account);
+
+        Ok(())
+    }
+
+    /// Check that the program's upgrade authority is the protocol authority
+    /// (permissionless). Fails with UpgradeAuthorityMismatch when the deploy
+    /// key has drifted from governance; an immutable program passes.
+    /// migrate_state, migrate_pool and execute_evacuation run the same check.
+    pub fn verify_upgrade_authority(ctx: Context<VerifyUpgradeAuthority>) -> Result<()> {
+        let authority = ctx.accounts.housebox_state.authority;
+        let upgrade_authority = ctx.accounts.program_data.upgrade_authority_address;
+        require_upgrade_authority(&ctx.accounts.program_data, &authority)?;
+
+        emit!(UpgradeAuthorityVerified {
+            authority,
+            upgrade_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Upgrade authority matches: {:?}", upgrade_authority);
+
+        Ok(())
+    }
+
+    /// Upgrade the state account to the current field layout in place
+    /// (authority only). The account is grown to the current size, with the
+    /// authority topping up rent; appended fields start zeroed, matching
+    /// their defaults at initialize. No redeploy or reinitialization needed.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let info = ctx.accounts.housebox_state.to_account_info();
+        require!(info.owner == &crate::ID, HouseboxError::InvalidStateVersion);
+        // authority is the first field, right after the discriminator
+        let authority = Pubkey::try_from(&info.try_borrow_data()?[8..40])
+            .map_err(|_| HouseboxError::InvalidStateVersion)?;
+        require!(ctx.accounts.authority.key() == authority, HouseboxError::Unauthorized);
+        require_upgrade_authority(&ctx.accounts.program_data, &authority)?;
+
+        let old_len = grow_account(
+            &info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + HouseboxState::INIT_SPACE,
+        )?;
+
+        let mut state = HouseboxState::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(state.layout_version < STATE_LAYOUT_VERSION, HouseboxError::InvalidStateVersion);
+        let old_version = state.layout_version;
+        state.layout_version = STATE_LAYOUT_VERSION;
+        state.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        record_admin_action(
+            &state,
+            ctx.accounts.admin_log.as_deref_mut(),
+            ctx.accounts.authority.key(),
+            "migrate_state",
+        )?;
+
+        msg!("State migrated from layout {} to {}", old_version, STATE_LAYOUT_VERSION);
+        msg!("Size: {} -> {} bytes", old_len, info.data_len());
+
+        Ok(())
+    }
+
+    /// Grow a player escrow, LP position, game session, or settled session
+    /// created under an older, shorter layout to the current size
+    /// (permissionless; the payer tops up rent). Appended fields start
+    /// zeroed, matching their defaults at creation, so the account loads
+    /// again in every instruction that takes it.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let info = ctx.accounts.account.to_account_info();
+        require!(info.owner == &crate::ID, HouseboxError::InvalidMigrationAccount);
+        let discriminator: [u8; 8] = info.try_borrow_data()?.get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(HouseboxError::InvalidMigrationAccount)?;
+
+        let new_len = if discriminator == PlayerEscrow::DISCRIMINATOR {
+            8 + PlayerEscrow::INIT_SPACE
+        } else if discriminator == LpPosition::DISCRIMINATOR {
+            8 + LpPosition::INIT_SPACE
+        } else if discriminator == GameSession::DISCRIMINATOR {
+            8 + GameSession::INIT_SPACE
+        } else if discriminator == SettledSession::DISCRIMINATOR {
+            8 + SettledSession::INIT_SPACE
+        } else {
+            return err!(HouseboxError::InvalidMigrationAccount);
+        };
+        require!(info.data_len() < new_len, HouseboxError::InvalidMigrationAccount);
+
+        let old_len = grow_account(
+            &info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+
+        msg!("Account {} migrated", info.key());
+        msg!("Size: {} -> {} bytes", old_len, info.data_len());
+
+        Ok(())
+    }
+
+    /// Start the pool migration timelock to state version `new_version`
+    /// (authority only, while paused). Emits PoolMigrationRequested so LPs
+    /// and players see it POOL_MIGRATION_TIMELOCK_SECONDS before funds move.
+    pub fn request_pool_migration(ctx: Context<AdminAction>, new_version: u8) -> Result<()> {
+        ctx.accounts.log_admin_action("request_pool_migration")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(state.is_pause_confirmed(), HouseboxError::MigrationRequiresPause);
+        require!(state.migrated_to == Pubkey::default(), HouseboxError::PoolMigrated);
+        require!(state.pool_migration_requested_at == 0, HouseboxError::PoolMigrationPending);
+        require!(new_version > state.version, HouseboxError::InvalidStateVersion);
+
+        let now = Clock::get()?.unix_timestamp;
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "pool_migration_version",
+            state.pool_migration_version,
+            new_version,
+        )?;
+        state.pool_migration_version = new_version;
+        state.pool_migration_requested_at = now;
+
+        emit!(PoolMigrationRequested {
+            new_version,
+            executable_at: now + POOL_MIGRATION_TIMELOCK_SECONDS,
+            timestamp: now,
+        });
+        msg!("Pool migration to state version {} requested", new_version);
+
+        Ok(())
+    }
+
+    /// Cancel a pending pool migration (authority only).
+    pub fn cancel_pool_migration(ctx: Context<AdminAction>) -> Result<()> {
+        ctx.accounts.log_admin_action("cancel_pool_migration")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.housebox_state;
+        require!(state.pool_migration_requested_at != 0, HouseboxError::PoolMigrationNotPending);
+        emit_parameter_change(
+            ctx.accounts.authority.key(),
+            "pool_migration_version",
+            state.pool_migration_version,
+            0,
+        )?;
+        state.pool_migration_version = 0;
+        state.pool_migration_requested_at = 0;
+
+        msg!("Pool migration cancelled");
+
+        Ok(())
+    }
+
+    /// Migrate the pool to the requested state version once the timelock
+    /// has passed (authority only, while paused). Copies solsum/vsum
+    /// accounting and config into the `["housebox_state", version]` PDA,
+    /// moves all vault SOL (LP pool and escrow) to `["sol_vault", version]`,
+    /// and hands vToken mint and protocol account authority to the new
+    /// state, for a program upgrade that reads the versioned PDAs. The old
+    /// state stays paused with zeroed accounting and cannot be unpaused.
+    /// Staked, vesting, LST-backed, and streaming balances must be unwound first.
+    pub fn migrate_pool(ctx: Context<MigratePool>, new_version: u8) -> Result<()> {
+        ctx.accounts.log_admin_action("migrate_pool")?;
+
+        let state = &ctx.accounts.housebox_state;
+        require!(
+            ctx.accounts.authority.key() == state.authority,
+            HouseboxError::Unauthorized
+        );
+        require_upgrade_authority(&ctx.accounts.program_data, &state.authority)?;
+        require!(state.is_pause_confirmed(), HouseboxError::MigrationRequiresPause);
+        require!(state.migrated_to == Pubkey::default(), HouseboxError::PoolMigrated);
+        require!(state.pool_migration_requested_at != 0, HouseboxError::PoolMigrationNotPending);
+        require!(new_version == state.pool_migration_version, HouseboxError::InvalidStateVersion);
+        let now = Clock::get()?.unix_timestamp;
+        let executable_at = state.pool_migration_requested_at
+            .checked_add(POOL_MIGRATION_TIMELOCK_SECONDS)
+            .ok_or(HouseboxError::MathOverflow)?;
+        require!(now >= executable_at, HouseboxError::TimelockNotElapsed);
+        require!(
+            state.staked_vtokens == 0
+                && state.stake_rewards_reserved == 0
+                && state.protocol_vesting_balance == 0
+                && state.lst_sol_value == 0
+                && state.redemption_streams_reserved == 0,
+            HouseboxError::MigrationBlocked
+        );
+
+        let new_state_key = ctx.accounts.new_housebox_state.key();
+        let seeds = &[
+            b"housebox_state".as_ref(),
+            &[ctx.bumps.housebox_state],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Move every lamport in the vault (LP pool + player escrow)
+        let vault_lamports = ctx.accounts.sol_vault.lamports();
+        if vault_lamports > 0 {
+            let vault_seeds = &[
+                b"sol_vault".as_ref(),
+                &[state.sol_vault_bump],
+            ];
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.sol_vault.to_account_info(),
+                        to: ctx.accounts.new_sol_vault.to_account_info(),
+                    },
+                    &[&vault_seeds[..]],
+                ),
+                vault_lamports,
+            )?;
+        }
+
+        // Hand vToken minting and the protocol vToken account to the new state
+        token_interface::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::SetAuthority {
+                    current_authority: ctx.accounts.housebox_state.to_account_info(),
+                    account_or_mint: ctx.accounts.vtoken_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            spl_token_2022::instruction::AuthorityType::MintTokens,
+            Some(new_state_key),
+        )?;
+        token_interface::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::SetAuthority {
+                    current_authority: ctx.accounts.housebox_state.to_account_info(),
+                    account_or_mint: ctx.accounts.protocol_vtoken_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            spl_token_2022::instruction::AuthorityType::AccountOwner,
+            Some(new_state_key),
+        )?;
+
+        // Copy accounting and config into the new state
+        let mut migrated = (**ctx.accounts.housebox_state).clone();
+        migrated.version = new_version;
+        migrated.sol_vault_bump = ctx.bumps.new_sol_vault;
+        migrated.pool_migration_version = 0;
+        migrated.pool_migration_requested_at = 0;
+        ctx.accounts.new_housebox_state.set_inner(migrated);
+
+        let state = &mut ctx.accounts.housebox_state;
+        let (solsum, vsum) = (state.solsum, state.vsum);
+        state.solsum = 0;
+        state.vsum = 0;
+        state.migrated_to = new_state_key;
+        state.pool_migration_requested_at = 0;
+
+        emit!(PoolMigrationExecuted {
+            new_state: new_state_key,
+            new_version,
+            lamports: vault_lamports,
+            solsum,
+            vsum,
+            timestamp: now,
+        });
+        msg!("Pool migrated to state version {}: {}", new_version, new_state_key);
+        msg!("Moved {} lamports (Solsum: {}, Vsum: {})", vault_lamports, solsum, vsum);
+
+        Ok(())
+    }
+
+    /// Create an SPL-asset house pool for `asset_mint` (authority only).
+    /// The pool holds the asset in a token vault and has its own vToken mint
+    /// (same decimals as the asset) and protocol vToken account, all owned by
+    /// the pool PDA. Pause, exodus mode, and the LP whitelist are shared.
+    pub fn initialize_asset_pool(ctx: Context<InitializeAssetPool>, lp_percent: u8) -> Result<()> {
+        ctx.accounts.log_admin_action("initialize_asset_pool")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(lp_percent > 0 && lp_percent <= 100, HouseboxError::InvalidLpPercent);
+
+        let pool = &mut ctx.accounts.asset_pool;
+        pool.asset_mint = ctx.accounts.asset_mint.key();
+        pool.asset_vault = ctx.accounts.asset_vault.key();
+        pool.vtoken_mint = ctx.accounts.vtoken_mint.key();
+        pool.protocol_vtoken_account = ctx.accounts.protocol_vtoken_account.key();
+        pool.lp_percent = lp_percent;
+        pool.asset_sum = 0;
+        pool.vsum = 0;
+        pool.bump = ctx.bumps.asset_pool;
+
+        msg!("Asset pool initialized for mint {}", pool.asset_mint);
+        msg!("LP percent: {}%", lp_percent);
+
+        Ok(())
+    }
+
+    /// LP locks the pool asset, receives the pool's vTokens.
+    /// Same rate-aware minting and LP/protocol split as lp_lock, in asset units.
+    pub fn asset_lp_lock(
+        ctx: Context<AssetLpLock>,
+        amount: u64,
+        min_vtokens_out: Option<u64>,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(!state.sunset_mode, HouseboxError::ProtocolSunset);
+        require!(amount > 0, HouseboxError::ZeroAmount);
+        require!(
+            !state.lp_whitelist_enabled || ctx.accounts.lp_whitelist.is_some(),
+            HouseboxError::LpNotWhitelisted
+        );
+
+        // Transfer asset from LP to the pool vault
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.asset_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.lp_asset_account.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info(),
+                    to: ctx.accounts.asset_vault.to_account_info(),
+                    authority: ctx.accounts.lp.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.asset_mint.decimals,
+        )?;
+
+        let split = compute_asset_lock_split(&ctx.accounts.asset_pool, amount)?;
+        if let Some(min_out) = min_vtokens_out {
+            require!(split.lp_vtokens >= min_out, HouseboxError::SlippageExceeded);
+        }
+
+        let asset_mint_key = ctx.accounts.asset_mint.key();
+        let seeds = &[
+            b"asset_pool".as_ref(),
+            asset_mint_key.as_ref(),
+            &[ctx.accounts.asset_pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // Mint vTokens to LP
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::MintTo {
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                    to: ctx.accounts.lp_vtoken_account.to_account_info(),
+                    authority: ctx.accounts.asset_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            split.lp_vtokens,
+        )?;
+
+        // Mint vTokens to protocol
+        if split.protocol_vtokens > 0 {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::MintTo {
+                        mint: ctx.accounts.vtoken_mint.to_account_info(),
+                        to: ctx.accounts.protocol_vtoken_account.to_account_info(),
+                        authority: ctx.accounts.asset_pool.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                split.protocol_vtokens,
+            )?;
+        }
+
+        let pool = &mut ctx.accounts.asset_pool;
+        pool.asset_sum = pool.asset_sum.checked_add(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+        pool.vsum = pool.vsum.checked_add(split.vtokens_to_mint)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("LP locked {} asset units, received {} vTokens (LP: {}, Protocol: {})", amount, split.vtokens_to_mint, split.lp_vtokens, split.protocol_vtokens);
+        msg!("Asset sum: {}, Vsum: {}", pool.asset_sum, pool.vsum);
+
+        Ok(())
+    }
+
+    /// LP requests redemption of asset-pool vTokens (deferred burn, same delay
+    /// and claim window as request_redemption). One pending request per LP per pool.
+    pub fn asset_request_redemption(
+        ctx: Context<AssetRequestRedemption>,
+        vtoken_amount: u64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.exodus_mode, HouseboxError::ProtocolPaused);
+        require!(vtoken_amount > 0, HouseboxError::ZeroAmount);
+        require!(ctx.accounts.asset_pool.vsum > 0, HouseboxError::NoLiquidity);
+
+        let now = Clock::get()?.unix_timestamp;
+        let request = &mut ctx.accounts.redemption_request;
+        request.lp = ctx.accounts.lp.key();
+        request.vtoken_amount = vtoken_amount;
+        request.requested_at = now;
+        request.bump = ctx.bumps.redemption_request;
+        request.extended = false;
+        request.execute_after = now + REDEMPTION_DELAY_SECONDS;
+        request.redeem_all = false;
+
+        msg!("Asset redemption requested: {} vTokens (deferred burn)", vtoken_amount);
+        msg!("Ready at timestamp: {}", request.ready_at());
+
+        Ok(())
+    }
+
+    /// Execute an asset-pool redemption after the delay. Burns the LP's vTokens
+    /// and transfers the asset at the execution-time ratio.
+    pub fn asset_execute_redemption(ctx: Context<AssetExecuteRedemption>) -> Result<()> {
+        let request = &ctx.accounts.redemption_request;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= request.ready_at(), HouseboxError::RedemptionNotReady);
+        require!(now <= request.expires_at(), HouseboxError::RedemptionExpired);
+
+        let vtoken_amount = request.vtoken_amount;
+        require!(
+            ctx.accounts.lp_vtoken_account.amount >= vtoken_amount,
+            HouseboxError::InsufficientVtokens
+        );
+
+        let pool = &ctx.accounts.asset_pool;
+        require!(pool.vsum > 0, HouseboxError::NoLiquidity);
+        let amount_out = (vtoken_amount as u128)
+            .checked_mul(pool.asset_sum as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div(pool.vsum as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64;
+        require!(amount_out > 0, HouseboxError::AmountTooSmall);
+
+        // Burn vTokens from LP
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::Burn {
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                    from: ctx.accounts.lp_vtoken_account.to_account_info(),
+                    authority: ctx.accounts.lp.to_account_info(),
+                },
+            ),
+            vtoken_amount,
+        )?;
+
+        let asset_mint_key = ctx.accounts.asset_mint.key();
+        let seeds = &[
+            b"asset_pool".as_ref(),
+            asset_mint_key.as_ref(),
+            &[ctx.accounts.asset_pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.asset_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.asset_vault.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info(),
+                    to: ctx.accounts.lp_asset_account.to_account_info(),
+                    authority: ctx.accounts.asset_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_out,
+            ctx.accounts.asset_mint.decimals,
+        )?;
+
+        let pool = &mut ctx.accounts.asset_pool;
+        pool.vsum = pool.vsum.checked_sub(vtoken_amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+        pool.asset_sum = pool.asset_sum.checked_sub(amount_out)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        // Account will be closed by Anchor's `close = lp` constraint
+        msg!("Asset redemption executed: {} vTokens burned, {} asset units transferred to LP", vtoken_amount, amount_out);
+        msg!("Asset sum: {}, Vsum: {}", pool.asset_sum, pool.vsum);
+
+        Ok(())
+    }
+
+    /// Player deposits a pool asset into their canonical escrow. The balance
+    /// is held in the escrow's slot for the asset's mint, alongside the SOL
+    /// balance.
+    pub fn escrow_asset_deposit(ctx: Context<EscrowAssetDeposit>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.housebox_state.is_paused(), HouseboxError::ProtocolPaused);
+        require!(!ctx.accounts.housebox_state.sunset_mode, HouseboxError::ProtocolSunset);
+        require!(amount > 0, HouseboxError::ZeroAmount);
+        require!(
+            !ctx.accounts.player_escrow.is_excluded(Clock::get()?.unix_timestamp),
+            HouseboxError::PlayerSelfExcluded
+        );
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.asset_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.player_asset_account.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info(),
+                    to: ctx.accounts.asset_vault.to_account_info(),
+                    authority: ctx.accounts.player.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.asset_mint.decimals,
+        )?;
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        escrow.player = ctx.accounts.player.key();
+        escrow.bump = ctx.bumps.player_escrow;
+
+        if escrow.verified_withdrawal_address == Pubkey::default() {
+            escrow.verified_withdrawal_address = ctx.accounts.player.key();
+            msg!("Verified withdrawal address set to: {}", ctx.accounts.player.key());
+        }
+
+        let slot = escrow.asset_slot_mut(&ctx.accounts.asset_mint.key())?;
+        slot.balance = slot.balance.checked_add(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+
+        msg!("Player deposited {} asset units to escrow", amount);
+        msg!("Escrow asset balance: {}", slot.balance);
+
+        Ok(())
+    }
+
+    /// Settle player session P&L in one of the escrow's asset balances
+    /// against that asset's pool (server-signed). Accounting only, as in
+    /// player_settle.
+    pub fn escrow_asset_settle(
+        ctx: Context<EscrowAssetSettle>,
+        pnl: i64,
+        session_id: [u8; 32],
+        expires_at: i64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.housebox_state;
+        require!(!state.is_paused() || state.settle_while_paused, HouseboxError::ProtocolPaused);
+        require!(
+            state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= expires_at,
+            HouseboxError::SettlementExpired
+        );
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        let pool = &mut ctx.accounts.asset_pool;
+        let slot = escrow.asset_slot_mut(&pool.asset_mint)?;
+
+        if pnl < 0 {
+            // Player lost
+            let loss = (-pnl) as u64;
+            require!(slot.balance >= loss, HouseboxError::InsufficientEscrow);
+
+            slot.balance = slot.balance.checked_sub(loss)
+                .ok_or(HouseboxError::MathOverflow)?;
+            pool.asset_sum = pool.asset_sum.checked_add(loss)
+                .ok_or(HouseboxError::MathOverflow)?;
+
+            msg!("Player lost {} asset units", loss);
+        } else if pnl > 0 {
+            // Player won
+            let win = pnl as u64;
+            require!(pool.asset_sum >= win, HouseboxError::HouseInsolvent);
+
+            slot.balance = slot.balance.checked_add(win)
+                .ok_or(HouseboxError::MathOverflow)?;
+            pool.asset_sum = pool.asset_sum.checked_sub(win)
+                .ok_or(HouseboxError::MathOverflow)?;
+
+            msg!("Player won {} asset units", win);
+        }
+
+        // Mark session as settled
+        let settled = &mut ctx.accounts.settled_session;
+        settled.session_id = session_id;
+        settled.player = ctx.accounts.player.key();
+        settled.settled_at = Clock::get()?.unix_timestamp;
+        settled.pnl = pnl;
+        settled.escrow_balance = slot.balance;
+        settled.asset_mint = pool.asset_mint;
+
+        msg!("Session settled. Escrow asset balance: {}", slot.balance);
+        msg!("Asset sum: {}", pool.asset_sum);
+
+        Ok(())
+    }
+
+    /// Player withdraws a pool asset from their canonical escrow
+    /// (server-authorized). Like player_withdraw, allowed even when paused.
+    pub fn escrow_asset_withdraw(ctx: Context<EscrowAssetWithdraw>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.housebox_state.is_server_key(&ctx.accounts.server_signer.key()),
+            HouseboxError::InvalidServerSignature
+        );
+        require!(amount > 0, HouseboxError::ZeroAmount);
+
+        let escrow = &mut ctx.accounts.player_escrow;
+        require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+        require!(
+            escrow.verified_withdrawal_address == ctx.accounts.player.key(),
+            HouseboxError::WithdrawalAddressMismatch
+        );
+
+        let slot = escrow.asset_slot_mut(&ctx.accounts.asset_mint.key())?;
+        require!(slot.balance >= amount, HouseboxError::InsufficientEscrow);
+        slot.balance = slot.balance.checked_sub(amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+        let remaining = slot.balance;
+        if remaining == 0 {
+            // Free the slot for another asset
+            slot.mint = Pubkey::default();
+        }
+
+        let asset_mint_key = ctx.accounts.asset_mint.key();
+        let seeds = &[
+            b"asset_pool".as_ref(),
+            asset_mint_key.as_ref(),
+            &[ctx.accounts.asset_pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.asset_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.asset_vault.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info(),
+                    to: ctx.accounts.player_asset_account.to_account_info(),
+                    authority: ctx.accounts.asset_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.asset_mint.decimals,
+        )?;
+
+        msg!("Player withdrew {} asset units from escrow", amount);
+        msg!("Remaining escrow asset balance: {}", remaining);
+
+        Ok(())
+    }
+
+    /// Withdraw vTokens from an asset pool's protocol account (authority only).
+    pub fn withdraw_asset_protocol_vtokens(
+        ctx: Context<WithdrawAssetProtocolVtokens>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.log_admin_action("withdraw_asset_protocol_vtokens")?;
+
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.housebox_state.authority,
+            HouseboxError::Unauthorized
+        );
+        require!(amount > 0, HouseboxError::ZeroAmount);
+
+        let asset_mint_key = ctx.accounts.asset_pool.asset_mint;
+        let seeds = &[
+            b"asset_pool".as_ref(),
+            asset_mint_key.as_ref(),
+            &[ctx.accounts.asset_pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.protocol_vtoken_account.to_account_info(),
+                    mint: ctx.accounts.vtoken_mint.to_account_info(),
+                    to: ctx.accounts.destination_vtoken_account.to_account_info(),
+                    authority: ctx.accounts.asset_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.vtoken_mint.decimals,
+        )?;
+
+        msg!("Withdrew {} vTokens from asset pool protocol account", amount);
+
+        Ok(())
+    }
+}
+
+// ============================================
+// HELPERS
+// ============================================
+
+/// Withdrawal destination: the optional destination account, else the player wallet.
+fn withdrawal_destination<'info>(
+    player: &AccountInfo<'info>,
+    destination: &Option<UncheckedAccount<'info>>,
+) -> AccountInfo<'info> {
+    match destination {
+        Some(destination) => destination.to_account_info(),
+        None => player.clone(),
+    }
+}
+
+/// Apply a session P&L between a player's escrow and the LP pool (accounting only).
+/// Losses are taken from unreserved balance only, so a settlement can never
+/// consume another open session's reservation; callers settling a session
+/// release its reservation first. The operator's settlement fee comes out of
+/// the pool's share of a loss. Wins above max_settlement_pnl are rejected.
+/// Wins are paid from the insurance fund first and only reduce solsum once it
+/// is exhausted. A win that leaves solsum below solvency_floor_lamports trips
+/// the circuit breaker and pauses the protocol.
+fn apply_settlement_pnl(
+    state: &mut HouseboxState,
+    escrow: &mut PlayerEscrow,
+    pnl: i64,
+) -> Result<SettlementSplit> {
+    require!(!escrow.frozen, HouseboxError::EscrowFrozen);
+    require!(
+        !escrow.is_excluded(Clock::get()?.unix_timestamp),
+        HouseboxError::PlayerSelfExcluded
+    );
+
+    let mut split = SettlementSplit::default();
+    if pnl < 0 {
+        // Player lost
+        let loss = pnl.unsigned_abs();
+        require!(escrow.available() >= loss, HouseboxError::InsufficientEscrow);
+        record_player_loss(escrow, loss, Clock::get()?.unix_timestamp)?;
+
+        // Rake goes to the protocol treasury, the rest to the LP pool
+        let rake = (loss as u128)
+            .checked_mul(state.rake_bps as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64;
+
+        // Parts of the rake feed the jackpot and the insurance fund
+        let jackpot_cut = (rake as u128)
+            .checked_mul(state.jackpot_contribution_bps as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64;
+        let insurance_cut = (rake as u128)
+            .checked_mul(state.insurance_contribution_bps as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64;
+        // Escrow yield only accrues while some balance is opted in
+        let yield_cut = if state.escrow_yield_staked > 0 {
+            (rake as u128)
+                .checked_mul(state.escrow_yield_bps as u128)
+                .ok_or(HouseboxError::MathOverflow)?
+                .checked_div(BPS_DENOMINATOR as u128)
+                .ok_or(HouseboxError::MathOverflow)? as u64
+        } else {
+            0
+        };
+        // Rake boost for vToken stakers, while anything is staked
+        let stake_cut = if state.staked_vtokens > 0 {
+            (rake as u128)
+                .checked_mul(state.stake_rake_bps as u128)
+                .ok_or(HouseboxError::MathOverflow)?
+                .checked_div(BPS_DENOMINATOR as u128)
+                .ok_or(HouseboxError::MathOverflow)? as u64
+        } else {
+            0
+        };
+        let treasury_cut = rake.checked_sub(jackpot_cut)
+            .and_then(|remaining| remaining.checked_sub(insurance_cut))
+            .and_then(|remaining| remaining.checked_sub(yield_cut))
+            .and_then(|remaining| remaining.checked_sub(stake_cut))
+            .ok_or(HouseboxError::MathOverflow)?;
+        // The operator recoups its settlement costs from the pool's share
+        let operator_fee = state.settlement_fee_lamports.min(loss - rake);
+
+        escrow.balance = escrow.balance.checked_sub(loss)
+            .ok_or(HouseboxError::MathOverflow)?;
+        emit_if_low_balance(escrow, loss)?;
+        state.solsum = state.solsum.checked_add(loss - rake - operator_fee)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.treasury_balance = state.treasury_balance.checked_add(treasury_cut)
+            .ok_or(HouseboxError::MathOverflow)?;
+        // A referred player's referrer earns a share of the treasury's cut
+        if escrow.referrer != Pubkey::default() {
+            let referral_cut = ((treasury_cut as u128)
+                .checked_mul(state.player_referral_bps as u128)
+                .ok_or(HouseboxError::MathOverflow)?
+                .checked_div(BPS_DENOMINATOR as u128)
+                .ok_or(HouseboxError::MathOverflow)? as u64)
+                .min(treasury_cut);
+            escrow.referral_credit = escrow.referral_credit.checked_add(referral_cut)
+                .ok_or(HouseboxError::MathOverflow)?;
+            split.referral = referral_cut;
+        }
+        state.operator_fee_balance = state.operator_fee_balance.checked_add(operator_fee)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.jackpot_balance = state.jackpot_balance.checked_add(jackpot_cut)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.insurance_balance = state.insurance_balance.checked_add(insurance_cut)
+            .ok_or(HouseboxError::MathOverflow)?;
+        if yield_cut > 0 {
+            distribute_escrow_yield(state, yield_cut)?;
+        }
+        if stake_cut > 0 {
+            distribute_stake_rewards(state, stake_cut)?;
+        }
+
+        // Losses spend cash first; bonus is lost once the balance runs below it
+        escrow.bonus_balance = escrow.bonus_balance.min(escrow.balance);
+
+        split.to_pool = loss - rake - operator_fee;
+        split.treasury = treasury_cut;
+        split.operator_fee = operator_fee;
+        split.jackpot = jackpot_cut;
+        split.insurance = insurance_cut;
+        split.escrow_yield = yield_cut;
+        split.staking = stake_cut;
+
+        msg!("Player lost {} lamports (rake: {}, fee: {})", loss, rake, operator_fee);
+    } else if pnl > 0 {
+        // Player won
+        let win = pnl as u64;
+        require!(
+            state.max_settlement_pnl == 0 || win <= state.max_settlement_pnl,
+            HouseboxError::SettlementExceedsLimit
+        );
+
+        // Insurance absorbs the win before the LP pool does
+        let from_insurance = win.min(state.insurance_balance);
+        let from_pool = win - from_insurance;
+        require!(state.liquid_solsum() >= from_pool, HouseboxError::HouseInsolvent);
+
+        escrow.balance = escrow.balance.checked_add(win)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.insurance_balance -= from_insurance;
+        state.solsum = state.solsum.checked_sub(from_pool)
+            .ok_or(HouseboxError::MathOverflow)?;
+        split.from_insurance = from_insurance;
+        split.from_pool = from_pool;
+
+        msg!("Player won {} lamports (insurance: {})", win, from_insurance);
+
+        if state.solsum < state.solvency_floor_lamports && !state.is_pause_confirmed() {
+            state.paused = true;
+            state.guardian_pause_expires_at = 0;
+            emit!(SolvencyBreakerTripped {
+                solsum: state.solsum,
+                floor: state.solvency_floor_lamports,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+            msg!("Solvency floor breached, protocol PAUSED");
+        }
+    }
+
+    Ok(split)
+}
+
+/// First error a settlement of `pnl` would hit in player_settle, checked
+/// without applying it. `escrow` must already have its limits rolled to `now`.
+fn settlement_check_error(
+    state: &HouseboxState,
+    escrow: &PlayerEscrow,
+    pnl: i64,
+    game_type: u16,
+    now: i64,
+) -> Option<HouseboxError> {
+    if state.is_paused() && !state.settle_while_paused {
+        return Some(HouseboxError::ProtocolPaused);
+    }
+    if escrow.frozen {
+        return Some(HouseboxError::EscrowFrozen);
+    }
+    if escrow.is_excluded(now) {
+        return Some(HouseboxError::PlayerSelfExcluded);
+    }
+
+    if pnl < 0 {
+        let loss = pnl.unsigned_abs();
+        if escrow.available() < loss {
+            return Some(HouseboxError::InsufficientEscrow);
+        }
+        let lost_today = escrow.lost_today.saturating_add(loss);
+        if escrow.daily_loss_limit != 0 && lost_today > escrow.daily_loss_limit {
+            return Some(HouseboxError::DailyLossLimitExceeded);
+        }
+    } else if pnl > 0 {
+        let win = pnl as u64;
+        if state.max_settlement_pnl != 0 && win > state.max_settlement_pnl {
+            return Some(HouseboxError::SettlementExceedsLimit);
+        }
+        let from_pool = win - win.min(state.insurance_balance);
+        if state.liquid_solsum() < from_pool {
+            return Some(HouseboxError::HouseInsolvent);
+        }
+        let bucket = state.game_types.iter()
+            .find(|bucket| game_type != 0 && bucket.game_type == game_type);
+        if let Some(bucket) = bucket {
+            let exposure = bucket.exposure.saturating_add(win);
+            if bucket.max_exposure != 0 && exposure > bucket.max_exposure {
+                return Some(HouseboxError::GameExposureExceeded);
+            }
+        }
+    }
+
+    None
+}
+
+/// Carry a session's seed commitment and revealed seed into its settlement
+/// record. A committed seed must be revealed before the session settles.
+fn record_session_seed(session: &GameSession, settled: &mut SettledSession) -> Result<()> {
+    if session.seed_commitment == [0u8; 32] {
+        return Ok(());
+    }
+    require!(session.seed_revealed, HouseboxError::SeedNotRevealed);
+
+    settled.seed_commitment = session.seed_commitment;
+    settled.revealed_seed = session.revealed_seed;
+
+    Ok(())
+}
+
+/// Load the `["session", session_id]` PDA passed to a permissionless
+/// settlement. The account is always passed, so a submitter can't leave out
+/// an open session and settle against unreserved balance; it is a
+/// GameSession only if open_session created it.
+fn load_open_session(info: &AccountInfo) -> Result<Option<GameSession>> {
+    if info.owner != &crate::ID || info.lamports() == 0 {
+        return Ok(None);
+    }
+    Ok(Some(GameSession::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+}
+
+/// Close a program account that isn't typed in the accounts struct,
+/// returning its rent to `destination`.
+fn close_program_account<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = destination.lamports().checked_add(info.lamports())
+        .ok_or(HouseboxError::MathOverflow)?;
+    **destination.try_borrow_mut_lamports()? = lamports;
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&system_program::ID);
+    info.realloc(0, false)?;
+    Ok(())
+}
+
+/// Look up a slot's hash in the SlotHashes sysvar (u64 count, then
+/// newest-first entries of slot (u64 LE) and 32-byte hash).
+fn slot_hash_at(slot_hashes: &AccountInfo, slot: u64) -> Option<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data().ok()?;
+    let count = u64::from_le_bytes(data.get(0..8)?.try_into().ok()?) as usize;
+    (0..count).find_map(|i| {
+        let entry = data.get(8 + i * 40..8 + (i + 1) * 40)?;
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().ok()?);
+        (entry_slot == slot).then(|| entry[8..].try_into().ok()).flatten()
+    })
+}
+
+/// A settled session can be closed once it is an hour old and past the
+/// dispute window, with no open dispute or pending clawback.
+fn check_settled_session_closable(state: &HouseboxState, settled: &SettledSession, now: i64) -> Result<()> {
+    let age = now - settled.settled_at;
+    require!(age >= 3600, HouseboxError::SettlementTooRecent);
+    require!(age >= state.dispute_window_seconds, HouseboxError::SettlementTooRecent);
+    require!(!settled.disputed || settled.dispute_resolved, HouseboxError::SettlementDisputed);
+    require!(settled.clawback_at == 0, HouseboxError::ClawbackPending);
+
+    Ok(())
+}
+
+/// Undo a SOL settlement's PnL using its recorded split. Each bucket the
+/// settlement fed (treasury, operator fee, jackpot, insurance) hands back its
+/// own cut, and insurance is refilled for what it paid toward a win. A cut a
+/// bucket has already paid out, and the escrow yield and staking rewards
+/// already credited through their accumulators, is covered by the pool.
+fn reverse_settlement(
+    state: &mut HouseboxState,
+    escrow: &mut PlayerEscrow,
+    settled: &mut SettledSession,
+) -> Result<()> {
+    require!(!settled.reversed, HouseboxError::ClawbackNotAllowed);
+    let split = settled.split;
+
+    // Take back what the settlement credited the escrow before touching the house side
+    if settled.pnl > 0 {
+        let clawback = settled.pnl as u64;
+        require!(escrow.available() >= clawback, HouseboxError::InsufficientEscrow);
+        escrow.balance = escrow.balance.checked_sub(clawback)
+            .ok_or(HouseboxError::MathOverflow)?;
+        emit_if_low_balance(escrow, clawback)?;
+    }
+
+    // Cancel whatever referral credit from this settlement is still unpaid
+    escrow.referral_credit -= split.referral.min(escrow.referral_credit);
+
+    let from_treasury = split.treasury.min(state.treasury_balance);
+    let from_operator_fee = split.operator_fee.min(state.operator_fee_balance);
+    let from_jackpot = split.jackpot.min(state.jackpot_balance);
+    let from_insurance = split.insurance.min(state.insurance_balance);
+    let from_buckets = from_treasury as u128
+        + from_operator_fee as u128
+        + from_jackpot as u128
+        + from_insurance as u128;
+
+    // Net lamports the pool pays out (positive) or takes back (negative)
+    let pool_delta = split.to_pool as i128
+        + split.treasury as i128
+        + split.operator_fee as i128
+        + split.jackpot as i128
+        + split.insurance as i128
+        + split.escrow_yield as i128
+        + split.staking as i128
+        - from_buckets as i128
+        - split.from_pool as i128;
+    if pool_delta > 0 {
+        require!(
+            state.liquid_solsum() as i128 >= pool_delta,
+            HouseboxError::HouseInsolvent
+        );
+    }
+
+    state.treasury_balance -= from_treasury;
+    state.operator_fee_balance -= from_operator_fee;
+    state.jackpot_balance -= from_jackpot;
+    state.insurance_balance = (state.insurance_balance - from_insurance)
+        .checked_add(split.from_insurance)
+        .ok_or(HouseboxError::MathOverflow)?;
+    let solsum = state.solsum as i128 - pool_delta;
+    state.solsum = u64::try_from(solsum).map_err(|_| HouseboxError::MathOverflow)?;
+
+    if settled.pnl < 0 {
+        let refund = settled.pnl.unsigned_abs();
+        escrow.balance = escrow.balance.checked_add(refund)
+            .ok_or(HouseboxError::MathOverflow)?;
+    }
+    settled.reversed = true;
+
+    Ok(())
+}
+
+/// Lamports the house side holds against player escrows: the LP pool plus
+/// the treasury, operator fee, jackpot, insurance, unclaimed escrow yield,
+/// and unclaimed staking reward balances fed by settlements.
+fn house_balance(state: &HouseboxState) -> i128 {
+    state.solsum as i128
+        + state.treasury_balance as i128
+        + state.operator_fee_balance as i128
+        + state.jackpot_balance as i128
+        + state.insurance_balance as i128
+        + state.escrow_yield_reserved as i128
+        + state.stake_rewards_reserved as i128
+}
+
+/// Credit rake already in the vault to opted-in escrow balances via the
+/// escrow yield accumulator.
+fn distribute_escrow_yield(state: &mut HouseboxState, amount_lamports: u64) -> Result<()> {
+    let increment = (amount_lamports as u128)
+        .checked_mul(STAKE_REWARD_SCALE)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_div(state.escrow_yield_staked as u128)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    state.escrow_yield_per_lamport = state.escrow_yield_per_lamport.checked_add(increment)
+        .ok_or(HouseboxError::MathOverflow)?;
+    state.escrow_yield_reserved = state.escrow_yield_reserved.checked_add(amount_lamports)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Move an escrow's yield since its last snapshot into pending_yield.
+fn accrue_escrow_yield(escrow: &mut PlayerEscrow, yield_per_lamport: u128) -> Result<()> {
+    let delta = yield_per_lamport.checked_sub(escrow.yield_per_lamport_paid)
+        .ok_or(HouseboxError::MathOverflow)?;
+    let accrued = (escrow.yield_balance as u128)
+        .checked_mul(delta)
+        .ok_or(HouseboxError::MathOverflow)?
+        / STAKE_REWARD_SCALE;
+
+    escrow.pending_yield = escrow.pending_yield
+        .checked_add(u64::try_from(accrued).map_err(|_| HouseboxError::MathOverflow)?)
+        .ok_or(HouseboxError::MathOverflow)?;
+    escrow.yield_per_lamport_paid = yield_per_lamport;
+
+    Ok(())
+}
+
+/// Count settled wagering volume toward the escrow's bonus requirement,
+/// releasing the bonus into withdrawable balance once it is met.
+fn record_wagering(escrow: &mut PlayerEscrow, wagered: u64) -> Result<()> {
+    if escrow.bonus_balance == 0 {
+        return Ok(());
+    }
+
+    escrow.wagered = escrow.wagered.checked_add(wagered)
+        .ok_or(HouseboxError::MathOverflow)?;
+    if escrow.wagered >= escrow.wagering_required {
+        msg!("Wagering requirement met, {} lamports of bonus released", escrow.bonus_balance);
+        escrow.bonus_balance = 0;
+        escrow.wagering_required = 0;
+        escrow.wagered = 0;
+    }
+
+    Ok(())
+}
+
+/// Whether `new` relaxes a player limit (0 = no limit).
+fn limit_is_looser(new: u64, current: u64) -> bool {
+    current != 0 && (new == 0 || new > current)
+}
+
+/// Apply pending loosened limits once their cooling-off has passed, and
+/// reset the daily counters on a new day.
+fn roll_player_limits(escrow: &mut PlayerEscrow, now: i64) {
+    if escrow.pending_limits_at != 0 && now >= escrow.pending_limits_at {
+        escrow.daily_deposit_limit = escrow.pending_deposit_limit;
+        escrow.daily_loss_limit = escrow.pending_loss_limit;
+        escrow.pending_limits_at = 0;
+    }
+
+    let day = now / SECONDS_PER_DAY;
+    if day != escrow.limit_day {
+        escrow.limit_day = day;
+        escrow.deposited_today = 0;
+        escrow.lost_today = 0;
+    }
+}
+
+// Civic gateway token states
+const GATEWAY_TOKEN_STATE_ACTIVE: u8 = 0;
+
+/// Read (owner_wallet, gatekeeper_network, state, expire_time) from a Civic
+/// gateway token. Borsh layout: features (u8), parent_gateway_token
+/// (Option<Pubkey>), owner_wallet, owner_identity (Option<Pubkey>),
+/// gatekeeper_network, issuing_gatekeeper, state (u8), expire_time (Option<i64>).
+fn gateway_token_fields(data: &[u8]) -> Option<(Pubkey, Pubkey, u8, Option<i64>)> {
+    let pubkey_at = |offset: usize| -> Option<Pubkey> {
+        Pubkey::try_from(data.get(offset..offset + 32)?).ok()
+    };
+    let option_len = |offset: usize, len: usize| -> Option<usize> {
+        match *data.get(offset)? {
+            0 => Some(1),
+            1 => Some(1 + len),
+            _ => None,
+        }
+    };
+
+    let mut offset = 1;
+    offset += option_len(offset, 32)?;
+    let owner_wallet = pubkey_at(offset)?;
+    offset += 32;
+    offset += option_len(offset, 32)?;
+    let gatekeeper_network = pubkey_at(offset)?;
+    offset += 64;
+    let token_state = *data.get(offset)?;
+    offset += 1;
+    let expire_time = match option_len(offset, 8)? {
+        1 => None,
+        _ => Some(i64::from_le_bytes(data.get(offset + 1..offset + 9)?.try_into().ok()?)),
+    };
+
+    Some((owner_wallet, gatekeeper_network, token_state, expire_time))
+}
+
+/// In compliance mode, require deposits above attestation_threshold_lamports
+/// to carry an active, unexpired gateway token issued to the player by the
+/// configured gatekeeper network.
+fn verify_deposit_attestation(
+    state: &HouseboxState,
+    player: &Pubkey,
+    amount: u64,
+    attestation: &Option<UncheckedAccount>,
+    now: i64,
+) -> Result<()> {
+    if state.attestation_threshold_lamports == 0 || amount <= state.attestation_threshold_lamports {
+        return Ok(());
+    }
+
+    let token = attestation.as_ref().ok_or(HouseboxError::AttestationRequired)?;
+    require!(
+        *token.owner == civic_gateway_program::ID,
+        HouseboxError::InvalidAttestation
+    );
+    let data = token.try_borrow_data()?;
+    let (owner_wallet, gatekeeper_network, token_state, expire_time) =
+        gateway_token_fields(&data).ok_or(HouseboxError::InvalidAttestation)?;
+    require!(
+        owner_wallet == *player
+            && gatekeeper_network == state.attestation_gatekeeper_network
+            && token_state == GATEWAY_TOKEN_STATE_ACTIVE
+            && expire_time.is_none_or(|expires| now < expires),
+        HouseboxError::InvalidAttestation
+    );
+
+    Ok(())
+}
+
+/// Emit LowEscrowBalance when a debit of `debited` took the escrow from at or
+/// above the player's low-balance threshold to below it.
+fn emit_if_low_balance(escrow: &PlayerEscrow, debited: u64) -> Result<()> {
+    let threshold = escrow.low_balance_threshold;
+    if threshold > 0 && escrow.balance < threshold && escrow.balance.saturating_add(debited) >= threshold {
+        emit!(LowEscrowBalance {
+            player: escrow.player,
+            balance: escrow.balance,
+            threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+/// Count a deposit against the player's daily deposit limit.
+fn record_player_deposit(escrow: &mut PlayerEscrow, amount: u64, now: i64) -> Result<()> {
+    require!(!escrow.is_excluded(now), HouseboxError::PlayerSelfExcluded);
+    roll_player_limits(escrow, now);
+    escrow.deposited_today = escrow.deposited_today.checked_add(amount)
+        .ok_or(HouseboxError::MathOverflow)?;
+    require!(
+        escrow.daily_deposit_limit == 0 || escrow.deposited_today <= escrow.daily_deposit_limit,
+        HouseboxError::DailyDepositLimitExceeded
+    );
+
+    Ok(())
+}
+
+/// Count a settled loss against the player's daily loss limit.
+fn record_player_loss(escrow: &mut PlayerEscrow, loss: u64, now: i64) -> Result<()> {
+    roll_player_limits(escrow, now);
+    escrow.lost_today = escrow.lost_today.checked_add(loss)
+        .ok_or(HouseboxError::MathOverflow)?;
+    require!(
+        escrow.daily_loss_limit == 0 || escrow.lost_today <= escrow.daily_loss_limit,
+        HouseboxError::DailyLossLimitExceeded
+    );
+
+    Ok(())
+}
+
+/// Make `new_server_pubkey` the primary server key. With a nonzero
+/// server_key_grace_seconds, the old key stays valid for that long.
+fn rotate_server_pubkey(
+    state: &mut HouseboxState,
+    actor: Pubkey,
+    new_server_pubkey: Pubkey,
+    now: i64,
+) -> Result<()> {
+    let old_pubkey = state.server_pubkey;
+    emit_parameter_change(actor, "server_pubkey", old_pubkey, new_server_pubkey)?;
+    state.server_pubkey = new_server_pubkey;
+
+    if state.server_key_grace_seconds > 0 {
+        state.previous_server_pubkey = old_pubkey;
+        state.previous_server_key_expires_at = now.checked_add(state.server_key_grace_seconds)
+            .ok_or(HouseboxError::MathOverflow)?;
+        msg!("Old key valid until timestamp: {}", state.previous_server_key_expires_at);
+    } else {
+        state.previous_server_pubkey = Pubkey::default();
+        state.previous_server_key_expires_at = 0;
+    }
+
+    msg!("Server pubkey updated");
+    msg!("Old: {}", old_pubkey);
+    msg!("New: {}", new_server_pubkey);
+
+    Ok(())
+}
+
+/// Point sweep_treasury at `fee_recipient` (default = treasury vault).
+fn set_fee_recipient(
+    state: &mut HouseboxState,
+    actor: Pubkey,
+    fee_recipient: Pubkey,
+) -> Result<()> {
+    let old_recipient = state.fee_recipient;
+    emit_parameter_change(actor, "fee_recipient", old_recipient, fee_recipient)?;
+    state.fee_recipient = fee_recipient;
+
+    msg!("Fee recipient updated");
+    msg!("Old: {}", old_recipient);
+    msg!("New: {}", fee_recipient);
+
+    Ok(())
+}
+
+/// Set how long a rotated-out primary server key stays valid.
+fn set_server_key_grace(
+    state: &mut HouseboxState,
+    actor: Pubkey,
+    grace_seconds: i64,
+) -> Result<()> {
+    require!(grace_seconds >= 0, HouseboxError::InvalidServerKeyGrace);
+
+    let old_grace = state.server_key_grace_seconds;
+    emit_parameter_change(actor, "server_key_grace_seconds", old_grace, grace_seconds)?;
+    state.server_key_grace_seconds = grace_seconds;
+
+    msg!("Server key grace period updated");
+    msg!("Old: {} seconds", old_grace);
+    msg!("New: {} seconds", grace_seconds);
+
+    Ok(())
+}
+
+/// Authorize an additional server key in a free server_keys slot.
+fn add_server_key_to(
+    state: &mut HouseboxState,
+    actor: Pubkey,
+    server_key: Pubkey,
+) -> Result<()> {
+    require!(
+        server_key != Pubkey::default() && !state.is_server_key(&server_key),
+        HouseboxError::InvalidServerKey
+    );
+    let slot = state.server_keys.iter_mut()
+        .find(|key| **key == Pubkey::default())
+        .ok_or(HouseboxError::ServerKeyListFull)?;
+    emit_parameter_change(actor, "server_keys", *slot, server_key)?;
+    *slot = server_key;
+
+    msg!("Server key added: {}", server_key);
+
+    Ok(())
+}
+
+/// Revoke an additional server key.
+fn remove_server_key_from(
+    state: &mut HouseboxState,
+    actor: Pubkey,
+    server_key: Pubkey,
+) -> Result<()> {
+    require!(server_key != Pubkey::default(), HouseboxError::InvalidServerKey);
+    let slot = state.server_keys.iter_mut()
+        .find(|key| **key == server_key)
+        .ok_or(HouseboxError::InvalidServerKey)?;
+    emit_parameter_change(actor, "server_keys", *slot, Pubkey::default())?;
+    *slot = Pubkey::default();
+
+    msg!("Server key removed: {}", server_key);
+
+    Ok(())
+}
+
+/// Record an admin parameter change as a ParameterChanged event.
+fn emit_parameter_change<T: Into<ParameterValue>>(
+    actor: Pubkey,
+    field: &str,
+    old_value: T,
+    new_value: T,
+) -> Result<()> {
+    emit!(ParameterChanged {
+        actor,
+        field: field.to_string(),
+        old_value: old_value.into(),
+        new_value: new_value.into(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Whether `signer` is the authority or holds `required` (or the admin role)
+/// through `role`.
+fn has_role(
+    state: &HouseboxState,
+    signer: &Pubkey,
+    role: Option<&Role>,
+    required: RoleKind,
+) -> bool {
+    if *signer == state.authority {
+        return true;
+    }
+    match role {
+        Some(role) => {
+            role.holder == *signer && (role.role == required || role.role == RoleKind::Admin)
+        }
+        None => false,
+    }
+}
+
+/// Require the program's upgrade authority to be `authority`. A program
+/// without one (immutable) can't drift, so it passes.
+fn require_upgrade_authority(program_data: &ProgramData, authority: &Pubkey) -> Result<()> {
+    if let Some(upgrade_authority) = program_data.upgrade_authority_address {
+        require!(upgrade_authority == *authority, HouseboxError::UpgradeAuthorityMismatch);
+    }
+
+    Ok(())
+}
+
+/// Check a timelocked parameter change is valid against the current state.
+fn validate_parameter_change(state: &HouseboxState, change: &ParameterChange) -> Result<()> {
+    match *change {
+        ParameterChange::ServerPubkey { .. }
+        | ParameterChange::AddServerKey { .. }
+        | ParameterChange::RemoveServerKey { .. }
+        | ParameterChange::FeeRecipient { .. } => {
+            require!(!state.council_enabled, HouseboxError::CouncilRequired);
+        }
+        ParameterChange::ServerKeyGrace { grace_seconds } => {
+            require!(!state.council_enabled, HouseboxError::CouncilRequired);
+            state.require_not_finalized(FINALIZE_CONFIG)?;
+            require!(grace_seconds >= 0, HouseboxError::InvalidServerKeyGrace);
+        }
+        ParameterChange::LpPercent { lp_percent } => {
+            require!(!state.council_enabled, HouseboxError::CouncilRequired);
+            state.require_not_finalized(FINALIZE_LP_PERCENT)?;
+            require!(lp_percent > 0 && lp_percent <= 100, HouseboxError::InvalidLpPercent);
+        }
+        ParameterChange::RakeBps { rake_bps } => {
+            state.require_not_finalized(FINALIZE_RAKE)?;
+            require!(rake_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+        }
+        ParameterChange::SettlementFee { .. } => {
+            state.require_not_finalized(FINALIZE_SETTLEMENT_FEE)?;
+        }
+        ParameterChange::Timelock { timelock_seconds } => {
+            require!(timelock_seconds >= 0, HouseboxError::InvalidTimelock);
+        }
+    }
+
+    Ok(())
+}
+
+/// Pay out of the treasury vault PDA. The vault can be emptied but not
+/// left below its rent-exempt minimum.
+fn pay_from_treasury<'info>(
+    treasury_vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    treasury_vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, HouseboxError::ZeroAmount);
+    let remaining = treasury_vault.lamports().checked_sub(amount)
+        .ok_or(HouseboxError::InsufficientTreasury)?;
+    require!(
+        remaining == 0 || remaining >= Rent::get()?.minimum_balance(0),
+        HouseboxError::InsufficientTreasury
+    );
+
+    let vault_seeds = &[
+        b"treasury_vault".as_ref(),
+        &[treasury_vault_bump],
+    ];
+    let vault_signer_seeds = &[&vault_seeds[..]];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Transfer {
+                from: treasury_vault.clone(),
+                to: destination.clone(),
+            },
+            vault_signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// Settle `pnl` on a player's escrow the same way on every SOL settlement
+/// path. With the player's open session, the loss is capped at its
+/// reservation, which is released, and a committed seed must have been
+/// revealed. Then the PnL is applied and the play recorded against the
+/// game's exposure and the player's bonus wagering.
+fn settle_player_pnl(
+    state: &mut HouseboxState,
+    escrow: &mut PlayerEscrow,
+    session: Option<&GameSession>,
+    pnl: i64,
+    game_type: u16,
+    total_wagered: u64,
+) -> Result<SettlementSplit> {
+    if let Some(session) = session {
+        require!(session.player == escrow.player, HouseboxError::Unauthorized);
+        require!(
+            pnl >= 0 || pnl.unsigned_abs() <= session.reserved_amount,
+            HouseboxError::LossExceedsReservation
+        );
+        require!(
+            session.seed_commitment == [0u8; 32] || session.seed_revealed,
+            HouseboxError::SeedNotRevealed
+        );
+        escrow.reserved = escrow.reserved.checked_sub(session.reserved_amount)
+            .ok_or(HouseboxError::MathOverflow)?;
+    }
+
+    let split = apply_settlement_pnl(state, escrow, pnl)?;
+    record_settlement_play(state, escrow, game_type, pnl, total_wagered)?;
+
+    Ok(split)
+}
+
+/// Record a settlement's play: game exposure for its game type and wagering
+/// toward the player's bonus release.
+fn record_settlement_play(
+    state: &mut HouseboxState,
+    escrow: &mut PlayerEscrow,
+    game_type: u16,
+    pnl: i64,
+    total_wagered: u64,
+) -> Result<()> {
+    record_game_exposure(state, game_type, pnl, total_wagered)?;
+    record_wagering(escrow, total_wagered)
+}
+
+/// Update a tracked game type's bucket with a settlement. Exposure is the
+/// pool's net payout on the game: player wins add to it, player losses pay
+/// it down. Untracked game types (including 0) are ignored.
+fn record_game_exposure(
+    state: &mut HouseboxState,
+    game_type: u16,
+    pnl: i64,
+    total_wagered: u64,
+) -> Result<()> {
+    if game_type == 0 {
+        return Ok(());
+    }
+    let Some(bucket) = state.game_types.iter_mut().find(|bucket| bucket.game_type == game_type) else {
+        return Ok(());
+    };
+
+    if pnl > 0 {
+        bucket.exposure = bucket.exposure.checked_add(pnl as u64)
+            .ok_or(HouseboxError::MathOverflow)?;
+        require!(
+            bucket.max_exposure == 0 || bucket.exposure <= bucket.max_exposure,
+            HouseboxError::GameExposureExceeded
+        );
+    } else {
+        bucket.exposure = bucket.exposure.saturating_sub(pnl.unsigned_abs());
+    }
+    bucket.house_pnl = bucket.house_pnl.checked_sub(pnl)
+        .ok_or(HouseboxError::MathOverflow)?;
+    bucket.total_wagered = bucket.total_wagered.checked_add(total_wagered)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Record a privileged instruction by `actor` in the admin action log when
+/// the log is enabled. Every authority, council, and governance instruction
+/// calls this through its accounts' log_admin_action before anything else;
+/// migrate_state calls it once the state account has been loaded.
+fn record_admin_action(
+    state: &HouseboxState,
+    admin_log: Option<&mut Account<'_, AdminLog>>,
+    actor: Pubkey,
+    instruction: &str,
+) -> Result<()> {
+    if !state.admin_log_enabled {
+        return Ok(());
+    }
+    let log = admin_log.ok_or(HouseboxError::AdminLogRequired)?;
+
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(
+        &hashv(&[b"global:", instruction.as_bytes()]).to_bytes()[..8],
+    );
+    log.record(AdminLogEntry {
+        instruction: discriminator,
+        actor,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Grow a program account to `new_len` with zeroed trailing bytes, `payer`
+/// topping up rent. Returns the old length; an account already at least
+/// `new_len` is left alone.
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<usize> {
+    let old_len = info.data_len();
+    if old_len >= new_len {
+        return Ok(old_len);
+    }
+
+    let top_up = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    info.realloc(new_len, true)?;
+
+    Ok(old_len)
+}
+
+/// Reject a withdrawal above the single-withdrawal cap. Every path that pays
+/// escrow SOL out of the vault checks it.
+fn check_max_withdrawal(state: &HouseboxState, amount: u64) -> Result<()> {
+    require!(
+        state.max_withdrawal_lamports == 0 || amount <= state.max_withdrawal_lamports,
+        HouseboxError::WithdrawalExceedsMax
+    );
+
+    Ok(())
+}
+
+/// Check a server-approved withdrawal against the single-withdrawal cap,
+/// count it against the rolling 24h withdrawal cap, and emit PlayerWithdrew.
+/// A window opens with the first withdrawal after the previous window has
+/// elapsed. Every server-approved withdrawal path goes through here.
+fn record_player_withdrawal(
+    state: &HouseboxState,
+    escrow: &mut PlayerEscrow,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    check_max_withdrawal(state, amount)?;
+
+    if now >= escrow.withdrawal_window_start + SECONDS_PER_DAY {
+        escrow.withdrawal_window_start = now;
+        escrow.withdrawn_in_window = 0;
+    }
+    escrow.withdrawn_in_window = escrow.withdrawn_in_window.checked_add(amount)
+        .ok_or(HouseboxError::MathOverflow)?;
+    require!(
+        state.daily_withdrawal_limit_lamports == 0
+            || escrow.withdrawn_in_window <= state.daily_withdrawal_limit_lamports,
+        HouseboxError::DailyWithdrawalLimitExceeded
+    );
+
+    emit!(PlayerWithdrew {
+        player: escrow.player,
+        amount,
+        affiliate: escrow.affiliate,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// Ed25519 program instruction layout (single signature, data inline)
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Voucher message for player_settle_voucher.
+fn settle_voucher_message(
+    player: &Pubkey,
+    pnl: i64,
+    session_id: &[u8; 32],
+    game_type: u16,
+    wager_count: u32,
+    total_wagered: u64,
+    expires_at: i64,
+) -> Vec<u8> {
+    let mut message = b"housebox:settle".to_vec();
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(player.as_ref());
+    message.extend_from_slice(&pnl.to_le_bytes());
+    message.extend_from_slice(session_id);
+    message.extend_from_slice(&game_type.to_le_bytes());
+    message.extend_from_slice(&wager_count.to_le_bytes());
+    message.extend_from_slice(&total_wagered.to_le_bytes());
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+/// Voucher message for player_withdraw_voucher.
+fn withdraw_voucher_message(
+    player: &Pubkey,
+    amount_lamports: u64,
+    nonce: u64,
+    expires_at: i64,
+) -> Vec<u8> {
+    let mut message = b"housebox:withdraw".to_vec();
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(player.as_ref());
+    message.extend_from_slice(&amount_lamports.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+/// Voucher message for player_settle_cosigned.
+fn cosigned_settle_message(
+    player: &Pubkey,
+    pnl: i64,
+    session_id: &[u8; 32],
+    game_type: u16,
+    wager_count: u32,
+    total_wagered: u64,
+    expires_at: i64,
+) -> Vec<u8> {
+    let mut message = b"housebox:settle_cosigned".to_vec();
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(player.as_ref());
+    message.extend_from_slice(&pnl.to_le_bytes());
+    message.extend_from_slice(session_id);
+    message.extend_from_slice(&game_type.to_le_bytes());
+    message.extend_from_slice(&wager_count.to_le_bytes());
+    message.extend_from_slice(&total_wagered.to_le_bytes());
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+/// Check that the instruction before the current one is an Ed25519 program
+/// instruction verifying one signature by `server` over exactly `message`,
+/// with signature, key, and message all inline in that instruction.
+fn verify_server_voucher(instructions: &AccountInfo, state: &HouseboxState, message: &[u8]) -> Result<()> {
+    let (signed_pubkey, signed_message) = ed25519_signature_before(instructions, 1)?;
+    require!(
+        state.is_server_key(&signed_pubkey) && signed_message == message,
+        HouseboxError::InvalidServerSignature
+    );
+
+    Ok(())
+}
+
+/// Read the Ed25519 program instruction `back` positions before the current
+/// one, which must verify a single signature with signature, key, and
+/// message all inline, and return the signing key and signed message.
+fn ed25519_signature_before(instructions: &AccountInfo, back: usize) -> Result<(Pubkey, Vec<u8>)> {
+    let current = ix_sysvar::load_current_index_checked(instructions)? as usize;
+    require!(current >= back, HouseboxError::InvalidVoucher);
+    let ix = ix_sysvar::load_instruction_at_checked(current - back, instructions)?;
+    require!(ix.program_id == ed25519_program::ID, HouseboxError::InvalidVoucher);
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_OFFSETS_START + ED25519_OFFSETS_LEN && data[0] == 1,
+        HouseboxError::InvalidVoucher
+    );
+    let read_u16 = |offset: usize| -> usize {
+        u16::from_le_bytes([data[offset], data[offset + 1]]) as usize
+    };
+    let offsets = ED25519_OFFSETS_START;
+    let signature_ix = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4);
+    let pubkey_ix = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8);
+    let message_size = read_u16(offsets + 10);
+    let message_ix = read_u16(offsets + 12);
+
+    // All data must live in the Ed25519 instruction itself
+    let inline = u16::MAX as usize;
+    require!(
+        signature_ix == inline && pubkey_ix == inline && message_ix == inline,
+        HouseboxError::InvalidVoucher
+    );
+
+    let signed_pubkey = data.get(pubkey_offset..pubkey_offset + 32)
+        .and_then(|bytes| Pubkey::try_from(bytes).ok())
+        .ok_or(HouseboxError::InvalidVoucher)?;
+    let signed_message = data.get(message_offset..message_offset + message_size)
+        .ok_or(HouseboxError::InvalidVoucher)?;
+
+    Ok((signed_pubkey, signed_message.to_vec()))
+}
+
+/// Merkle leaf for a settlement:
+/// sha256(0x00 || player || pnl (i64 LE) || session_id || game_type (u16 LE)
+/// || wager_count (u32 LE) || total_wagered (u64 LE) || expires_at (i64 LE)).
+fn settlement_leaf(
+    player: &Pubkey,
+    pnl: i64,
+    session_id: &[u8; 32],
+    game_type: u16,
+    wager_count: u32,
+    total_wagered: u64,
+    expires_at: i64,
+) -> [u8; 32] {
+    hashv(&[
+        &[0u8],
+        player.as_ref(),
+        &pnl.to_le_bytes(),
+        session_id,
+        &game_type.to_le_bytes(),
+        &wager_count.to_le_bytes(),
+        &total_wagered.to_le_bytes(),
+        &expires_at.to_le_bytes(),
+    ]).to_bytes()
+}
+
+/// Verify a Merkle proof with sorted-pair internal nodes: sha256(0x01 || min || max).
+fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (a, b) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[&[1u8], &a, &b]).to_bytes()
+    });
+    computed == *root
+}
+
+/// Record a redemption request (shared by request_redemption and request_redemption_max).
+fn record_redemption_request(
+    ctx: Context<RequestRedemption>,
+    vtoken_amount: u64,
+    redeem_all: bool,
+    execute_after: Option<i64>,
+) -> Result<()> {
+    let state = &ctx.accounts.housebox_state;
+    require!(!state.is_paused() || state.exodus_mode, HouseboxError::ProtocolPaused);
+    require!(state.vsum > 0, HouseboxError::NoLiquidity);
+
+    let now = Clock::get()?.unix_timestamp;
+    let earliest = now.checked_add(REDEMPTION_DELAY_SECONDS)
+        .ok_or(HouseboxError::MathOverflow)?;
+    let latest = now.checked_add(MAX_REDEMPTION_SCHEDULE_SECONDS)
+        .ok_or(HouseboxError::MathOverflow)?;
+    let execute_after = execute_after.unwrap_or(earliest);
+    require!(execute_after >= earliest, HouseboxError::ExecuteAfterTooSoon);
+    require!(execute_after <= latest, HouseboxError::ExecuteAfterTooLate);
+
+    // Create redemption request (intent only — no token operations)
+    let request = &mut ctx.accounts.redemption_request;
+    request.lp = ctx.accounts.lp.key();
+    request.vtoken_amount = vtoken_amount;
+    request.requested_at = now;
+    request.bump = ctx.bumps.redemption_request;
+    request.extended = false;
+    request.execute_after = execute_after;
+    request.redeem_all = redeem_all;
+
+    msg!("Ready at timestamp: {}", request.ready_at());
+
+    Ok(())
+}
+
+/// Set up a fresh HouseboxState (shared by the legacy and Token-2022 initializers).
+fn init_housebox_state(
+    state: &mut HouseboxState,
+    authority: Pubkey,
+    server_pubkey: Pubkey,
+    vtoken_mint: Pubkey,
+    lp_percent: u8,
+) -> Result<()> {
+    require!(lp_percent > 0 && lp_percent <= 100, HouseboxError::InvalidLpPercent);
+
+    state.authority = authority;
+    state.server_pubkey = server_pubkey;
+    state.pause_authority = authority;
+    state.vtoken_mint = vtoken_mint;
+    state.lp_percent = lp_percent;
+    state.paused = false;
+    state.solsum = 0;
+    state.vsum = 0;
+    state.min_lp_lock_lamports = 0;
+    state.lst_sol_value = 0;
+    state.rate_sync_exchange_rate = 0;
+    state.rate_synced_at = 0;
+    state.redemption_extension_fee_lamports = 0;
+    state.lp_whitelist_enabled = false;
+    state.staked_vtokens = 0;
+    state.stake_reward_per_token = 0;
+    state.stake_rewards_reserved = 0;
+    state.protocol_vesting_period_seconds = 0;
+    state.protocol_vesting_balance = 0;
+    state.protocol_vesting_unlocked = 0;
+    state.protocol_vesting_checkpoint = 0;
+    state.protocol_vesting_end = 0;
+    state.lp_referral_bps = 0;
+    state.dynamic_lp_percent_max = lp_percent;
+    state.dynamic_tvl_target_lamports = 0;
+    state.performance_fee_bps = 0;
+    state.max_vsum = 0;
+    state.early_exit_penalty_bps = 0;
+    state.early_exit_decay_seconds = 0;
+    state.redemption_stream_seconds = 0;
+    state.redemption_streams_reserved = 0;
+    state.max_settlement_pnl = 0;
+    state.session_timeout_seconds = 0;
+    state.player_referral_bps = 0;
+    state.dispute_window_seconds = 0;
+    state.dispute_authority = authority;
+    state.server_keys = [Pubkey::default(); MAX_EXTRA_SERVER_KEYS];
+    state.server_key_grace_seconds = 0;
+    state.previous_server_pubkey = Pubkey::default();
+    state.previous_server_key_expires_at = 0;
+    state.rake_bps = 0;
+    state.treasury_balance = 0;
+    state.jackpot_contribution_bps = 0;
+    state.jackpot_balance = 0;
+    state.insurance_contribution_bps = 0;
+    state.insurance_balance = 0;
+    state.solvency_floor_lamports = 0;
+    state.daily_withdrawal_limit_lamports = 0;
+    state.max_withdrawal_lamports = 0;
+    state.escrow_yield_bps = 0;
+    state.escrow_yield_staked = 0;
+    state.escrow_yield_per_lamport = 0;
+    state.escrow_yield_reserved = 0;
+    state.attestation_threshold_lamports = 0;
+    state.attestation_gatekeeper_network = Pubkey::default();
+    state.settlement_fee_lamports = 0;
+    state.operator_fee_balance = 0;
+    state.deposit_match_bps = 0;
+    state.deposit_match_budget_lamports = 0;
+    state.deposit_match_min_lamports = 0;
+    state.deposit_match_wagering_multiplier = 0;
+    state.settle_while_paused = false;
+    state.game_types = [GameTypeBucket::default(); MAX_GAME_TYPES];
+    state.min_session_escrow_lamports = 0;
+    state.pending_authority = Pubkey::default();
+    state.council_enabled = false;
+    state.parameter_timelock_seconds = 0;
+    state.parameter_change_count = 0;
+    state.guardian = Pubkey::default();
+    state.config_enabled = false;
+    state.layout_version = STATE_LAYOUT_VERSION;
+    state.evacuation_address = Pubkey::default();
+    state.evacuation_requested_at = 0;
+    state.finalized_params = 0;
+    state.admin_log_enabled = false;
+    state.fee_recipient = Pubkey::default();
+    state.maintenance_start = 0;
+    state.maintenance_end = 0;
+    state.sunset_mode = false;
+    state.guardian_pause_seconds = DEFAULT_GUARDIAN_PAUSE_SECONDS;
+    state.guardian_pause_expires_at = 0;
+    state.bonus_budget_lamports = 0;
+    state.performance_high_water_rate = EXCHANGE_RATE_SCALE as u64;
+    state.stake_rake_bps = 0;
+    state.pool_migration_version = 0;
+    state.pool_migration_requested_at = 0;
+    state.exodus_mode = false;
+    state.version = POOL_STATE_VERSION;
+    state.migrated_to = Pubkey::default();
+
+    Ok(())
+}
+
+/// Lamports per vToken, scaled by EXCHANGE_RATE_SCALE.
+fn exchange_rate_of(state: &HouseboxState) -> Result<u64> {
+    require!(state.vsum > 0, HouseboxError::NoLiquidity);
+    let rate = (state.solsum as u128)
+        .checked_mul(EXCHANGE_RATE_SCALE)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_div(state.vsum as u128)
+        .ok_or(HouseboxError::MathOverflow)?;
+    u64::try_from(rate).map_err(|_| HouseboxError::MathOverflow.into())
+}
+
+/// Natural log of `num / den`, scaled by LN_SCALE, in integer arithmetic.
+/// The ratio is halved into [1, 2) (each halving adds ln 2), then the rest
+/// is the atanh series ln(m) = 2 * (z + z^3/3 + z^5/5 + ...) with
+/// z = (m - 1) / (m + 1) <= 1/3, summed until its terms vanish.
+fn ln_ratio(num: u64, den: u64) -> Result<i128> {
+    require!(num > 0 && den > 0, HouseboxError::MathOverflow);
+    let negative = num < den;
+    let (num, mut den) = if negative {
+        (den as u128, num as u128)
+    } else {
+        (num as u128, den as u128)
+    };
+
+    let mut halvings: u128 = 0;
+    while num >= den * 2 {
+        den *= 2;
+        halvings += 1;
+    }
+
+    let m = num * LN_SCALE / den;
+    let z = (m - LN_SCALE) * LN_SCALE / (m + LN_SCALE);
+    let z_squared = z * z / LN_SCALE;
+    let mut term = z;
+    let mut series: u128 = 0;
+    let mut divisor: u128 = 1;
+    while term > 0 {
+        series += term / divisor;
+        term = term * z_squared / LN_SCALE;
+        divisor += 2;
+    }
+
+    let ln = (halvings * LN_2_SCALED + 2 * series) as i128;
+    Ok(if negative { -ln } else { ln })
+}
+
+/// Release the linearly vested part of the protocol vesting balance into
+/// protocol_vesting_unlocked, up to `now`.
+fn checkpoint_protocol_vesting(state: &mut HouseboxState, now: i64) -> Result<()> {
+    let locked = state.protocol_vesting_balance.checked_sub(state.protocol_vesting_unlocked)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    if now >= state.protocol_vesting_end {
+        state.protocol_vesting_unlocked = state.protocol_vesting_balance;
+    } else if now > state.protocol_vesting_checkpoint {
+        let released = (locked as u128)
+            .checked_mul((now - state.protocol_vesting_checkpoint) as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div((state.protocol_vesting_end - state.protocol_vesting_checkpoint) as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64;
+        state.protocol_vesting_unlocked = state.protocol_vesting_unlocked.checked_add(released)
+            .ok_or(HouseboxError::MathOverflow)?;
+    }
+    state.protocol_vesting_checkpoint = now;
+
+    Ok(())
+}
+
+/// Add newly minted protocol vTokens to the vesting balance. The schedule's
+/// end moves to the amount-weighted average of the current end and a fresh
+/// period, so top-ups don't restart vesting of what is already locked.
+fn accrue_protocol_vesting(state: &mut HouseboxState, amount: u64, now: i64) -> Result<()> {
+    checkpoint_protocol_vesting(state, now)?;
+    if amount == 0 {
+        return Ok(());
+    }
+    let locked = state.protocol_vesting_balance - state.protocol_vesting_unlocked;
+    state.protocol_vesting_end = weighted_vesting_end(
+        locked,
+        state.protocol_vesting_end,
+        amount,
+        now,
+        state.protocol_vesting_period_seconds,
+    )?;
+    state.protocol_vesting_balance = state.protocol_vesting_balance.checked_add(amount)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// End of a linear schedule after adding `amount` to `locked` still vesting
+/// until `end`: the amount-weighted average of `end` and `now + period`.
+fn weighted_vesting_end(locked: u64, end: i64, amount: u64, now: i64, period: i64) -> Result<i64> {
+    let fresh_end = now.checked_add(period)
+        .ok_or(HouseboxError::MathOverflow)?;
+    if locked == 0 || end <= now {
+        return Ok(fresh_end);
+    }
+
+    let weighted = (locked as i128 * end as i128 + amount as i128 * fresh_end as i128)
+        / (locked as i128 + amount as i128);
+    i64::try_from(weighted).map_err(|_| HouseboxError::MathOverflow.into())
+}
+
+/// Release the linearly vested part of a redemption stream into `unlocked`, up to `now`.
+fn checkpoint_redemption_stream(stream: &mut RedemptionStream, now: i64) -> Result<()> {
+    let locked = stream.balance.checked_sub(stream.unlocked)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    if now >= stream.end {
+        stream.unlocked = stream.balance;
+    } else if now > stream.checkpoint {
+        let released = (locked as u128)
+            .checked_mul((now - stream.checkpoint) as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div((stream.end - stream.checkpoint) as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64;
+        stream.unlocked = stream.unlocked.checked_add(released)
+            .ok_or(HouseboxError::MathOverflow)?;
+    }
+    stream.checkpoint = now;
+
+    Ok(())
+}
+
+/// Add a redemption payout to a stream. As with protocol vesting, the end
+/// moves to the amount-weighted average of the current end and a fresh
+/// window, so a new payout doesn't hold back earlier ones.
+fn accrue_redemption_stream(
+    stream: &mut RedemptionStream,
+    amount: u64,
+    window_seconds: i64,
+    now: i64,
+) -> Result<()> {
+    checkpoint_redemption_stream(stream, now)?;
+    let locked = stream.balance - stream.unlocked;
+    stream.end = weighted_vesting_end(locked, stream.end, amount, now, window_seconds)?;
+    stream.balance = stream.balance.checked_add(amount)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Credit lamports already in the vault to stakers via the reward accumulator.
+fn distribute_stake_rewards(state: &mut HouseboxState, amount_lamports: u64) -> Result<()> {
+    require!(state.staked_vtokens > 0, HouseboxError::NoStakers);
+
+    let increment = (amount_lamports as u128)
+        .checked_mul(STAKE_REWARD_SCALE)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_div(state.staked_vtokens as u128)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    state.stake_reward_per_token = state.stake_reward_per_token.checked_add(increment)
+        .ok_or(HouseboxError::MathOverflow)?;
+    state.stake_rewards_reserved = state.stake_rewards_reserved.checked_add(amount_lamports)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Move a stake position's rewards since its last snapshot into pending_rewards.
+fn accrue_stake_rewards(position: &mut StakePosition, reward_per_token: u128) -> Result<()> {
+    let delta = reward_per_token.checked_sub(position.reward_per_token_paid)
+        .ok_or(HouseboxError::MathOverflow)?;
+    let accrued = (position.staked_amount as u128)
+        .checked_mul(delta)
+        .ok_or(HouseboxError::MathOverflow)?
+        / STAKE_REWARD_SCALE;
+
+    position.pending_rewards = position.pending_rewards
+        .checked_add(u64::try_from(accrued).map_err(|_| HouseboxError::MathOverflow)?)
+        .ok_or(HouseboxError::MathOverflow)?;
+    position.reward_per_token_paid = reward_per_token;
+
+    Ok(())
+}
+
+/// SOL paid for redeeming `vtoken_amount` at the current ratio.
+/// Redemptions pay SOL, so they can only draw on the liquid (non-LST) part of the pool.
+fn redemption_sol_out(state: &HouseboxState, vtoken_amount: u64) -> Result<u64> {
+    require!(state.vsum > 0, HouseboxError::NoLiquidity);
+
+    let sol_out = (vtoken_amount as u128)
+        .checked_mul(state.solsum as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_div(state.vsum as u128)
+        .ok_or(HouseboxError::MathOverflow)? as u64;
+
+    require!(sol_out > 0, HouseboxError::AmountTooSmall);
+    require!(state.liquid_solsum() >= sol_out, HouseboxError::InsufficientLiquidity);
+
+    Ok(sol_out)
+}
+
+/// Size a deposit-match bonus: match_bps of the deposit, capped by the
+/// remaining budget and the protocol's vTokens, redeemed at the current ratio.
+/// Returns (vTokens to burn, bonus lamports), or None when the deposit doesn't
+/// qualify or the promotion can't fund a bonus.
+fn deposit_match_redemption(
+    state: &HouseboxState,
+    amount: u64,
+    protocol_vtokens: u64,
+) -> Result<Option<(u64, u64)>> {
+    if state.deposit_match_bps == 0
+        || amount < state.deposit_match_min_lamports
+        || state.vsum == 0
+        || state.solsum == 0
+    {
+        return Ok(None);
+    }
+
+    let target = ((amount as u128)
+        .checked_mul(state.deposit_match_bps as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        / BPS_DENOMINATOR as u128)
+        .min(state.deposit_match_budget_lamports as u128);
+
+    // Round the redemption down so the bonus never exceeds the target
+    let vtoken_amount = (target
+        .checked_mul(state.vsum as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        / state.solsum as u128)
+        .min(protocol_vtokens as u128) as u64;
+    let bonus = (vtoken_amount as u128)
+        .checked_mul(state.solsum as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        / state.vsum as u128;
+    let bonus = u64::try_from(bonus).map_err(|_| HouseboxError::MathOverflow)?;
+
+    if vtoken_amount == 0 || bonus == 0 || state.liquid_solsum() < bonus {
+        return Ok(None);
+    }
+
+    Ok(Some((vtoken_amount, bonus)))
+}
+
+/// Exchange rate for pricing positions; 1:1 (bootstrap) when the pool is empty.
+fn current_exchange_rate(state: &HouseboxState) -> Result<u64> {
+    if state.vsum == 0 {
+        Ok(EXCHANGE_RATE_SCALE as u64)
+    } else {
+        exchange_rate_of(state)
+    }
+}
+
+/// Add newly minted LP vTokens to the position and restart its lock clock.
+fn record_lp_position_entry(
+    position: &mut LpPosition,
+    lp_vtokens: u64,
+) -> Result<()> {
+    position.vtokens = position.vtokens.checked_add(lp_vtokens)
+        .ok_or(HouseboxError::MathOverflow)?;
+    position.last_locked_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+/// Early-exit penalty (bps) for a redemption at `now`: early_exit_penalty_bps
+/// right after the position's last lock, decaying linearly to zero.
+fn early_exit_penalty_bps(state: &HouseboxState, position: &LpPosition, now: i64) -> Result<u64> {
+    let decay = state.early_exit_decay_seconds;
+    let elapsed = now.saturating_sub(position.last_locked_at);
+    if decay <= 0 || elapsed >= decay {
+        return Ok(0);
+    }
+
+    let remaining = (decay - elapsed) as u128;
+    let bps = (state.early_exit_penalty_bps as u128)
+        .checked_mul(remaining)
+        .ok_or(HouseboxError::MathOverflow)?
+        / decay as u128;
+
+    Ok(bps as u64)
+}
+
+/// Early-exit penalty (lamports) on redeeming `vtoken_amount` worth `sol_out`.
+/// vTokens the position locked pay the decayed rate from its lock clock; any
+/// beyond that (received by transfer, never locked or synced by this LP) pay
+/// the full early_exit_penalty_bps, so moving vTokens to a fresh wallet does
+/// not reset the clock to zero.
+fn early_exit_penalty_lamports(
+    state: &HouseboxState,
+    position: &LpPosition,
+    vtoken_amount: u64,
+    sol_out: u64,
+    now: i64,
+) -> Result<u64> {
+    if vtoken_amount == 0 {
+        return Ok(0);
+    }
+
+    let tracked = vtoken_amount.min(position.vtokens);
+    let untracked = vtoken_amount - tracked;
+    let weighted_bps = (tracked as u128)
+        .checked_mul(early_exit_penalty_bps(state, position, now)? as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_add(
+            (untracked as u128)
+                .checked_mul(state.early_exit_penalty_bps as u128)
+                .ok_or(HouseboxError::MathOverflow)?,
+        )
+        .ok_or(HouseboxError::MathOverflow)?;
+    let penalty = (sol_out as u128)
+        .checked_mul(weighted_bps)
+        .ok_or(HouseboxError::MathOverflow)?
+        / (vtoken_amount as u128 * BPS_DENOMINATOR as u128);
+
+    Ok(penalty as u64)
+}
+
+/// Charge the performance fee on pool-wide gains: when the exchange rate is
+/// above the high-water rate, performance_fee_bps of the lamport gain on all
+/// outstanding vTokens moves from solsum to the treasury and the high-water
+/// rate is raised to the post-fee rate. Runs before every lock and redemption
+/// prices vTokens, so the fee does not depend on which wallet holds them.
+fn crystallize_performance_fee(state: &mut HouseboxState) -> Result<u64> {
+    if state.vsum == 0 {
+        // Empty pool restarts at the bootstrap rate
+        state.performance_high_water_rate = EXCHANGE_RATE_SCALE as u64;
+        return Ok(0);
+    }
+
+    let rate = exchange_rate_of(state)?;
+    let high_water = state.performance_high_water_rate;
+    if state.performance_fee_bps == 0 || high_water == 0 || rate <= high_water {
+        state.performance_high_water_rate = high_water.max(rate);
+        return Ok(0);
+    }
+
+    let fee = (state.vsum as u128)
+        .checked_mul((rate - high_water) as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_mul(state.performance_fee_bps as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        / (EXCHANGE_RATE_SCALE * BPS_DENOMINATOR as u128);
+    let fee = u64::try_from(fee)
+        .map_err(|_| HouseboxError::MathOverflow)?
+        .min(state.liquid_solsum());
+
+    state.solsum = state.solsum.checked_sub(fee)
+        .ok_or(HouseboxError::MathOverflow)?;
+    state.treasury_balance = state.treasury_balance.checked_add(fee)
+        .ok_or(HouseboxError::MathOverflow)?;
+    state.performance_high_water_rate = exchange_rate_of(state)?;
+
+    msg!("Performance fee: {} lamports to treasury", fee);
+
+    Ok(fee)
+}
+
+/// vTokens minted for a lock, split between LP and protocol.
+pub struct LockSplit {
+    pub vtokens_to_mint: u64,
+    pub lp_vtokens: u64,
+    pub protocol_vtokens: u64,
+}
+
+/// LP share for new locks. With a dynamic haircut configured, the share
+/// rises linearly from lp_percent at the TVL target toward
+/// dynamic_lp_percent_max as solsum approaches zero.
+fn effective_lp_percent(state: &HouseboxState) -> Result<u8> {
+    let target = state.dynamic_tvl_target_lamports;
+    if target == 0 || state.solsum >= target {
+        return Ok(state.lp_percent);
+    }
+
+    let spread = state.dynamic_lp_percent_max.saturating_sub(state.lp_percent) as u128;
+    let bonus = spread
+        .checked_mul((target - state.solsum) as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        / target as u128;
+
+    Ok(state.lp_percent + bonus as u8)
+}
+
+/// Compute the rate-aware vToken mint for a deposit worth `value_lamports`.
+fn compute_lock_split(state: &HouseboxState, value_lamports: u64) -> Result<LockSplit> {
+    let vtokens_to_mint = if state.solsum == 0 && state.vsum == 0 {
+        // Bootstrap: 1:1 ratio (lamports to vTokens)
+        value_lamports
+    } else {
+        // Proportional: vtokens = amount * vsum / solsum
+        (value_lamports as u128)
+            .checked_mul(state.vsum as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div(state.solsum as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64
+    };
+
+    require!(vtokens_to_mint > 0, HouseboxError::AmountTooSmall);
+
+    // Governance cap on outstanding vTokens (0 = uncapped)
+    if state.max_vsum > 0 {
+        let new_vsum = state.vsum.checked_add(vtokens_to_mint)
+            .ok_or(HouseboxError::MathOverflow)?;
+        require!(new_vsum <= state.max_vsum, HouseboxError::VsumCapExceeded);
+    }
+
+    // Split: LP gets lp_percent (utilization-adjusted), protocol gets the rest
+    let lp_vtokens = vtokens_to_mint
+        .checked_mul(effective_lp_percent(state)? as u64)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_div(100)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    let protocol_vtokens = vtokens_to_mint.checked_sub(lp_vtokens)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    Ok(LockSplit { vtokens_to_mint, lp_vtokens, protocol_vtokens })
+}
+
+/// Compute the rate-aware vToken mint for an asset-pool deposit of `amount`.
+fn compute_asset_lock_split(pool: &AssetPool, amount: u64) -> Result<LockSplit> {
+    let vtokens_to_mint = if pool.asset_sum == 0 && pool.vsum == 0 {
+        // Bootstrap: 1:1 ratio (asset units to vTokens)
+        amount
+    } else {
+        // Proportional: vtokens = amount * vsum / asset_sum
+        (amount as u128)
+            .checked_mul(pool.vsum as u128)
+            .ok_or(HouseboxError::MathOverflow)?
+            .checked_div(pool.asset_sum as u128)
+            .ok_or(HouseboxError::MathOverflow)? as u64
+    };
+
+    require!(vtokens_to_mint > 0, HouseboxError::AmountTooSmall);
+
+    let lp_vtokens = vtokens_to_mint
+        .checked_mul(pool.lp_percent as u64)
+        .ok_or(HouseboxError::MathOverflow)?
+        / 100;
+    let protocol_vtokens = vtokens_to_mint.checked_sub(lp_vtokens)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    Ok(LockSplit { vtokens_to_mint, lp_vtokens, protocol_vtokens })
+}
+
+/// Mint a lock's vTokens to the LP and protocol destination (housebox_state signs).
+/// The protocol destination is the protocol vToken account or, with vesting
+/// enabled, the protocol vesting vault.
+fn mint_lock_vtokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vtoken_mint: &InterfaceAccount<'info, Mint>,
+    lp_vtoken_account: &InterfaceAccount<'info, TokenAccount>,
+    protocol_destination: &InterfaceAccount<'info, TokenAccount>,
+    housebox_state: &Account<'info, HouseboxState>,
+    signer_seeds: &[&[&[u8]]],
+    split: &LockSplit,
+) -> Result<()> {
+    // Mint vTokens to LP
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_interface::MintTo {
+                mint: vtoken_mint.to_account_info(),
+                to: lp_vtoken_account.to_account_info(),
+                authority: housebox_state.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        split.lp_vtokens,
+    )?;
+
+    // Mint vTokens to protocol
+    if split.protocol_vtokens > 0 {
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token_interface::MintTo {
+                    mint: vtoken_mint.to_account_info(),
+                    to: protocol_destination.to_account_info(),
+                    authority: housebox_state.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            split.protocol_vtokens,
+        )?;
+    }
+
+    Ok(())
+}
+
+// SPL stake pool account layout offsets (borsh, fixed prefix)
+const STAKE_POOL_POOL_MINT_OFFSET: usize = 162;
+const STAKE_POOL_TOTAL_LAMPORTS_OFFSET: usize = 258;
+/// SPL stake pool WithdrawSol instruction tag
+const SPL_STAKE_POOL_WITHDRAW_SOL: u8 = 16;
+const STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+const STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET: usize = 274;
+
+/// Re-mark an LST's locked balance at the stake pool's current rate, moving
+/// the change since its last mark into solsum and lst_sol_value. Returns the
+/// new lamport value.
+fn remark_lst_value(
+    state: &mut HouseboxState,
+    lst_config: &mut LstConfig,
+    stake_pool: &AccountInfo,
+) -> Result<u64> {
+    let value = stake_pool_lamports_for(stake_pool, &lst_config.lst_mint, lst_config.total_locked)?;
+    let old_value = lst_config.sol_value;
+    if value >= old_value {
+        let gain = value - old_value;
+        state.solsum = state.solsum.checked_add(gain)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.lst_sol_value = state.lst_sol_value.checked_add(gain)
+            .ok_or(HouseboxError::MathOverflow)?;
+    } else {
+        let loss = old_value - value;
+        state.solsum = state.solsum.checked_sub(loss)
+            .ok_or(HouseboxError::MathOverflow)?;
+        state.lst_sol_value = state.lst_sol_value.checked_sub(loss)
+            .ok_or(HouseboxError::MathOverflow)?;
+    }
+    lst_config.sol_value = value;
+
+    Ok(value)
+}
+
+/// Value `amount_lst` pool tokens in lamports at the SPL stake pool exchange rate.
+/// Rejects pools not owned by the stake pool program, pools for a different
+/// mint, and pools whose rate was not updated this epoch.
+fn stake_pool_lamports_for(
+    stake_pool: &AccountInfo,
+    lst_mint: &Pubkey,
+    amount_lst: u64,
+) -> Result<u64> {
+    require!(
+        *stake_pool.owner == spl_stake_pool_program::ID,
+        HouseboxError::InvalidStakePool
+    );
+
+    let data = stake_pool.try_borrow_data()?;
+    require!(
+        data.len() >= STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET + 8,
+        HouseboxError::InvalidStakePool
+    );
+
+    let read_u64 = |offset: usize| -> u64 {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&data[offset..offset + 8]);
+        u64::from_le_bytes(buf)
+    };
+
+    let pool_mint = Pubkey::try_from(
+        &data[STAKE_POOL_POOL_MINT_OFFSET..STAKE_POOL_POOL_MINT_OFFSET + 32],
+    )
+    .map_err(|_| HouseboxError::InvalidStakePool)?;
+    require!(pool_mint == *lst_mint, HouseboxError::InvalidStakePool);
+
+    let last_update_epoch = read_u64(STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET);
+    require!(
+        last_update_epoch == Clock::get()?.epoch,
+        HouseboxError::StaleOracle
+    );
+
+    let total_lamports = read_u64(STAKE_POOL_TOTAL_LAMPORTS_OFFSET);
+    let pool_token_supply = read_u64(STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET);
+    require!(pool_token_supply > 0, HouseboxError::InvalidStakePool);
+
+    let value = (amount_lst as u128)
+        .checked_mul(total_lamports as u128)
+        .ok_or(HouseboxError::MathOverflow)?
+        .checked_div(pool_token_supply as u128)
+        .ok_or(HouseboxError::MathOverflow)?;
+
+    u64::try_from(value).map_err(|_| HouseboxError::MathOverflow.into())
+}
+
+// Switchboard on-demand RandomnessAccountData layout (after the 8-byte
+// discriminator: authority, queue, seed_slothash, seed_slot, oracle,
+// reveal_slot, value)
+const SWITCHBOARD_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [10, 66, 229, 135, 220, 239, 217, 114];
+const SWITCHBOARD_SEED_SLOT_OFFSET: usize = 104;
+const SWITCHBOARD_REVEAL_SLOT_OFFSET: usize = 144;
+const SWITCHBOARD_VALUE_OFFSET: usize = 152;
+
+/// Read (seed_slot, reveal_slot, value) from a Switchboard randomness
+/// account. Rejects accounts not owned by the on-demand program or of
+/// another type; reveal_slot is 0 until the oracle reveals.
+fn read_switchboard_randomness(randomness: &AccountInfo) -> Result<(u64, u64, [u8; 32])> {
+    require!(
+        *randomness.owner == switchboard_on_demand_program::ID,
+        HouseboxError::InvalidRandomnessAccount
+    );
+
+    let data = randomness.try_borrow_data()?;
+    require!(
+        data.len() >= SWITCHBOARD_VALUE_OFFSET + 32
+            && data[..8] == SWITCHBOARD_RANDOMNESS_DISCRIMINATOR,
+        HouseboxError::InvalidRandomnessAccount
+    );
+
+    let read_u64 = |offset: usize| -> u64 {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&data[offset..offset + 8]);
+        u64::from_le_bytes(buf)
+    };
+
+    let mut value = [0u8; 32];
+    value.copy_from_slice(&data[SWITCHBOARD_VALUE_OFFSET..SWITCHBOARD_VALUE_OFFSET + 32]);
+
+    Ok((
+        read_u64(SWITCHBOARD_SEED_SLOT_OFFSET),
+        read_u64(SWITCHBOARD_REVEAL_SLOT_OFFSET),
+        value,
+    ))
+}
+
+// Token Metadata instruction discriminators
+const METADATA_CREATE_V3_DISCRIMINATOR: u8 = 33;
+const METADATA_UPDATE_V2_DISCRIMINATOR: u8 = 15;
+
+/// Borsh-encode a Token Metadata create (V3) or update (V2) instruction that
+/// sets name/symbol/URI with no creators, collection, or uses.
+fn metadata_instruction_data(
+    discriminator: u8,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+) -> Result<Vec<u8>> {
+    let mut data = vec![discriminator];
+    if discriminator == METADATA_UPDATE_V2_DISCRIMINATOR {
+        data.push(1); // data: Some
+    }
+    (name, symbol, uri, 0u16).serialize(&mut data)?; // DataV2 fields, zero seller fee
+    data.extend_from_slice(&[0, 0, 0]); // creators, collection, uses: None
+    if discriminator == METADATA_CREATE_V3_DISCRIMINATOR {
+        data.push(1); // is_mutable: true
+        data.push(0); // collection_details: None
+    } else {
+        data.extend_from_slice(&[0, 0, 0]); // update_authority, primary_sale_happened, is_mutable: None
+    }
+    Ok(data)
+}
+
+// ============================================
+// ACCOUNTS
+// ============================================
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + HouseboxState::INIT_SPACE,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    /// vToken mint (LP share token) - Housebox is mint authority (9 decimals, matching SOL)
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 9,
+        mint::authority = housebox_state,
+        mint::token_program = token_program,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeToken2022<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + HouseboxState::INIT_SPACE,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    /// vToken mint (Token-2022, interest-bearing) - created manually since the
+    /// extension must be initialized before the mint
+    /// CHECK: Uninitialized PDA; created and initialized in the handler
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump,
+        constraint = housebox_state.authority == authority.key() @ HouseboxError::Unauthorized
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    /// vToken mint (created in step 1)
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SOL vault PDA - system account that holds all SOL (LP + escrow)
+    /// CHECK: This is a PDA that just holds lamports, not a token account
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Protocol's vToken account PDA (receives LP haircut)
+    #[account(
+        init,
+        payer = authority,
+        token::mint = vtoken_mint,
+        token::authority = housebox_state,
+        token::token_program = token_program,
+        seeds = [b"protocol_vtoken"],
+        bump
+    )]
+    pub protocol_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct LpLock<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// LP's vToken account
+    #[account(
+        mut,
+        constraint = lp_vtoken_account.owner == lp.key(),
+        constraint = lp_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub lp_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Protocol's vToken account
+    #[account(
+        mut,
+        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Protocol vesting vault (required when protocol vesting is enabled)
+    #[account(
+        mut,
+        seeds = [b"protocol_vesting"],
+        bump
+    )]
+    pub protocol_vesting_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP's position (required when an early-exit penalty is configured)
+    #[account(
+        mut,
+        seeds = [b"lp_position", lp.key().as_ref()],
+        bump = lp_position.bump
+    )]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
+    /// LP's whitelist entry (required when lp_whitelist_enabled)
+    #[account(
+        seeds = [b"lp_whitelist", lp.key().as_ref()],
+        bump = lp_whitelist.bump
+    )]
+    pub lp_whitelist: Option<Account<'info, LpWhitelist>>,
+
+    /// LP's pre-funded deposit PDA (optional SOL source instead of the LP wallet)
+    #[account(
+        mut,
+        seeds = [b"lp_deposit", lp.key().as_ref()],
+        bump
+    )]
+    pub lp_deposit: Option<SystemAccount<'info>>,
+
+    /// Referrer's stats PDA (required when a referrer is given)
+    #[account(
+        mut,
+        seeds = [b"lp_referrer", lp_referrer.referrer.as_ref()],
+        bump = lp_referrer.bump
+    )]
+    pub lp_referrer: Option<Account<'info, LpReferrer>>,
+
+    /// Referrer's vToken account (required when a referrer is given)
+    #[account(
+        mut,
+        constraint = referrer_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub referrer_vtoken_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct LpLockLst<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        seeds = [b"lst_config", lst_mint.key().as_ref()],
+        bump = lst_config.bump
+    )]
+    pub lst_config: Account<'info, LstConfig>,
+
+    pub lst_mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL stake pool backing the LST (rate oracle)
+    /// CHECK: Address pinned by lst_config; layout validated in stake_pool_lamports_for
+    #[account(address = lst_config.stake_pool @ HouseboxError::InvalidStakePool)]
+    pub stake_pool: AccountInfo<'info>,
+
+    /// LP's LST account (source)
+    #[account(
+        mut,
+        constraint = lp_lst_account.owner == lp.key(),
+        constraint = lp_lst_account.mint == lst_mint.key()
+    )]
+    pub lp_lst_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Program-owned LST vault
+    #[account(
+        mut,
+        address = lst_config.lst_vault
+    )]
+    pub lst_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// LP's vToken account
+    #[account(
+        mut,
+        constraint = lp_vtoken_account.owner == lp.key(),
+        constraint = lp_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub lp_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Protocol's vToken account
+    #[account(
+        mut,
+        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Protocol vesting vault (required when protocol vesting is enabled)
+    #[account(
+        mut,
+        seeds = [b"protocol_vesting"],
+        bump
+    )]
+    pub protocol_vesting_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP's position (required when an early-exit penalty is configured)
+    #[account(
+        mut,
+        seeds = [b"lp_position", lp.key().as_ref()],
+        bump = lp_position.bump
+    )]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
+    /// LP's whitelist entry (required when lp_whitelist_enabled)
+    #[account(
+        seeds = [b"lp_whitelist", lp.key().as_ref()],
+        bump = lp_whitelist.bump
+    )]
+    pub lp_whitelist: Option<Account<'info, LpWhitelist>>,
+
+    /// Token program for the vToken mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program for the LST mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRedemption<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Redemption request PDA (one per LP)
+    #[account(
+        init,
+        payer = lp,
+        space = 8 + RedemptionRequest::INIT_SPACE,
+        seeds = [b"redemption", lp.key().as_ref()],
+        bump
+    )]
+    pub redemption_request: Account<'info, RedemptionRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRedemption<'info> {
+    /// LP must sign (needed for vToken burn authority)
+    #[account(
+        mut,
+        constraint = lp.key() == redemption_request.lp @ HouseboxError::Unauthorized
+    )]
+    pub lp: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// LP's vToken account (to burn from)
+    #[account(
+        mut,
+        constraint = lp_vtoken_account.owner == lp.key(),
+        constraint = lp_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub lp_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Redemption request PDA (will be closed, rent returned to LP)
+    #[account(
+        mut,
+        close = lp,
+        seeds = [b"redemption", redemption_request.lp.as_ref()],
+        bump = redemption_request.bump
+    )]
+    pub redemption_request: Account<'info, RedemptionRequest>,
+
+    /// LP's position (required when an early-exit penalty is configured)
+    #[account(
+        mut,
+        seeds = [b"lp_position", lp.key().as_ref()],
+        bump = lp_position.bump
+    )]
+    pub lp_position: Option<Account<'info, LpPosition>>,
+
+    /// LP's redemption stream (pays the redemption out linearly when streaming is configured)
+    #[account(
+        mut,
+        seeds = [b"redemption_stream", lp.key().as_ref()],
+        bump = redemption_stream.bump
+    )]
+    pub redemption_stream: Option<Account<'info, RedemptionStream>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRedemptionsBatch<'info> {
+    /// Keeper (permissionless)
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct OpenLpPosition<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        init,
+        payer = lp,
+        space = 8 + LpPosition::INIT_SPACE,
+        seeds = [b"lp_position", lp.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncLpPosition<'info> {
+    pub lp: Signer<'info>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        constraint = lp_vtoken_account.owner == lp.key(),
+        constraint = lp_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub lp_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_position", lp.key().as_ref()],
+        bump = lp_position.bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+}
+
+#[derive(Accounts)]
+pub struct OpenRedemptionStream<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        init,
+        payer = lp,
+        space = 8 + RedemptionStream::INIT_SPACE,
+        seeds = [b"redemption_stream", lp.key().as_ref()],
+        bump
+    )]
+    pub redemption_stream: Account<'info, RedemptionStream>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRedemptionStream<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"redemption_stream", lp.key().as_ref()],
+        bump = redemption_stream.bump
+    )]
+    pub redemption_stream: Account<'info, RedemptionStream>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLpDeposit<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    /// LP's pre-funded deposit PDA
+    #[account(
+        mut,
+        seeds = [b"lp_deposit", lp.key().as_ref()],
+        bump
+    )]
+    pub lp_deposit: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterLpReferrer<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + LpReferrer::INIT_SPACE,
+        seeds = [b"lp_referrer", referrer.key().as_ref()],
+        bump
+    )]
+    pub lp_referrer: Account<'info, LpReferrer>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendRedemption<'info> {
+    #[account(
+        mut,
+        constraint = lp.key() == redemption_request.lp @ HouseboxError::Unauthorized
+    )]
+    pub lp: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA (receives the extension fee)
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"redemption", redemption_request.lp.as_ref()],
+        bump = redemption_request.bump
+    )]
+    pub redemption_request: Account<'info, RedemptionRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStaking<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Staking vault PDA (holds staked vTokens)
+    #[account(
+        init,
+        payer = authority,
+        token::mint = vtoken_mint,
+        token::authority = housebox_state,
+        token::token_program = token_program,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct StakeVtokens<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's vToken account (source)
+    #[account(
+        mut,
+        constraint = owner_vtoken_account.owner == owner.key(),
+        constraint = owner_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub owner_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner's stake position PDA (created on first stake)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + StakePosition::INIT_SPACE,
+        seeds = [b"stake", owner.key().as_ref()],
+        bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeVtokens<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's vToken account (destination)
+    #[account(
+        mut,
+        constraint = owner_vtoken_account.owner == owner.key(),
+        constraint = owner_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub owner_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", owner.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakeRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", owner.key().as_ref()],
+        bump = stake_position.bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundStakeRewards<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerEscrowAction<'info> {
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowYieldAction<'info> {
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct DisputeSettlement<'info> {
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct ResolveSettlementDispute<'info> {
+    /// Must match housebox_state.dispute_authority
+    pub dispute_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    /// Disputing player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", settled_session.player.as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct SettlementClawback<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    /// Settled player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", settled_session.player.as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct CreditPlayerEscrow<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// CHECK: Player being credited; only used as a seed
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct FreezePlayerEscrow<'info> {
+    /// Authority or server signer
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    /// CHECK: Player whose escrow is frozen; only used as a seed
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct PayPlayerReferral<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    /// CHECK: Player whose rake is being shared; only used as a seed
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// CHECK: Must match player_escrow.referrer
+    #[account(mut)]
+    pub referrer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundInsurance<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerDepositRelayed<'info> {
+    /// Operator relayer: transaction fee payer and escrow rent payer
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// Player whose SOL is deposited
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Player's escrow PDA (created on first deposit, rent paid by the relayer)
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + PlayerEscrow::INIT_SPACE,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Player's attestation (Civic gateway token), required for deposits
+    /// above attestation_threshold_lamports
+    /// CHECK: Validated by verify_deposit_attestation
+    pub attestation: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerDepositFor<'info> {
+    /// Pays the lamports and escrow rent (a wallet or a bridge program PDA)
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    /// CHECK: Player whose escrow is credited; only used as a seed
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = 8 + PlayerEscrow::INIT_SPACE,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Player's attestation (Civic gateway token), required for deposits
+    /// above attestation_threshold_lamports
+    /// CHECK: Validated by verify_deposit_attestation
+    pub attestation: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerDeposit<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Player's escrow PDA (created on first deposit)
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerEscrow::INIT_SPACE,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Player's attestation (Civic gateway token), required for deposits
+    /// above attestation_threshold_lamports
+    /// CHECK: Validated by verify_deposit_attestation
+    pub attestation: Option<UncheckedAccount<'info>>,
+
+    /// vToken mint (deposit-match promotion only)
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Protocol's vToken account, redeemed to fund deposit matches
+    #[account(
+        mut,
+        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(pnl: i64, session_id: [u8; 32])]
+pub struct PlayerSettle<'info> {
+    /// Server signer (must be an authorized server key)
+    #[account(mut)]
+    pub server_signer: Signer<'info>,
+
+    /// Player being settled (not signer)
+    /// CHECK: We just need the pubkey for escrow lookup; receives game session rent
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Settled session PDA (for replay protection)
+    #[account(
+        init,
+        payer = server_signer,
+        space = 8 + SettledSession::INIT_SPACE,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    /// Open game session (required if the session was opened with open_session)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"session", session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Option<Account<'info, GameSession>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBet<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Player's open bet (one at a time)
+    #[account(
+        init,
+        payer = player,
+        space = 8 + CoinFlipBet::INIT_SPACE,
+        seeds = [b"coin_flip", player.key().as_ref()],
+        bump
+    )]
+    pub coin_flip_bet: Account<'info, CoinFlipBet>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveBet<'info> {
+    /// Player who placed the bet — receives rent refund
+    /// CHECK: Verified by coin_flip_bet.player; only receives rent
+    #[account(
+        mut,
+        constraint = player.key() == coin_flip_bet.player @ HouseboxError::Unauthorized
+    )]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Bet being resolved (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"coin_flip", player.key().as_ref()],
+        bump = coin_flip_bet.bump
+    )]
+    pub coin_flip_bet: Account<'info, CoinFlipBet>,
+
+    /// CHECK: SlotHashes sysvar
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct OpenSession<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Game session PDA (holds the escrow reservation)
+    #[account(
+        init,
+        payer = player,
+        space = 8 + GameSession::INIT_SPACE,
+        seeds = [b"session", session_id.as_ref()],
+        bump
+    )]
+    pub game_session: Account<'info, GameSession>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct SettlePartial<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    /// Player being settled (not signer)
+    /// CHECK: Verified by game_session.player
+    #[account(
+        constraint = player.key() == game_session.player @ HouseboxError::Unauthorized
+    )]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Open game session (accumulates the partial PnL)
+    #[account(
+        mut,
+        seeds = [b"session", session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Account<'info, GameSession>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct FinalizeSession<'info> {
+    /// Server signer (must be an authorized server key); pays settled-session rent
+    #[account(mut)]
+    pub server_signer: Signer<'info>,
+
+    /// Player who opened the session — receives rent refund
+    /// CHECK: Verified by game_session.player; only receives rent
+    #[account(
+        mut,
+        constraint = player.key() == game_session.player @ HouseboxError::Unauthorized
+    )]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Game session PDA (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"session", session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Account<'info, GameSession>,
+
+    /// Settled session PDA (records the cumulative PnL; blocks later settlement)
+    #[account(
+        init,
+        payer = server_signer,
+        space = 8 + SettledSession::INIT_SPACE,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct ExpireSession<'info> {
+    /// Anyone can call (pays settled-session rent)
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// Player who opened the session — receives rent refund
+    /// CHECK: Verified by game_session.player; only receives rent
+    #[account(
+        mut,
+        constraint = player.key() == game_session.player @ HouseboxError::Unauthorized
+    )]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Game session PDA (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"session", session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Account<'info, GameSession>,
+
+    /// Settled session PDA (blocks a later settlement of the expired session)
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + SettledSession::INIT_SPACE,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct CloseSession<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    /// Player who opened the session — receives rent refund
+    /// CHECK: Verified by game_session.player; only receives rent
+    #[account(
+        mut,
+        constraint = player.key() == game_session.player @ HouseboxError::Unauthorized
+    )]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Game session PDA (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"session", session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Account<'info, GameSession>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct SessionSeed<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Open game session the seed belongs to
+    #[account(
+        mut,
+        seeds = [b"session", session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Account<'info, GameSession>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct RequestRandomness<'info> {
+    /// Server signer (must be an authorized server key) — pays rent
+    #[account(mut)]
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Open game session the randomness is for
+    #[account(
+        seeds = [b"session", session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Account<'info, GameSession>,
+
+    /// Switchboard randomness account
+    /// CHECK: Owner, type, and slots are validated by read_switchboard_randomness
+    pub randomness_account: AccountInfo<'info>,
+
+    /// Per-session randomness record
+    #[account(
+        init,
+        payer = server_signer,
+        space = 8 + SessionRandomness::INIT_SPACE,
+        seeds = [b"session_randomness", session_id.as_ref()],
+        bump
+    )]
+    pub session_randomness: Account<'info, SessionRandomness>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct ConsumeRandomness<'info> {
+    /// Switchboard randomness account bound by request_randomness
+    /// CHECK: Must match session_randomness; contents validated by read_switchboard_randomness
+    #[account(
+        constraint = randomness_account.key() == session_randomness.randomness_account
+            @ HouseboxError::InvalidRandomnessAccount
+    )]
+    pub randomness_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"session_randomness", session_id.as_ref()],
+        bump = session_randomness.bump
+    )]
+    pub session_randomness: Account<'info, SessionRandomness>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: [u8; 32])]
+pub struct SettleRound<'info> {
+    /// Server signer (must be an authorized server key)
+    #[account(mut)]
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Settled PDA for the round id (replay protection)
+    #[account(
+        init,
+        payer = server_signer,
+        space = 8 + SettledSession::INIT_SPACE,
+        seeds = [b"settled", round_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32])]
+pub struct PostSettlementRoot<'info> {
+    /// Server signer (must be an authorized server key)
+    #[account(mut)]
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        init,
+        payer = server_signer,
+        space = 8 + SettlementRoot::INIT_SPACE,
+        seeds = [b"settlement_root", root.as_ref()],
+        bump
+    )]
+    pub settlement_root: Account<'info, SettlementRoot>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(pnl: i64, session_id: [u8; 32])]
+pub struct ClaimSettlement<'info> {
+    /// Player or keeper submitting the proof (pays settled-session rent)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Player being settled (not signer)
+    /// CHECK: We just need the pubkey for escrow lookup and the leaf; receives game session rent
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        seeds = [b"settlement_root", settlement_root.root.as_ref()],
+        bump = settlement_root.bump
+    )]
+    pub settlement_root: Account<'info, SettlementRoot>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Settled session PDA (for replay protection, shared with player_settle)
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SettledSession::INIT_SPACE,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    /// Game session PDA, settled and closed if opened with open_session
+    /// CHECK: May not exist; loaded as a GameSession when owned by this program
+    #[account(
+        mut,
+        seeds = [b"session", session_id.as_ref()],
+        bump
+    )]
+    pub game_session: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerSettleNonce<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    /// Player being settled (not signer)
+    /// CHECK: We just need the pubkey for escrow lookup; receives game session rent
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Open game session being settled, if any (closed as in player_settle)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"session", game_session.session_id.as_ref()],
+        bump = game_session.bump
+    )]
+    pub game_session: Option<Account<'info, GameSession>>,
+}
+
+#[derive(Accounts)]
+#[instruction(pnl: i64, session_id: [u8; 32])]
+pub struct PlayerSettleVoucher<'info> {
+    /// Anyone can submit (pays settled-session rent)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Player being settled (not signer)
+    /// CHECK: We just need the pubkey for escrow lookup and the voucher message;
+    /// receives game session rent
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Settled session PDA (for replay protection, shared with player_settle)
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SettledSession::INIT_SPACE,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    /// Game session PDA, settled and closed if opened with open_session
+    /// CHECK: May not exist; loaded as a GameSession when owned by this program
+    #[account(
+        mut,
+        seeds = [b"session", session_id.as_ref()],
+        bump
+    )]
+    pub game_session: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar (Ed25519 voucher introspection)
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerWithdrawVoucher<'info> {
+    /// Anyone can submit
+    pub payer: Signer<'info>,
+
+    /// Player whose escrow is being withdrawn from (not a signer)
+    /// CHECK: We just need the pubkey for escrow lookup and destination validation
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// CHECK: Instructions sysvar (Ed25519 voucher introspection)
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    /// Verified withdrawal address, when it differs from the player wallet
+    /// CHECK: Must match player_escrow.verified_withdrawal_address
+    #[account(mut)]
+    pub withdrawal_destination: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnassistedWithdrawal<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Player's escrow
+    #[account(
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Unassisted withdrawal request PDA (one per player)
+    #[account(
+        init,
+        payer = player,
+        space = 8 + UnassistedWithdrawal::INIT_SPACE,
+        seeds = [b"unassisted_withdrawal", player.key().as_ref()],
+        bump
+    )]
+    pub unassisted_withdrawal: Account<'info, UnassistedWithdrawal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ObjectUnassistedWithdrawal<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    /// Player who made the request — receives rent refund
+    /// CHECK: Verified by unassisted_withdrawal.player; only receives rent
+    #[account(
+        mut,
+        constraint = player.key() == unassisted_withdrawal.player @ HouseboxError::Unauthorized
+    )]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Unassisted withdrawal request PDA (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"unassisted_withdrawal", unassisted_withdrawal.player.as_ref()],
+        bump = unassisted_withdrawal.bump
+    )]
+    pub unassisted_withdrawal: Account<'info, UnassistedWithdrawal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteUnassistedWithdrawal<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Unassisted withdrawal request PDA (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"unassisted_withdrawal", player.key().as_ref()],
+        bump = unassisted_withdrawal.bump
+    )]
+    pub unassisted_withdrawal: Account<'info, UnassistedWithdrawal>,
+
+    /// Verified withdrawal address, when it differs from the player wallet
+    /// CHECK: Must match player_escrow.verified_withdrawal_address
+    #[account(mut)]
+    pub withdrawal_destination: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdrawalAddressChange<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Player's escrow
+    #[account(
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Address change request PDA (one per player)
+    #[account(
+        init,
+        payer = player,
+        space = 8 + WithdrawalAddressChange::INIT_SPACE,
+        seeds = [b"address_change", player.key().as_ref()],
+        bump
+    )]
+    pub address_change: Account<'info, WithdrawalAddressChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestEscrowRecovery<'info> {
+    /// Escrow's registered recovery key — pays rent
+    #[account(mut)]
+    pub recovery_signer: Signer<'info>,
+
+    /// CHECK: Player whose escrow is recovered; only used as a seed
+    pub player: UncheckedAccount<'info>,
+
+    /// Player's escrow
+    #[account(
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.recovery_key != Pubkey::default()
+            && player_escrow.recovery_key == recovery_signer.key() @ HouseboxError::InvalidRecoveryKey
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Recovery request PDA (one per player)
+    #[account(
+        init,
+        payer = recovery_signer,
+        space = 8 + EscrowRecovery::INIT_SPACE,
+        seeds = [b"recovery", player.key().as_ref()],
+        bump
+    )]
+    pub escrow_recovery: Account<'info, EscrowRecovery>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmEscrowRecovery<'info> {
+    /// Escrow's registered recovery key — receives the request rent
+    #[account(mut)]
+    pub recovery_signer: Signer<'info>,
+
+    /// CHECK: Player whose escrow is recovered; only used as a seed
+    pub player: UncheckedAccount<'info>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.recovery_key != Pubkey::default()
+            && player_escrow.recovery_key == recovery_signer.key() @ HouseboxError::InvalidRecoveryKey
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    #[account(
+        mut,
+        close = recovery_signer,
+        seeds = [b"recovery", player.key().as_ref()],
+        bump = escrow_recovery.bump
+    )]
+    pub escrow_recovery: Account<'info, EscrowRecovery>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEscrowRecovery<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"recovery", player.key().as_ref()],
+        bump = escrow_recovery.bump
+    )]
+    pub escrow_recovery: Account<'info, EscrowRecovery>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmWithdrawalAddressChange<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Address change request PDA (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"address_change", player.key().as_ref()],
+        bump = address_change.bump
+    )]
+    pub address_change: Account<'info, WithdrawalAddressChange>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePlayerEscrow<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player's escrow (will be closed, rent returned to player)
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct TransferEscrowOwnership<'info> {
+    /// Current escrow owner (receives the old escrow's rent)
+    #[account(mut)]
+    pub old_player: Signer<'info>,
+
+    /// New wallet (pays the new escrow's rent)
+    #[account(mut)]
+    pub new_player: Signer<'info>,
+
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Old escrow (will be closed, rent returned to the old wallet)
+    #[account(
+        mut,
+        close = old_player,
+        seeds = [b"escrow", old_player.key().as_ref()],
+        bump = old_escrow.bump,
+        constraint = old_escrow.player == old_player.key()
+    )]
+    pub old_escrow: Account<'info, PlayerEscrow>,
+
+    /// New escrow (must not already exist)
+    #[account(
+        init,
+        payer = new_player,
+        space = 8 + PlayerEscrow::INIT_SPACE,
+        seeds = [b"escrow", new_player.key().as_ref()],
+        bump
+    )]
+    pub new_escrow: Account<'info, PlayerEscrow>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerWithdraw<'info> {
+    /// Server signer (must be an authorized server key)
+    /// Required to authorize withdrawals - players cannot withdraw directly
+    #[account(mut)]
+    pub server_signer: Signer<'info>,
+
+    /// Player whose escrow is being withdrawn from (not a signer)
+    /// CHECK: We just need the pubkey for escrow lookup and destination validation
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Verified withdrawal address, when it differs from the player wallet
+    /// CHECK: Must match player_escrow.verified_withdrawal_address
+    #[account(mut)]
+    pub withdrawal_destination: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepEscrow<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    /// Player whose escrow is being swept (not a signer)
+    /// CHECK: We just need the pubkey for escrow lookup
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Player's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Operational account the player authorized
+    /// CHECK: Must match player_escrow.sweep_destination
+    #[account(
+        mut,
+        address = player_escrow.sweep_destination
+    )]
+    pub sweep_destination: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerWithdrawTo<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    /// Player must co-sign withdrawals to an unverified destination
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// CHECK: Any destination approved by both player and server
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Player's escrow
+    #[account(
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Player's pending withdrawal (one at a time)
+    #[account(
+        init,
+        payer = player,
+        space = 8 + WithdrawalRequest::INIT_SPACE,
+        seeds = [b"withdrawal_request", player.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawalRequest<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"withdrawal_request", player.key().as_ref()],
+        bump = withdrawal_request.bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWithdrawalsBatch<'info> {
+    /// Server signer (must be an authorized server key)
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminAction<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Account<'info, AdminLog>>,
+
+    /// Signer's role, for instructions delegated to a role holder
+    pub role: Option<Account<'info, Role>>,
+}
+
+impl<'info> AdminAction<'info> {
+    /// Require the signer to be the authority or to hold `role`.
+    pub fn require_role(&self, role: RoleKind) -> Result<()> {
+        require!(
+            has_role(
+                &self.housebox_state,
+                &self.authority.key(),
+                self.role.as_deref(),
+                role,
+            ),
+            HouseboxError::Unauthorized
+        );
+
+        Ok(())
+    }
+
+    /// Record this admin instruction in the action log when it is enabled.
+    pub fn log_admin_action(&mut self, instruction: &str) -> Result<()> {
+        record_admin_action(
+            &self.housebox_state,
+            self.admin_log.as_mut(),
+            self.authority.key(),
+            instruction,
+        )
+    }
+}
+
+/// Give a privileged instruction's accounts the same log_admin_action as
+/// AdminAction, with `$signer` recorded as the actor.
+macro_rules! impl_log_admin_action {
+    ($($accounts:ident => $signer:ident),* $(,)?) => {
+        $(
+            impl<'info> $accounts<'info> {
+                /// Record this admin instruction in the action log when it is enabled.
+                pub fn log_admin_action(&mut self, instruction: &str) -> Result<()> {
+                    record_admin_action(
+                        &self.housebox_state,
+                        self.admin_log.as_deref_mut(),
+                        self.$signer.key(),
+                        instruction,
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_log_admin_action! {
+    InitializeStaking => authority,
+    ResolveSettlementDispute => dispute_authority,
+    SettlementClawback => authority,
+    FreezePlayerEscrow => signer,
+    ExecuteEvacuation => authority,
+    GrantRole => authority,
+    RevokeRole => authority,
+    InitializeAdminCouncil => authority,
+    ExecuteCouncilProposal => executor,
+    ScheduleParameterChange => authority,
+    ExecuteParameterChange => executor,
+    CancelParameterChange => authority,
+    InitializeConfig => authority,
+    UpdateConfig => authority,
+    WhitelistLst => authority,
+    UpdateLstConfig => authority,
+    UnwindLst => authority,
+    SetVtokenMetadata => authority,
+    AddLpWhitelist => authority,
+    RemoveLpWhitelist => authority,
+    InitializeProtocolVesting => authority,
+    InitializeRateTwap => authority,
+    WithdrawTreasuryVault => authority,
+    WithdrawTreasury => authority,
+    WithdrawProtocolVtokens => authority,
+    RescueLamports => authority,
+    RescueTokens => authority,
+    RotateProtocolVtokenAccount => authority,
+    MigratePool => authority,
+    InitializeAssetPool => authority,
+    WithdrawAssetProtocolVtokens => authority,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdminLog<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AdminLog::INIT_SPACE,
+        seeds = [b"admin_log"],
+        bump
+    )]
+    pub admin_log: Account<'info, AdminLog>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: RoleKind, holder: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Role::INIT_SPACE,
+        seeds = [b"role", holder.as_ref(), &[role as u8]],
+        bump
+    )]
+    pub role_account: Account<'info, Role>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: RoleKind, holder: Pubkey)]
+pub struct RevokeRole<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"role", holder.as_ref(), &[role as u8]],
+        bump = role_account.bump
+    )]
+    pub role_account: Account<'info, Role>,
+}
+
+#[derive(Accounts)]
+pub struct WhitelistLst<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    pub lst_mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL stake pool whose exchange rate values the LST
+    /// CHECK: Owner and layout validated in stake_pool_lamports_for
+    pub stake_pool: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LstConfig::INIT_SPACE,
+        seeds = [b"lst_config", lst_mint.key().as_ref()],
+        bump
+    )]
+    pub lst_config: Account<'info, LstConfig>,
+
+    /// Program-owned LST vault PDA
+    #[account(
+        init,
+        payer = authority,
+        token::mint = lst_mint,
+        token::authority = housebox_state,
+        token::token_program = token_program,
+        seeds = [b"lst_vault", lst_mint.key().as_ref()],
+        bump
+    )]
+    pub lst_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(lp: Pubkey)]
+pub struct AddLpWhitelist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LpWhitelist::INIT_SPACE,
+        seeds = [b"lp_whitelist", lp.as_ref()],
+        bump
+    )]
+    pub lp_whitelist: Account<'info, LpWhitelist>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLpWhitelist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"lp_whitelist", lp_whitelist.lp.as_ref()],
+        bump = lp_whitelist.bump
+    )]
+    pub lp_whitelist: Account<'info, LpWhitelist>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLstConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        seeds = [b"lst_config", lst_config.lst_mint.as_ref()],
+        bump = lst_config.bump
+    )]
+    pub lst_config: Account<'info, LstConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MarkLstValue<'info> {
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        seeds = [b"lst_config", lst_config.lst_mint.as_ref()],
+        bump = lst_config.bump
+    )]
+    pub lst_config: Account<'info, LstConfig>,
+
+    /// SPL stake pool backing the LST (rate oracle)
+    /// CHECK: Address pinned by lst_config; layout validated in stake_pool_lamports_for
+    #[account(address = lst_config.stake_pool @ HouseboxError::InvalidStakePool)]
+    pub stake_pool: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnwindLst<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    /// Signer's treasurer role (when not the authority)
+    pub role: Option<Account<'info, Role>>,
+
+    #[account(
+        mut,
+        seeds = [b"lst_config", lst_config.lst_mint.as_ref()],
+        bump = lst_config.bump
+    )]
+    pub lst_config: Box<Account<'info, LstConfig>>,
+
+    /// LST mint (pool tokens are burned by the withdrawal)
+    #[account(
+        mut,
+        address = lst_config.lst_mint
+    )]
+    pub lst_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL stake pool backing the LST
+    /// CHECK: Address pinned by lst_config; layout validated in stake_pool_lamports_for
+    #[account(
+        mut,
+        address = lst_config.stake_pool @ HouseboxError::InvalidStakePool
+    )]
+    pub stake_pool: AccountInfo<'info>,
+
+    /// CHECK: Validated by the stake pool program
+    pub stake_pool_withdraw_authority: AccountInfo<'info>,
+
+    /// CHECK: Validated by the stake pool program
+    #[account(mut)]
+    pub reserve_stake: AccountInfo<'info>,
+
+    /// CHECK: Validated by the stake pool program
+    #[account(mut)]
+    pub manager_fee_account: AccountInfo<'info>,
+
+    /// Program-owned LST vault (pool tokens burned from here)
+    #[account(
+        mut,
+        address = lst_config.lst_vault
+    )]
+    pub lst_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (receives the unstaked lamports)
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    /// CHECK: Stake history sysvar
+    #[account(address = stake_history::ID)]
+    pub stake_history: AccountInfo<'info>,
+
+    /// CHECK: Pinned to the native stake program id
+    #[account(address = stake::program::ID)]
+    pub stake_program: AccountInfo<'info>,
+
+    /// CHECK: Pinned to the SPL stake pool program id
+    #[account(address = spl_stake_pool_program::ID)]
+    pub stake_pool_program: AccountInfo<'info>,
+
+    pub lst_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetVtokenMetadata<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Metadata PDA for the vToken mint
+    /// CHECK: Address derived from the Token Metadata program; initialized by its CPI
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), vtoken_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    /// CHECK: Pinned to the Token Metadata program id
+    #[account(address = token_metadata_program::ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRateTwap<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RateTwap::INIT_SPACE,
+        seeds = [b"rate_twap"],
+        bump
+    )]
+    pub rate_twap: Account<'info, RateTwap>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRateTwap<'info> {
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        seeds = [b"rate_twap"],
+        bump = rate_twap.bump
+    )]
+    pub rate_twap: Account<'info, RateTwap>,
+}
+
+#[derive(Accounts)]
+pub struct SyncVtokenRate<'info> {
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Interest-bearing Token-2022 vToken mint
+    /// CHECK: Pinned to housebox_state.vtoken_mint and owned by Token-2022
+    #[account(
+        mut,
+        address = housebox_state.vtoken_mint,
+        owner = token_2022::ID @ HouseboxError::NotInterestBearing
+    )]
+    pub vtoken_mint: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct CloseSettledSession<'info> {
+    #[account(
+        mut,
+        constraint = housebox_state.is_server_key(&server_signer.key()) @ HouseboxError::Unauthorized
+    )]
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        close = server_signer,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSettledSessionsBatch<'info> {
+    #[account(
+        mut,
+        constraint = housebox_state.is_server_key(&server_signer.key()) @ HouseboxError::Unauthorized
+    )]
+    pub server_signer: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolVesting<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Protocol vesting vault PDA (holds unvested protocol vTokens)
+    #[account(
+        init,
+        payer = authority,
+        token::mint = vtoken_mint,
+        token::authority = housebox_state,
+        token::token_program = token_program,
+        seeds = [b"protocol_vesting"],
+        bump
+    )]
+    pub protocol_vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVestedProtocolVtokens<'info> {
+    /// Anyone can call (destination is fixed)
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_vesting"],
+        bump
+    )]
+    pub protocol_vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Protocol's vToken account (destination)
+    #[account(
+        mut,
+        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// CHECK: Any destination chosen by the authority
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdminCouncil<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AdminCouncil::INIT_SPACE,
+        seeds = [b"admin_council"],
+        bump
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeCouncilAction<'info> {
+    /// Council member (pays the proposal rent)
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"admin_council"],
+        bump = admin_council.bump
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+
+    #[account(
+        init,
+        payer = member,
+        space = 8 + CouncilProposal::INIT_SPACE,
+        seeds = [b"council_proposal", admin_council.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub council_proposal: Account<'info, CouncilProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ApproveCouncilProposal<'info> {
+    /// Council member
+    pub member: Signer<'info>,
+
+    #[account(
+        seeds = [b"admin_council"],
+        bump = admin_council.bump
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+
+    #[account(
+        mut,
+        seeds = [b"council_proposal", proposal_id.to_le_bytes().as_ref()],
+        bump = council_proposal.bump
+    )]
+    pub council_proposal: Account<'info, CouncilProposal>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteCouncilProposal<'info> {
+    /// Anyone can execute an approved proposal
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"admin_council"],
+        bump = admin_council.bump
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+
+    #[account(
+        mut,
+        seeds = [b"council_proposal", proposal_id.to_le_bytes().as_ref()],
+        bump = council_proposal.bump
+    )]
+    pub council_proposal: Account<'info, CouncilProposal>,
+
+    /// Treasury vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump
+    )]
+    pub treasury_vault: SystemAccount<'info>,
+
+    /// Treasury withdrawal destination named in the proposal
+    /// CHECK: Must match the proposal's destination
+    #[account(mut)]
+    pub destination: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ScheduleParameterChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + QueuedParameterChange::INIT_SPACE,
+        seeds = [b"parameter_change", housebox_state.parameter_change_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub queued_change: Account<'info, QueuedParameterChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(change_id: u64)]
+pub struct ExecuteParameterChange<'info> {
+    /// Anyone can execute a change whose timelock has passed
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"parameter_change", change_id.to_le_bytes().as_ref()],
+        bump = queued_change.bump
+    )]
+    pub queued_change: Account<'info, QueuedParameterChange>,
+
+    /// Authority that paid the rent — receives it back
+    /// CHECK: Must be housebox_state.authority
+    #[account(
+        mut,
+        address = housebox_state.authority
+    )]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(change_id: u64)]
+pub struct CancelParameterChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"parameter_change", change_id.to_le_bytes().as_ref()],
+        bump = queued_change.bump
+    )]
+    pub queued_change: Account<'info, QueuedParameterChange>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + HouseboxConfig::INIT_SPACE,
+        seeds = [b"housebox_config"],
+        bump
+    )]
+    pub housebox_config: Account<'info, HouseboxConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_config"],
+        bump = housebox_config.bump
+    )]
+    pub housebox_config: Account<'info, HouseboxConfig>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyUpgradeAuthority<'info> {
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// This program's ProgramData (its upgrade authority must be the authority)
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// State account in a possibly older layout
+    /// CHECK: Owner, authority, and layout are checked in migrate_state
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: UncheckedAccount<'info>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    /// This program's ProgramData (its upgrade authority must be the authority)
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Program account in a possibly older layout
+    /// CHECK: Owner and account type are checked in migrate_account
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepTreasury<'info> {
+    /// Anyone can call (permissionless crank)
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Treasury vault PDA (protocol SOL fees, outside the LP vault)
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump
+    )]
+    pub treasury_vault: SystemAccount<'info>,
+
+    /// Configured fee recipient (required when housebox_state.fee_recipient is set)
+    /// CHECK: Checked against housebox_state.fee_recipient in sweep_treasury
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasuryVault<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    /// Treasury vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump
+    )]
+    pub treasury_vault: SystemAccount<'info>,
+
+    /// CHECK: Any destination chosen by the authority
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// Signer's treasurer role (when not the authority)
+    pub role: Option<Account<'info, Role>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteEvacuation<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Cold recovery address
+    /// CHECK: Must match housebox_state.evacuation_address
+    #[account(
+        mut,
+        address = housebox_state.evacuation_address
+    )]
+    pub evacuation_address: UncheckedAccount<'info>,
+
+    /// This program's ProgramData (its upgrade authority must be the authority)
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RescueLamports<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Any recovery address chosen by the authority
+    #[account(mut)]
+    pub recovery: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct RescueTokens<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Mint of the stray tokens
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// PDA the tokens were sent to
+    /// CHECK: Must be housebox_state or vtoken_mint
+    #[account(
+        constraint = stray_owner.key() == housebox_state.key()
+            || stray_owner.key() == vtoken_mint.key() @ HouseboxError::InvalidRescueSource
+    )]
+    pub stray_owner: UncheckedAccount<'info>,
+
+    /// The PDA's associated token account holding the stray tokens
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = stray_owner,
+        associated_token::token_program = token_program,
+        constraint = stray_token_account.key() != housebox_state.protocol_vtoken_account
+            @ HouseboxError::InvalidRescueSource
+    )]
+    pub stray_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Recovery token account chosen by the authority
+    #[account(
+        mut,
+        token::mint = mint
+    )]
+    pub recovery_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RotateProtocolVtokenAccount<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Current protocol vToken account (source)
+    #[account(
+        mut,
+        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Replacement protocol vToken account
+    #[account(
+        mut,
+        token::mint = vtoken_mint,
+        constraint = new_protocol_vtoken_account.key() != protocol_vtoken_account.key()
+            @ HouseboxError::InvalidProtocolVtokenAccount
+    )]
+    pub new_protocol_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolVtokens<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: InterfaceAccount<'info, Mint>,
+
+    /// Protocol's vToken account (source)
+    #[account(
+        mut,
+        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Destination vToken account
+    #[account(mut)]
+    pub destination_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_version: u8)]
+pub struct MigratePool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    /// New-version state PDA
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + HouseboxState::INIT_SPACE,
+        seeds = [b"housebox_state", [new_version].as_ref()],
+        bump
+    )]
+    pub new_housebox_state: Box<Account<'info, HouseboxState>>,
+
+    /// This program's ProgramData (its upgrade authority must be the authority)
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Box<Account<'info, ProgramData>>,
+
+    /// SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// New-version SOL vault PDA
+    /// CHECK: This is a PDA that just holds lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault", [new_version].as_ref()],
+        bump
+    )]
+    pub new_sol_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vtoken_mint"],
+        bump
+    )]
+    pub vtoken_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Protocol's vToken account
+    #[account(
+        mut,
+        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAssetPool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    /// Admin action log (required once initialize_admin_log has run)
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AssetPool::INIT_SPACE,
+        seeds = [b"asset_pool", asset_mint.key().as_ref()],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    /// Pool asset vault PDA (LP pool + player escrow)
+    #[account(
+        init,
+        payer = authority,
+        token::mint = asset_mint,
+        token::authority = asset_pool,
+        token::token_program = asset_token_program,
+        seeds = [b"asset_vault", asset_mint.key().as_ref()],
+        bump
+    )]
+    pub asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Pool vToken mint - asset pool is mint authority (asset decimals)
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = asset_mint.decimals,
+        mint::authority = asset_pool,
+        mint::token_program = token_program,
+        seeds = [b"asset_vtoken_mint", asset_mint.key().as_ref()],
+        bump
+    )]
+    pub vtoken_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Pool protocol vToken account PDA (receives LP haircut)
+    #[account(
+        init,
+        payer = authority,
+        token::mint = vtoken_mint,
+        token::authority = asset_pool,
+        token::token_program = token_program,
+        seeds = [b"asset_protocol_vtoken", asset_mint.key().as_ref()],
+        bump
+    )]
+    pub protocol_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    /// Token program for the vToken mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program for the asset mint
+    pub asset_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AssetLpLock<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    #[account(
+        mut,
+        seeds = [b"asset_pool", asset_mint.key().as_ref()],
+        bump = asset_pool.bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = asset_pool.asset_vault
+    )]
+    pub asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP's asset account (source)
+    #[account(
+        mut,
+        constraint = lp_asset_account.owner == lp.key(),
+        constraint = lp_asset_account.mint == asset_mint.key()
+    )]
+    pub lp_asset_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = asset_pool.vtoken_mint
+    )]
+    pub vtoken_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// LP's pool vToken account
+    #[account(
+        mut,
+        constraint = lp_vtoken_account.owner == lp.key(),
+        constraint = lp_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub lp_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Pool protocol vToken account
+    #[account(
+        mut,
+        address = asset_pool.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP's whitelist entry (required when lp_whitelist_enabled)
+    #[account(
+        seeds = [b"lp_whitelist", lp.key().as_ref()],
+        bump = lp_whitelist.bump
+    )]
+    pub lp_whitelist: Option<Account<'info, LpWhitelist>>,
+
+    /// Token program for the vToken mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program for the asset mint
+    pub asset_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AssetRequestRedemption<'info> {
+    #[account(mut)]
+    pub lp: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        seeds = [b"asset_pool", asset_pool.asset_mint.as_ref()],
+        bump = asset_pool.bump
+    )]
+    pub asset_pool: Account<'info, AssetPool>,
+
+    /// Asset redemption request PDA (one per LP per pool)
+    #[account(
+        init,
+        payer = lp,
+        space = 8 + RedemptionRequest::INIT_SPACE,
+        seeds = [b"asset_redemption", asset_pool.key().as_ref(), lp.key().as_ref()],
+        bump
+    )]
+    pub redemption_request: Account<'info, RedemptionRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AssetExecuteRedemption<'info> {
+    /// LP must sign (needed for vToken burn authority)
+    #[account(
+        mut,
+        constraint = lp.key() == redemption_request.lp @ HouseboxError::Unauthorized
+    )]
+    pub lp: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"asset_pool", asset_mint.key().as_ref()],
+        bump = asset_pool.bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = asset_pool.asset_vault
+    )]
+    pub asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP's asset account (destination)
+    #[account(
+        mut,
+        constraint = lp_asset_account.owner == lp.key(),
+        constraint = lp_asset_account.mint == asset_mint.key()
+    )]
+    pub lp_asset_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = asset_pool.vtoken_mint
+    )]
+    pub vtoken_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// LP's pool vToken account (to burn from)
+    #[account(
+        mut,
+        constraint = lp_vtoken_account.owner == lp.key(),
+        constraint = lp_vtoken_account.mint == vtoken_mint.key()
+    )]
+    pub lp_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Asset redemption request PDA (will be closed, rent returned to LP)
+    #[account(
+        mut,
+        close = lp,
+        seeds = [b"asset_redemption", asset_pool.key().as_ref(), redemption_request.lp.as_ref()],
+        bump = redemption_request.bump
+    )]
+    pub redemption_request: Box<Account<'info, RedemptionRequest>>,
+
+    /// Token program for the vToken mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program for the asset mint
+    pub asset_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowAssetDeposit<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
+
+    #[account(
+        seeds = [b"asset_pool", asset_mint.key().as_ref()],
+        bump = asset_pool.bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = asset_pool.asset_vault
+    )]
+    pub asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Player's asset account (source)
+    #[account(
+        mut,
+        constraint = player_asset_account.owner == player.key(),
+        constraint = player_asset_account.mint == asset_mint.key()
+    )]
+    pub player_asset_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Player's canonical escrow PDA (created on first deposit)
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerEscrow::INIT_SPACE,
+        seeds = [b"escrow", player.key().as_ref()],
         bump
     )]
-    pub vtoken_mint: Box<Account<'info, Mint>>,
+    pub player_escrow: Box<Account<'info, PlayerEscrow>>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// Token program for the asset mint
+    pub asset_token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeVault<'info> {
+#[instruction(pnl: i64, session_id: [u8; 32])]
+pub struct EscrowAssetSettle<'info> {
+    /// Server signer (must be an authorized server key)
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub server_signer: Signer<'info>,
+
+    /// Player being settled (not signer)
+    /// CHECK: We just need the pubkey for escrow lookup
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    #[account(
+        mut,
+        seeds = [b"asset_pool", asset_pool.asset_mint.as_ref()],
+        bump = asset_pool.bump
+    )]
+    pub asset_pool: Account<'info, AssetPool>,
 
+    /// Player's canonical escrow
     #[account(
         mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
+
+    /// Settled session PDA (for replay protection, shared with player_settle)
+    #[account(
+        init,
+        payer = server_signer,
+        space = 8 + SettledSession::INIT_SPACE,
+        seeds = [b"settled", session_id.as_ref()],
+        bump
+    )]
+    pub settled_session: Account<'info, SettledSession>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowAssetWithdraw<'info> {
+    /// Server signer (must be an authorized server key)
+    #[account(mut)]
+    pub server_signer: Signer<'info>,
+
+    /// Player whose escrow is being withdrawn from (not a signer)
+    /// CHECK: We just need the pubkey for escrow lookup and destination validation
+    pub player: AccountInfo<'info>,
+
+    #[account(
         seeds = [b"housebox_state"],
-        bump,
-        constraint = housebox_state.authority == authority.key() @ HouseboxError::Unauthorized
+        bump
     )]
     pub housebox_state: Box<Account<'info, HouseboxState>>,
 
-    /// vToken mint (created in step 1)
     #[account(
-        seeds = [b"vtoken_mint"],
+        seeds = [b"asset_pool", asset_mint.key().as_ref()],
+        bump = asset_pool.bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = asset_pool.asset_vault
+    )]
+    pub asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Player's asset account (destination)
+    #[account(
+        mut,
+        constraint = player_asset_account.owner == player.key(),
+        constraint = player_asset_account.mint == asset_mint.key()
+    )]
+    pub player_asset_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Player's canonical escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump,
+        constraint = player_escrow.player == player.key()
+    )]
+    pub player_escrow: Box<Account<'info, PlayerEscrow>>,
+
+    /// Token program for the asset mint
+    pub asset_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAssetProtocolVtokens<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"housebox_state"],
         bump
     )]
-    pub vtoken_mint: Box<Account<'info, Mint>>,
+    pub housebox_state: Box<Account<'info, HouseboxState>>,
 
-    /// SOL vault PDA - system account that holds all SOL (LP + escrow)
-    /// CHECK: This is a PDA that just holds lamports, not a token account
+    /// Admin action log (required once initialize_admin_log has run)
     #[account(
         mut,
-        seeds = [b"sol_vault"],
+        seeds = [b"admin_log"],
+        bump = admin_log.bump
+    )]
+    pub admin_log: Option<Box<Account<'info, AdminLog>>>,
+
+    #[account(
+        seeds = [b"asset_pool", asset_pool.asset_mint.as_ref()],
+        bump = asset_pool.bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(address = asset_pool.vtoken_mint)]
+    pub vtoken_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Pool protocol vToken account (source)
+    #[account(
+        mut,
+        address = asset_pool.protocol_vtoken_account
+    )]
+    pub protocol_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Destination vToken account
+    #[account(mut)]
+    pub destination_vtoken_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct GetLpShare<'info> {
+    #[account(
+        seeds = [b"housebox_state"],
         bump
     )]
-    pub sol_vault: SystemAccount<'info>,
+    pub housebox_state: Account<'info, HouseboxState>,
 
-    /// Protocol's vToken account PDA (receives LP haircut)
+    /// LP's vToken account
+    #[account(constraint = lp_vtoken_account.mint == housebox_state.vtoken_mint)]
+    pub lp_vtoken_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_id: [u8; 32])]
+pub struct GetSettlement<'info> {
     #[account(
-        init,
-        payer = authority,
-        token::mint = vtoken_mint,
-        token::authority = housebox_state,
-        seeds = [b"protocol_vtoken"],
+        seeds = [b"settled", session_id.as_ref()],
         bump
     )]
-    pub protocol_vtoken_account: Box<Account<'info, TokenAccount>>,
+    pub settled_session: Account<'info, SettledSession>,
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+#[derive(Accounts)]
+pub struct CheckSettlement<'info> {
+    #[account(
+        seeds = [b"housebox_state"],
+        bump
+    )]
+    pub housebox_state: Account<'info, HouseboxState>,
+
+    /// Player whose settlement is being checked
+    /// CHECK: We just need the pubkey for escrow lookup
+    pub player: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"escrow", player.key().as_ref()],
+        bump = player_escrow.bump
+    )]
+    pub player_escrow: Account<'info, PlayerEscrow>,
 }
 
 #[derive(Accounts)]
-pub struct LpLock<'info> {
+pub struct CancelRedemption<'info> {
+    /// LP who made the request — receives rent refund
     #[account(mut)]
     pub lp: Signer<'info>,
 
-    #[account(
-        mut,
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+    /// Redemption request PDA (will be closed, rent returned to LP)
+    #[account(
+        mut,
+        close = lp,
+        seeds = [b"redemption", lp.key().as_ref()],
+        bump = redemption_request.bump
+    )]
+    pub redemption_request: Account<'info, RedemptionRequest>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredRedemption<'info> {
+    /// Anyone can call (permissionless cleanup)
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// LP who made the request — receives rent refund
+    /// CHECK: Verified by redemption_request.lp field; only receives rent
+    #[account(
+        mut,
+        constraint = lp.key() == redemption_request.lp @ HouseboxError::Unauthorized
+    )]
+    pub lp: AccountInfo<'info>,
+
+    /// Redemption request PDA (will be closed, rent returned to LP)
+    #[account(
+        mut,
+        close = lp,
+        seeds = [b"redemption", redemption_request.lp.as_ref()],
+        bump = redemption_request.bump
+    )]
+    pub redemption_request: Account<'info, RedemptionRequest>,
+}
+
+// ============================================
+// STATE
+// ============================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct HouseboxState {
+    /// Program upgrade authority
+    pub authority: Pubkey,
+    /// Server's signing key for settlements
+    pub server_pubkey: Pubkey,
+    /// Who can pause/unpause
+    pub pause_authority: Pubkey,
+    /// Bump for sol_vault PDA
+    pub sol_vault_bump: u8,
+    /// vToken mint (LP share token)
+    pub vtoken_mint: Pubkey,
+    /// LP's share of vTokens (e.g., 80 = 80%)
+    pub lp_percent: u8,
+    /// Emergency pause flag
+    pub paused: bool,
+    /// Total SOL (lamports) in LP pool (redeemable by vToken holders)
+    pub solsum: u64,
+    /// Total vTokens outstanding (redemption denominator)
+    pub vsum: u64,
+    /// Protocol's vToken account (receives haircut)
+    pub protocol_vtoken_account: Pubkey,
+    /// Minimum lamports per lp_lock (0 = no minimum)
+    pub min_lp_lock_lamports: u64,
+    /// Lamport value of locked LSTs at their last mark (included in solsum, not in the SOL vault)
+    pub lst_sol_value: u64,
+    /// Exchange rate (scaled 1e9) at the last vToken interest rate sync
+    pub rate_sync_exchange_rate: u64,
+    /// Unix timestamp of the last vToken interest rate sync (0 = never)
+    pub rate_synced_at: i64,
+    /// Lamport fee to extend a redemption claim window (0 = free)
+    pub redemption_extension_fee_lamports: u64,
+    /// Permissioned LP mode: locks require an LpWhitelist PDA
+    pub lp_whitelist_enabled: bool,
+    /// Total vTokens held in the staking vault
+    pub staked_vtokens: u64,
+    /// Cumulative staking reward lamports per staked vToken (scaled by STAKE_REWARD_SCALE)
+    pub stake_reward_per_token: u128,
+    /// Lamports in the vault owed to stakers (outside solsum)
+    pub stake_rewards_reserved: u64,
+    /// Protocol haircut vesting period in seconds (0 = mint to protocol instantly)
+    pub protocol_vesting_period_seconds: i64,
+    /// Protocol vTokens held in the vesting vault (locked + unlocked)
+    pub protocol_vesting_balance: u64,
+    /// Vested protocol vTokens not yet claimed
+    pub protocol_vesting_unlocked: u64,
+    /// Last vesting checkpoint timestamp
+    pub protocol_vesting_checkpoint: i64,
+    /// Timestamp at which the current locked balance is fully vested
+    pub protocol_vesting_end: i64,
+    /// Referrer's share of the protocol haircut on referred locks (bps)
+    pub lp_referral_bps: u16,
+    /// Dynamic haircut: LP percent when the pool is empty
+    pub dynamic_lp_percent_max: u8,
+    /// Dynamic haircut: solsum at which the LP share reaches lp_percent (0 = disabled)
+    pub dynamic_tvl_target_lamports: u64,
+    /// Performance fee on pool gains above performance_high_water_rate (bps)
+    pub performance_fee_bps: u16,
+    /// Cap on total outstanding vTokens (0 = uncapped)
+    pub max_vsum: u64,
+    /// Emergency LP exit: redemption requests stay open while paused
+    pub exodus_mode: bool,
+    /// State version (PDA seed suffix for versions after the first)
+    pub version: u8,
+    /// State this pool was migrated to (default = not migrated)
+    pub migrated_to: Pubkey,
+    /// Redemption penalty right after a lock (bps, retained in the pool)
+    pub early_exit_penalty_bps: u16,
+    /// Seconds over which the early-exit penalty decays to zero
+    pub early_exit_decay_seconds: i64,
+    /// Window over which streamed redemption payouts vest (0 = lump sum)
+    pub redemption_stream_seconds: i64,
+    /// Lamports in the vault owed to redemption streams (outside solsum)
+    pub redemption_streams_reserved: u64,
+    /// Largest player win a single settlement may apply (0 = unlimited)
+    pub max_settlement_pnl: u64,
+    /// Seconds after which an open session can be expired at its partial PnL (0 = never)
+    pub session_timeout_seconds: i64,
+    /// Referrer's share of rake from players they referred (bps)
+    pub player_referral_bps: u16,
+    /// Seconds after a settlement during which the player may dispute it (0 = off)
+    pub dispute_window_seconds: i64,
+    /// Key that confirms or reverses disputed settlements
+    pub dispute_authority: Pubkey,
+    /// Additional authorized server keys (default = empty slot)
+    pub server_keys: [Pubkey; MAX_EXTRA_SERVER_KEYS],
+    /// How long a rotated-out server key stays valid (0 = no grace)
+    pub server_key_grace_seconds: i64,
+    /// Server key replaced by the last rotation
+    pub previous_server_pubkey: Pubkey,
+    /// When previous_server_pubkey stops being accepted
+    pub previous_server_key_expires_at: i64,
+    /// Share of player losses taken as rake into the treasury (bps)
+    pub rake_bps: u16,
+    /// Lamports in the vault owed to the protocol treasury and not yet swept
+    /// to the treasury vault (outside solsum)
+    pub treasury_balance: u64,
+    /// Share of rake that feeds the jackpot (bps)
+    pub jackpot_contribution_bps: u16,
+    /// Lamports in the vault held for the jackpot (outside solsum)
+    pub jackpot_balance: u64,
+    /// Share of rake that feeds the insurance fund (bps)
+    pub insurance_contribution_bps: u16,
+    /// Lamports in the vault held as insurance against player wins (outside solsum)
+    pub insurance_balance: u64,
+    /// Solsum below which a settlement auto-pauses the protocol (0 = off)
+    pub solvency_floor_lamports: u64,
+    /// Rolling 24h cap on server-approved withdrawals per escrow (0 = no cap)
+    pub daily_withdrawal_limit_lamports: u64,
+    /// Cap on a single server-approved withdrawal (0 = no cap)
+    pub max_withdrawal_lamports: u64,
+    /// Share of rake paid as yield to opted-in escrow balances (bps)
+    pub escrow_yield_bps: u16,
+    /// Total escrow lamports opted into escrow yield
+    pub escrow_yield_staked: u64,
+    /// Cumulative yield lamports per opted-in lamport (scaled by STAKE_REWARD_SCALE)
+    pub escrow_yield_per_lamport: u128,
+    /// Lamports in the vault owed as escrow yield (outside solsum)
+    pub escrow_yield_reserved: u64,
+    /// Deposits above this require a player attestation (0 = compliance mode off)
+    pub attestation_threshold_lamports: u64,
+    /// Gatekeeper network that must have issued deposit attestations
+    pub attestation_gatekeeper_network: Pubkey,
+    /// Operator fee per losing settlement, taken from the pool's share (0 = off)
+    pub settlement_fee_lamports: u64,
+    /// Settlement fees held in the vault for the operator (outside solsum)
+    pub operator_fee_balance: u64,
+    /// Deposit-match promotion: bonus as bps of the deposit (0 = off)
+    pub deposit_match_bps: u16,
+    /// Remaining deposit-match budget in lamports
+    pub deposit_match_budget_lamports: u64,
+    /// Minimum deposit that qualifies for a match
+    pub deposit_match_min_lamports: u64,
+    /// Times a matched bonus must be wagered before it is withdrawable
+    pub deposit_match_wagering_multiplier: u16,
+    /// Settlements stay open while the protocol is paused
+    pub settle_while_paused: bool,
+    /// Per-game-type exposure accounting
+    pub game_types: [GameTypeBucket; MAX_GAME_TYPES],
+    /// Minimum available escrow balance to open a session (0 = none)
+    pub min_session_escrow_lamports: u64,
+    /// Proposed authority awaiting accept_authority (default = none)
+    pub pending_authority: Pubkey,
+    /// Sensitive admin operations require admin council approval
+    pub council_enabled: bool,
+    /// Delay before scheduled parameter changes apply (0 = changes are direct)
+    pub parameter_timelock_seconds: i64,
+    /// Id of the next scheduled parameter change
+    pub parameter_change_count: u64,
+    /// Key that can pause but not unpause (default = none)
+    pub guardian: Pubkey,
+    /// Tunables in ConfigParams are managed through update_config
+    pub config_enabled: bool,
+    /// Field layout version (STATE_LAYOUT_VERSION; 0 = predates migrate_state)
+    pub layout_version: u16,
+    /// Cold recovery address for an emergency evacuation (default = none)
+    pub evacuation_address: Pubkey,
+    /// When a pending evacuation was requested (0 = none)
+    pub evacuation_requested_at: i64,
+    /// Parameter groups locked by finalize_config (FINALIZE_* bits)
+    pub finalized_params: u32,
+    /// Whether admin actions must be recorded in the AdminLog
+    pub admin_log_enabled: bool,
+    /// Where sweep_treasury sends protocol SOL fees (default = treasury vault)
+    pub fee_recipient: Pubkey,
+    /// Start of a scheduled maintenance pause (0 = none)
+    pub maintenance_start: i64,
+    /// When the scheduled maintenance pause lifts on its own
+    pub maintenance_end: i64,
+    /// Wind-down: new LP locks and player deposits are rejected
+    pub sunset_mode: bool,
+    /// How long an unconfirmed guardian pause lasts (0 = DEFAULT_GUARDIAN_PAUSE_SECONDS)
+    pub guardian_pause_seconds: i64,
+    /// When the current guardian pause lapses unless confirmed (0 = not a guardian pause)
+    pub guardian_pause_expires_at: i64,
+    /// Remaining pool-funded budget for grant_bonus in lamports
+    pub bonus_budget_lamports: u64,
+    /// Exchange rate (scaled 1e9) above which pool gains are charged the
+    /// performance fee (0 = unset, taken from the current rate)
+    pub performance_high_water_rate: u64,
+    /// Share of settlement rake (bps) routed to vToken stakers
+    pub stake_rake_bps: u16,
+    /// State version a pending pool migration moves to (0 = none)
+    pub pool_migration_version: u8,
+    /// When a pending pool migration was requested (0 = none)
+    pub pool_migration_requested_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct GameTypeBucket {
+    /// Operator-defined game type code (0 = free slot)
+    pub game_type: u16,
+    /// Cap on exposure (0 = no cap)
+    pub max_exposure: u64,
+    /// Pool's net payout on the game, floored at zero (lamports)
+    pub exposure: u64,
+    /// Cumulative house PnL on the game (positive = house won)
+    pub house_pnl: i64,
+    /// Cumulative amount wagered on the game (lamports)
+    pub total_wagered: u64,
+}
+
+impl HouseboxState {
+    /// Reject a change to a parameter group locked by finalize_config.
+    pub fn require_not_finalized(&self, flag: u32) -> Result<()> {
+        require!(self.finalized_params & flag == 0, HouseboxError::ParameterFinalized);
+        Ok(())
+    }
+
+    /// Whether the pause flag is in force: set by the pause authority, or by
+    /// the guardian and not yet lapsed.
+    pub fn pause_in_effect(&self) -> bool {
+        if !self.paused {
+            return false;
+        }
+        self.guardian_pause_expires_at == 0
+            || Clock::get()
+                .map(|clock| clock.unix_timestamp < self.guardian_pause_expires_at)
+                .unwrap_or(true)
+    }
+
+    /// Whether the protocol is paused and the pause does not lapse on its own.
+    pub fn is_pause_confirmed(&self) -> bool {
+        self.paused && self.guardian_pause_expires_at == 0
+    }
+
+    /// Whether the protocol is paused, manually or by a scheduled
+    /// maintenance window that has started and not yet ended.
+    pub fn is_paused(&self) -> bool {
+        if self.pause_in_effect() {
+            return true;
+        }
+        self.maintenance_end > 0
+            && Clock::get()
+                .map(|clock| {
+                    clock.unix_timestamp >= self.maintenance_start
+                        && clock.unix_timestamp < self.maintenance_end
+                })
+                .unwrap_or(false)
+    }
+
+    /// Whether `key` is the primary server key, one of the additional ones,
+    /// or a rotated-out primary still inside its grace period.
+    pub fn is_server_key(&self, key: &Pubkey) -> bool {
+        if *key == self.server_pubkey {
+            return true;
+        }
+        if *key == Pubkey::default() {
+            return false;
+        }
+        if self.server_keys.contains(key) {
+            return true;
+        }
+        *key == self.previous_server_pubkey
+            && Clock::get()
+                .map(|clock| clock.unix_timestamp < self.previous_server_key_expires_at)
+                .unwrap_or(false)
+    }
+
+    /// Whether locks and redemptions must carry the LP's position account.
+    pub fn requires_lp_position(&self) -> bool {
+        self.early_exit_penalty_bps > 0
+    }
+
+    /// Portion of solsum backed by SOL in the vault (excludes LST-backed value).
+    pub fn liquid_solsum(&self) -> u64 {
+        self.solsum.saturating_sub(self.lst_sol_value)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AssetPool {
+    /// SPL mint the pool is denominated in
+    pub asset_mint: Pubkey,
+    /// Pool-owned vault holding the asset (LP pool + player escrow)
+    pub asset_vault: Pubkey,
+    /// Pool vToken mint (LP share token)
+    pub vtoken_mint: Pubkey,
+    /// Pool protocol vToken account (receives haircut)
+    pub protocol_vtoken_account: Pubkey,
+    /// LP's share of vTokens (e.g., 80 = 80%)
+    pub lp_percent: u8,
+    /// Total asset units in the LP pool (redeemable by vToken holders)
+    pub asset_sum: u64,
+    /// Total pool vTokens outstanding (redemption denominator)
+    pub vsum: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PlayerEscrow {
+    /// Player's wallet pubkey
+    pub player: Pubkey,
+    /// Escrowed SOL balance (lamports)
+    pub balance: u64,
+    /// PDA bump
+    pub bump: u8,
+    /// Verified withdrawal address (set on first deposit, checked on withdraw)
+    pub verified_withdrawal_address: Pubkey,
+    /// Next withdrawal voucher nonce (replay protection)
+    pub withdrawal_nonce: u64,
+    /// Balance reserved by open game sessions (sum of their reserved_amount)
+    /// and by yield_balance
+    pub reserved: u64,
+    /// Referrer recorded on first deposit (default = none)
+    pub referrer: Pubkey,
+    /// Player-set maximum deposits per day (0 = no limit)
+    pub daily_deposit_limit: u64,
+    /// Player-set maximum settled losses per day (0 = no limit)
+    pub daily_loss_limit: u64,
+    /// Loosened deposit limit waiting out the cooling-off period
+    pub pending_deposit_limit: u64,
+    /// Loosened loss limit waiting out the cooling-off period
+    pub pending_loss_limit: u64,
+    /// When the pending limits take effect (0 = none pending)
+    pub pending_limits_at: i64,
+    /// Day index (unix time / SECONDS_PER_DAY) the counters below cover
+    pub limit_day: i64,
+    /// Lamports deposited during limit_day
+    pub deposited_today: u64,
+    /// Lamports lost in settlements during limit_day
+    pub lost_today: u64,
+    /// Self-exclusion end (deposits and settlements blocked until then)
+    pub excluded_until: i64,
+    /// Frozen by the authority or server (settlements and withdrawals blocked)
+    pub frozen: bool,
+    /// Next nonce player_settle_nonce accepts (replay protection)
+    pub settlement_nonce: u64,
+    /// Bonus credit inside balance that can be played but not withdrawn
+    pub bonus_balance: u64,
+    /// Wagering volume needed before the bonus becomes withdrawable
+    pub wagering_required: u64,
+    /// Wagering volume recorded toward wagering_required
+    pub wagered: u64,
+    /// Start of the current 24h withdrawal window
+    pub withdrawal_window_start: i64,
+    /// Lamports withdrawn with server approval in the current window
+    pub withdrawn_in_window: u64,
+    /// Lamports opted into escrow yield (set aside from play and withdrawal)
+    pub yield_balance: u64,
+    /// escrow_yield_per_lamport at the last accrual
+    pub yield_per_lamport_paid: u128,
+    /// Accrued escrow yield not yet claimed
+    pub pending_yield: u64,
+    /// Key that can move the verified withdrawal address after a delay
+    /// if the player's key is lost (default = none)
+    pub recovery_key: Pubkey,
+    /// Balance below which debits emit LowEscrowBalance (0 = off)
+    pub low_balance_threshold: u64,
+    /// Pool asset balances held alongside the SOL balance
+    pub asset_balances: [EscrowAssetBalance; MAX_ESCROW_ASSETS],
+    /// Operational account the server may sweep escrow into
+    pub sweep_destination: Pubkey,
+    /// Lamports the server may still sweep (0 = not authorized)
+    pub sweep_allowance: u64,
+    /// When the sweep authorization lapses
+    pub sweep_expires_at: i64,
+    /// Affiliate/campaign code recorded on first deposit (zero = none)
+    pub affiliate: [u8; 16],
+    /// Referrer's share of this player's rake, held in treasury_balance
+    /// until pay_player_referral pays it out
+    pub referral_credit: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct EscrowAssetBalance {
+    /// Asset mint (default = free slot)
+    pub mint: Pubkey,
+    /// Balance in the asset's base units
+    pub balance: u64,
+}
+
+impl PlayerEscrow {
+    /// Balance not reserved by open game sessions.
+    pub fn available(&self) -> u64 {
+        self.balance.saturating_sub(self.reserved)
+    }
 
-    /// SOL vault PDA
-    /// CHECK: This is a PDA that just holds lamports
-    #[account(
-        mut,
-        seeds = [b"sol_vault"],
-        bump
-    )]
-    pub sol_vault: SystemAccount<'info>,
+    /// Available balance excluding bonus credit still under a wagering requirement.
+    pub fn withdrawable(&self) -> u64 {
+        self.available().saturating_sub(self.bonus_balance)
+    }
 
-    #[account(
-        mut,
-        seeds = [b"vtoken_mint"],
-        bump
-    )]
-    pub vtoken_mint: Account<'info, Mint>,
+    /// Whether the player's self-exclusion is still in force.
+    pub fn is_excluded(&self, now: i64) -> bool {
+        now < self.excluded_until
+    }
 
-    /// LP's vToken account
-    #[account(
-        mut,
-        constraint = lp_vtoken_account.owner == lp.key(),
-        constraint = lp_vtoken_account.mint == vtoken_mint.key()
-    )]
-    pub lp_vtoken_account: Account<'info, TokenAccount>,
+    /// Whether nothing is owed through the escrow: no SOL or asset balance,
+    /// reservation, unclaimed yield, or unpaid referral credit.
+    pub fn is_empty(&self) -> bool {
+        self.balance == 0
+            && self.reserved == 0
+            && self.pending_yield == 0
+            && self.referral_credit == 0
+            && self.asset_balances.iter().all(|slot| slot.balance == 0)
+    }
 
-    /// Protocol's vToken account
-    #[account(
-        mut,
-        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
-    )]
-    pub protocol_vtoken_account: Account<'info, TokenAccount>,
+    /// Slot for an asset mint, claiming a free one on first use.
+    pub fn asset_slot_mut(&mut self, mint: &Pubkey) -> Result<&mut EscrowAssetBalance> {
+        let index = self.asset_balances.iter()
+            .position(|slot| slot.mint == *mint)
+            .or_else(|| self.asset_balances.iter().position(|slot| slot.mint == Pubkey::default()))
+            .ok_or(HouseboxError::EscrowAssetSlotsFull)?;
+        let slot = &mut self.asset_balances[index];
+        slot.mint = *mint;
+        Ok(slot)
+    }
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+#[account]
+#[derive(InitSpace)]
+pub struct Role {
+    /// Key holding the role
+    pub holder: Pubkey,
+    /// Delegated duty
+    pub role: RoleKind,
+    /// When it was granted
+    pub granted_at: i64,
+    /// PDA bump
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct RequestRedemption<'info> {
-    #[account(mut)]
-    pub lp: Signer<'info>,
+/// Duties the authority can delegate through Role PDAs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum RoleKind {
+    /// Every delegated instruction below
+    Admin,
+    /// Server keys, sessions, disputes, attestation, pause/unpause
+    Operator,
+    /// Exposure, withdrawal and redemption limits
+    RiskManager,
+    /// Fees, rake, contributions and treasury withdrawals
+    Treasurer,
+}
 
-    #[account(
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+#[account]
+#[derive(InitSpace)]
+pub struct AdminLog {
+    /// Most recent admin actions (ring buffer)
+    pub entries: [AdminLogEntry; ADMIN_LOG_CAPACITY],
+    /// Slot the next entry is written to
+    pub next_index: u16,
+    /// Admin actions recorded since initialization
+    pub total_actions: u64,
+    /// sha256 chain over every entry recorded (zero = none yet)
+    pub head_hash: [u8; 32],
+    /// PDA bump
+    pub bump: u8,
+}
 
-    /// Redemption request PDA (one per LP)
-    #[account(
-        init,
-        payer = lp,
-        space = 8 + RedemptionRequest::INIT_SPACE,
-        seeds = [b"redemption", lp.key().as_ref()],
-        bump
-    )]
-    pub redemption_request: Account<'info, RedemptionRequest>,
+impl AdminLog {
+    /// Append an entry, overwriting the oldest once full, and extend the
+    /// hash chain with it.
+    pub fn record(&mut self, entry: AdminLogEntry) {
+        self.head_hash = hashv(&[
+            &self.head_hash,
+            &entry.instruction,
+            entry.actor.as_ref(),
+            &entry.timestamp.to_le_bytes(),
+        ])
+        .to_bytes();
+        self.entries[self.next_index as usize] = entry;
+        self.next_index = ((self.next_index as usize + 1) % ADMIN_LOG_CAPACITY) as u16;
+        self.total_actions = self.total_actions.saturating_add(1);
+    }
+}
 
-    pub system_program: Program<'info, System>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct AdminLogEntry {
+    /// Anchor discriminator of the instruction (zero = empty slot)
+    pub instruction: [u8; 8],
+    /// Signer of the instruction
+    pub actor: Pubkey,
+    /// When it ran
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct ExecuteRedemption<'info> {
-    /// LP must sign (needed for vToken burn authority)
-    #[account(
-        mut,
-        constraint = lp.key() == redemption_request.lp @ HouseboxError::Unauthorized
-    )]
-    pub lp: Signer<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct AdminCouncil {
+    /// Council member keys (default = empty slot)
+    pub members: [Pubkey; MAX_COUNCIL_MEMBERS],
+    /// Approvals needed to execute a proposal
+    pub threshold: u8,
+    /// Id of the next proposal
+    pub proposal_count: u64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    #[account(
-        mut,
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+impl AdminCouncil {
+    /// Slot of `key` among the members, if it is one.
+    pub fn member_index(&self, key: &Pubkey) -> Option<usize> {
+        if *key == Pubkey::default() {
+            return None;
+        }
+        self.members.iter().position(|member| member == key)
+    }
+}
 
-    /// SOL vault PDA
-    /// CHECK: This is a PDA that just holds lamports
-    #[account(
-        mut,
-        seeds = [b"sol_vault"],
-        bump
-    )]
-    pub sol_vault: SystemAccount<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct CouncilProposal {
+    /// Sequential proposal id
+    pub id: u64,
+    /// Action applied on execution
+    pub action: CouncilAction,
+    /// Member who proposed it
+    pub proposer: Pubkey,
+    /// Bitmask of approving member slots
+    pub approvals: u8,
+    /// Whether the action has been applied
+    pub executed: bool,
+    /// When the proposal was created
+    pub created_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    #[account(
-        mut,
-        seeds = [b"vtoken_mint"],
-        bump
-    )]
-    pub vtoken_mint: Account<'info, Mint>,
+/// Sensitive admin operations gated by the admin council.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub enum CouncilAction {
+    UpdateServerPubkey { server_pubkey: Pubkey },
+    AddServerKey { server_key: Pubkey },
+    RemoveServerKey { server_key: Pubkey },
+    UpdateServerKeyGrace { grace_seconds: i64 },
+    UpdateFeeRecipient { fee_recipient: Pubkey },
+    UpdateLpPercent { lp_percent: u8 },
+    WithdrawTreasury { amount_lamports: u64, destination: Pubkey },
+}
 
-    /// LP's vToken account (to burn from)
-    #[account(
-        mut,
-        constraint = lp_vtoken_account.owner == lp.key(),
-        constraint = lp_vtoken_account.mint == vtoken_mint.key()
-    )]
-    pub lp_vtoken_account: Account<'info, TokenAccount>,
+#[account]
+#[derive(InitSpace)]
+pub struct QueuedParameterChange {
+    /// Sequential change id
+    pub id: u64,
+    /// Change applied on execution
+    pub change: ParameterChange,
+    /// Earliest execution time
+    pub execute_after: i64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    /// Redemption request PDA (will be closed, rent returned to LP)
-    #[account(
-        mut,
-        close = lp,
-        seeds = [b"redemption", redemption_request.lp.as_ref()],
-        bump = redemption_request.bump
-    )]
-    pub redemption_request: Account<'info, RedemptionRequest>,
+/// Parameter updates subject to the parameter timelock.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub enum ParameterChange {
+    ServerPubkey { server_pubkey: Pubkey },
+    AddServerKey { server_key: Pubkey },
+    RemoveServerKey { server_key: Pubkey },
+    ServerKeyGrace { grace_seconds: i64 },
+    FeeRecipient { fee_recipient: Pubkey },
+    LpPercent { lp_percent: u8 },
+    RakeBps { rake_bps: u16 },
+    SettlementFee { fee_lamports: u64 },
+    Timelock { timelock_seconds: i64 },
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+#[account]
+#[derive(InitSpace)]
+pub struct HouseboxConfig {
+    /// Operational tunables
+    pub params: ConfigParams,
+    /// Last update time
+    pub updated_at: i64,
+    /// PDA bump
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct PlayerDeposit<'info> {
-    #[account(mut)]
-    pub player: Signer<'info>,
+/// Operational tunables managed through update_config.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ConfigParams {
+    /// Performance fee on pool gains above the high-water rate (bps)
+    pub performance_fee_bps: u16,
+    /// Cap on outstanding vTokens (0 = none)
+    pub max_vsum: u64,
+    /// Per-session cap on player wins (0 = none)
+    pub max_settlement_pnl: u64,
+    /// Age after which an open session can be expired (0 = never)
+    pub session_timeout_seconds: i64,
+    /// Minimum lp_lock amount (0 = none)
+    pub min_lp_lock_lamports: u64,
+    /// solsum floor that trips the circuit breaker (0 = off)
+    pub solvency_floor_lamports: u64,
+    /// Rolling 24h withdrawal cap per player (0 = none)
+    pub daily_withdrawal_limit_lamports: u64,
+    /// Single-withdrawal cap (0 = none)
+    pub max_withdrawal_lamports: u64,
+    /// Fee for extend_redemption
+    pub redemption_extension_fee_lamports: u64,
+    /// Minimum available escrow to open a session (0 = none)
+    pub min_session_escrow_lamports: u64,
+}
 
-    #[account(
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+impl ConfigParams {
+    /// Current tunables in HouseboxState.
+    pub fn from_state(state: &HouseboxState) -> Self {
+        Self {
+            performance_fee_bps: state.performance_fee_bps,
+            max_vsum: state.max_vsum,
+            max_settlement_pnl: state.max_settlement_pnl,
+            session_timeout_seconds: state.session_timeout_seconds,
+            min_lp_lock_lamports: state.min_lp_lock_lamports,
+            solvency_floor_lamports: state.solvency_floor_lamports,
+            daily_withdrawal_limit_lamports: state.daily_withdrawal_limit_lamports,
+            max_withdrawal_lamports: state.max_withdrawal_lamports,
+            redemption_extension_fee_lamports: state.redemption_extension_fee_lamports,
+            min_session_escrow_lamports: state.min_session_escrow_lamports,
+        }
+    }
 
-    /// SOL vault PDA
-    /// CHECK: This is a PDA that just holds lamports
-    #[account(
-        mut,
-        seeds = [b"sol_vault"],
-        bump
-    )]
-    pub sol_vault: SystemAccount<'info>,
+    /// Check values with the same rules as the individual update
+    /// instructions, including the finalize_config lock they share.
+    pub fn validate(&self, state: &HouseboxState) -> Result<()> {
+        state.require_not_finalized(FINALIZE_CONFIG)?;
+        require!(self.performance_fee_bps as u64 <= BPS_DENOMINATOR, HouseboxError::InvalidBps);
+        require!(self.session_timeout_seconds >= 0, HouseboxError::InvalidSessionTimeout);
 
-    /// Player's escrow PDA (created on first deposit)
-    #[account(
-        init_if_needed,
-        payer = player,
-        space = 8 + PlayerEscrow::INIT_SPACE,
-        seeds = [b"escrow", player.key().as_ref()],
-        bump
-    )]
-    pub player_escrow: Account<'info, PlayerEscrow>,
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
+    /// Copy the tunables into HouseboxState, where instructions read them.
+    pub fn apply(&self, state: &mut HouseboxState) {
+        state.performance_fee_bps = self.performance_fee_bps;
+        state.max_vsum = self.max_vsum;
+        state.max_settlement_pnl = self.max_settlement_pnl;
+        state.session_timeout_seconds = self.session_timeout_seconds;
+        state.min_lp_lock_lamports = self.min_lp_lock_lamports;
+        state.solvency_floor_lamports = self.solvency_floor_lamports;
+        state.daily_withdrawal_limit_lamports = self.daily_withdrawal_limit_lamports;
+        state.max_withdrawal_lamports = self.max_withdrawal_lamports;
+        state.redemption_extension_fee_lamports = self.redemption_extension_fee_lamports;
+        state.min_session_escrow_lamports = self.min_session_escrow_lamports;
+    }
 }
 
-#[derive(Accounts)]
-#[instruction(pnl: i64, session_id: [u8; 32])]
-pub struct PlayerSettle<'info> {
-    /// Server signer (must match housebox_state.server_pubkey)
-    #[account(mut)]
-    pub server_signer: Signer<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowRecovery {
+    /// Player whose escrow is being recovered
+    pub player: Pubkey,
+    /// Verified withdrawal address to set
+    pub new_address: Pubkey,
+    /// When the request was made (delay counts from here)
+    pub requested_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    /// Player being settled (not signer)
-    /// CHECK: We just need the pubkey for escrow lookup
-    pub player: AccountInfo<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawalAddressChange {
+    /// Player who requested the change
+    pub player: Pubkey,
+    /// Requested verified withdrawal address
+    pub new_address: Pubkey,
+    /// When the request was made (delay counts from here)
+    pub requested_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    #[account(
-        mut,
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+#[account]
+#[derive(InitSpace)]
+pub struct UnassistedWithdrawal {
+    /// Player who requested the withdrawal
+    pub player: Pubkey,
+    /// Lamports to withdraw from escrow
+    pub amount: u64,
+    /// When the request was made (delay counts from here)
+    pub requested_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    /// Player's escrow
-    #[account(
-        mut,
-        seeds = [b"escrow", player.key().as_ref()],
-        bump = player_escrow.bump
-    )]
-    pub player_escrow: Account<'info, PlayerEscrow>,
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawalRequest {
+    /// Player who requested the withdrawal
+    pub player: Pubkey,
+    /// Lamports to withdraw to the verified withdrawal address
+    pub amount_lamports: u64,
+    /// Unix timestamp when the request was made
+    pub requested_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    /// Settled session PDA (for replay protection)
-    #[account(
-        init,
-        payer = server_signer,
-        space = 8 + SettledSession::INIT_SPACE,
-        seeds = [b"settled", session_id.as_ref()],
-        bump
-    )]
-    pub settled_session: Account<'info, SettledSession>,
+#[account]
+#[derive(InitSpace)]
+pub struct CoinFlipBet {
+    /// Player who placed the bet
+    pub player: Pubkey,
+    /// Wager reserved from the escrow (lamports)
+    pub wager: u64,
+    /// sha256 of the player's secret
+    pub commitment: [u8; 32],
+    /// Player's call
+    pub heads: bool,
+    /// Slot the bet was placed in (the flip uses the next slot's hash)
+    pub placed_slot: u64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    pub system_program: Program<'info, System>,
+#[account]
+#[derive(InitSpace)]
+pub struct GameSession {
+    /// Unique session identifier
+    pub session_id: [u8; 32],
+    /// Player who opened the session
+    pub player: Pubkey,
+    /// Escrow lamports reserved for this session
+    pub reserved_amount: u64,
+    /// When the session was opened
+    pub opened_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// PnL applied so far by partial settlements
+    pub cumulative_pnl: i64,
+    /// Last partial settlement (0 = none)
+    pub last_settled_at: i64,
+    /// sha256 of the server's outcome seed (zero = no commitment)
+    pub seed_commitment: [u8; 32],
+    /// Seed preimage, once revealed
+    pub revealed_seed: [u8; 32],
+    /// Whether revealed_seed has been checked against seed_commitment
+    pub seed_revealed: bool,
+    /// Where the partial settlements' PnL went so far
+    pub split: SettlementSplit,
 }
 
-#[derive(Accounts)]
-pub struct PlayerWithdraw<'info> {
-    /// Server signer (must match housebox_state.server_pubkey)
-    /// Required to authorize withdrawals - players cannot withdraw directly
-    #[account(mut)]
-    pub server_signer: Signer<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct SessionRandomness {
+    /// Session the randomness belongs to
+    pub session_id: [u8; 32],
+    /// Switchboard randomness account bound to the session
+    pub randomness_account: Pubkey,
+    /// Commitment slot of the randomness account when it was bound
+    pub seed_slot: u64,
+    /// Revealed randomness value
+    pub value: [u8; 32],
+    /// Whether value has been consumed from the revealed account
+    pub consumed: bool,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    /// Player whose escrow is being withdrawn from (not a signer)
-    /// CHECK: We just need the pubkey for escrow lookup and destination validation
-    #[account(mut)]
-    pub player: AccountInfo<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct SettledSession {
+    /// Unique session identifier
+    pub session_id: [u8; 32],
+    /// Player who was settled
+    pub player: Pubkey,
+    /// When settlement occurred
+    pub settled_at: i64,
+    /// Settled PnL (positive = player won)
+    pub pnl: i64,
+    /// Asset pool mint for asset settlements (default = SOL)
+    pub asset_mint: Pubkey,
+    /// Player filed a dispute within the dispute window
+    pub disputed: bool,
+    /// Dispute authority confirmed or reversed the settlement
+    pub dispute_resolved: bool,
+    /// Operator-defined game type code (0 = unspecified)
+    pub game_type: u16,
+    /// Number of wagers in the session
+    pub wager_count: u32,
+    /// Total amount wagered in the session (lamports)
+    pub total_wagered: u64,
+    /// Server's seed commitment for the session (zero = none)
+    pub seed_commitment: [u8; 32],
+    /// Revealed seed matching seed_commitment
+    pub revealed_seed: [u8; 32],
+    /// When a proposed clawback may execute (0 = none pending)
+    pub clawback_at: i64,
+    /// Player disputed the pending clawback
+    pub clawback_disputed: bool,
+    /// PnL has been reversed by a dispute or clawback
+    pub reversed: bool,
+    /// Player's escrow balance right after the settlement (0 for rounds)
+    pub escrow_balance: u64,
+    /// Where the PnL went, so a reversal can unwind each bucket (zero for rounds)
+    pub split: SettlementSplit,
+}
 
-    #[account(
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+/// How apply_settlement_pnl moved a settlement's lamports between the escrow
+/// and the house-side balances. Losses feed the pool and rake buckets; wins
+/// draw on from_insurance and from_pool.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct SettlementSplit {
+    /// Loss credited to the LP pool
+    pub to_pool: u64,
+    /// Rake credited to treasury_balance (including referral credit)
+    pub treasury: u64,
+    /// Operator settlement fee
+    pub operator_fee: u64,
+    /// Jackpot contribution
+    pub jackpot: u64,
+    /// Insurance fund contribution
+    pub insurance: u64,
+    /// Rake distributed as escrow yield
+    pub escrow_yield: u64,
+    /// Referral credit accrued to the escrow out of the treasury cut
+    pub referral: u64,
+    /// Win paid by the insurance fund
+    pub from_insurance: u64,
+    /// Win paid by the LP pool
+    pub from_pool: u64,
+    /// Rake distributed to vToken stakers
+    pub staking: u64,
+}
 
-    /// SOL vault PDA
-    /// CHECK: This is a PDA that just holds lamports
-    #[account(
-        mut,
-        seeds = [b"sol_vault"],
-        bump
-    )]
-    pub sol_vault: SystemAccount<'info>,
+impl SettlementSplit {
+    /// Sum of two splits, for sessions settled in parts.
+    pub fn combine(&self, other: &SettlementSplit) -> Result<SettlementSplit> {
+        let add = |a: u64, b: u64| a.checked_add(b).ok_or(HouseboxError::MathOverflow);
+        Ok(SettlementSplit {
+            to_pool: add(self.to_pool, other.to_pool)?,
+            treasury: add(self.treasury, other.treasury)?,
+            operator_fee: add(self.operator_fee, other.operator_fee)?,
+            jackpot: add(self.jackpot, other.jackpot)?,
+            insurance: add(self.insurance, other.insurance)?,
+            escrow_yield: add(self.escrow_yield, other.escrow_yield)?,
+            referral: add(self.referral, other.referral)?,
+            from_insurance: add(self.from_insurance, other.from_insurance)?,
+            from_pool: add(self.from_pool, other.from_pool)?,
+            staking: add(self.staking, other.staking)?,
+        })
+    }
+}
 
-    /// Player's escrow
-    #[account(
-        mut,
-        seeds = [b"escrow", player.key().as_ref()],
-        bump = player_escrow.bump,
-        constraint = player_escrow.player == player.key()
-    )]
-    pub player_escrow: Account<'info, PlayerEscrow>,
+#[account]
+#[derive(InitSpace)]
+pub struct SettlementRoot {
+    /// Merkle root of settlement leaves (see settlement_leaf)
+    pub root: [u8; 32],
+    /// When the server posted the root
+    pub posted_at: i64,
+    /// Settlements applied from this root so far
+    pub claimed_count: u64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    pub system_program: Program<'info, System>,
+#[account]
+#[derive(InitSpace)]
+pub struct RateTwap {
+    /// Sum of exchange rate (scaled 1e9) x seconds since initialization
+    pub cumulative_rate: u128,
+    /// Exchange rate recorded at the last update (held until the next one)
+    pub last_rate: u64,
+    /// Timestamp of the last update
+    pub last_updated_at: i64,
+    /// Ring buffer index of the newest observation
+    pub observation_index: u8,
+    /// Recent (timestamp, cumulative_rate) observations. TWAP between two
+    /// observations = delta cumulative_rate / delta timestamp.
+    pub observations: [TwapObservation; TWAP_OBSERVATIONS],
+    /// PDA bump
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct AdminAction<'info> {
-    pub authority: Signer<'info>,
+/// Return data of get_lp_share.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LpShare {
+    /// vTokens held in the account
+    pub vtokens: u64,
+    /// Share of vsum in basis points
+    pub share_bps: u64,
+    /// Lamport value at the current solsum/vsum ratio
+    pub sol_value: u64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+/// Return data of get_settlement.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SettlementRecord {
+    /// Player who was settled (default for a round)
+    pub player: Pubkey,
+    /// When settlement occurred
+    pub settled_at: i64,
+    /// Settled PnL (positive = player won)
+    pub pnl: i64,
+    /// Player's escrow balance right after the settlement
+    pub escrow_balance: u64,
+    /// Asset pool mint for asset settlements (default = SOL)
+    pub asset_mint: Pubkey,
+    /// Player filed a dispute
+    pub disputed: bool,
+    /// PnL has been reversed by a dispute or clawback
+    pub reversed: bool,
 }
 
-#[derive(Accounts)]
-#[instruction(session_id: [u8; 32])]
-pub struct CloseSettledSession<'info> {
-    #[account(
-        mut,
-        constraint = server_signer.key() == housebox_state.server_pubkey @ HouseboxError::Unauthorized
-    )]
-    pub server_signer: Signer<'info>,
+/// Before/after value in a ParameterChanged event.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum ParameterValue {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    Pubkey(Pubkey),
+}
 
-    #[account(
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+impl From<bool> for ParameterValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
 
-    #[account(
-        mut,
-        close = server_signer,
-        seeds = [b"settled", session_id.as_ref()],
-        bump
-    )]
-    pub settled_session: Account<'info, SettledSession>,
+impl From<u8> for ParameterValue {
+    fn from(value: u8) -> Self {
+        Self::U64(value.into())
+    }
 }
 
-#[derive(Accounts)]
-pub struct WithdrawProtocolVtokens<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
+impl From<u16> for ParameterValue {
+    fn from(value: u16) -> Self {
+        Self::U64(value.into())
+    }
+}
 
-    #[account(
-        seeds = [b"housebox_state"],
-        bump
-    )]
-    pub housebox_state: Account<'info, HouseboxState>,
+impl From<u32> for ParameterValue {
+    fn from(value: u32) -> Self {
+        Self::U64(value.into())
+    }
+}
 
-    /// Protocol's vToken account (source)
-    #[account(
-        mut,
-        constraint = protocol_vtoken_account.key() == housebox_state.protocol_vtoken_account
-    )]
-    pub protocol_vtoken_account: Account<'info, TokenAccount>,
+impl From<u64> for ParameterValue {
+    fn from(value: u64) -> Self {
+        Self::U64(value)
+    }
+}
 
-    /// Destination vToken account
-    #[account(mut)]
-    pub destination_vtoken_account: Account<'info, TokenAccount>,
+impl From<i64> for ParameterValue {
+    fn from(value: i64) -> Self {
+        Self::I64(value)
+    }
+}
 
-    pub token_program: Program<'info, Token>,
+impl From<Pubkey> for ParameterValue {
+    fn from(value: Pubkey) -> Self {
+        Self::Pubkey(value)
+    }
 }
 
-#[derive(Accounts)]
-pub struct CloseExpiredRedemption<'info> {
-    /// Anyone can call (permissionless cleanup)
-    #[account(mut)]
-    pub caller: Signer<'info>,
+/// Return data of check_settlement.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SettlementCheck {
+    /// The settlement would pass
+    pub ok: bool,
+    /// Anchor error code it would fail with (0 = none)
+    pub error_code: u32,
+}
 
-    /// LP who made the request — receives rent refund
-    /// CHECK: Verified by redemption_request.lp field; only receives rent
-    #[account(
-        mut,
-        constraint = lp.key() == redemption_request.lp @ HouseboxError::Unauthorized
-    )]
-    pub lp: AccountInfo<'info>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct TwapObservation {
+    pub timestamp: i64,
+    pub cumulative_rate: u128,
+}
 
-    /// Redemption request PDA (will be closed, rent returned to LP)
-    #[account(
-        mut,
-        close = lp,
-        seeds = [b"redemption", redemption_request.lp.as_ref()],
-        bump = redemption_request.bump
-    )]
-    pub redemption_request: Account<'info, RedemptionRequest>,
+#[account]
+#[derive(InitSpace)]
+pub struct LstConfig {
+    /// Whitelisted liquid-staking token mint
+    pub lst_mint: Pubkey,
+    /// SPL stake pool used to value the LST in lamports
+    pub stake_pool: Pubkey,
+    /// Program-owned vault holding locked LST
+    pub lst_vault: Pubkey,
+    /// Whether lp_lock_lst accepts this LST
+    pub enabled: bool,
+    /// Total LST (pool token units) locked
+    pub total_locked: u64,
+    /// PDA bump
+    pub bump: u8,
+    /// Lamport value of total_locked at its last mark (part of lst_sol_value)
+    pub sol_value: u64,
 }
 
-// ============================================
-// STATE
-// ============================================
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    /// Staker's wallet
+    pub owner: Pubkey,
+    /// vTokens currently staked
+    pub staked_amount: u64,
+    /// Accumulator value at the last accrual
+    pub reward_per_token_paid: u128,
+    /// Accrued, unclaimed reward lamports
+    pub pending_rewards: u64,
+    /// PDA bump
+    pub bump: u8,
+}
 
 #[account]
 #[derive(InitSpace)]
-pub struct HouseboxState {
-    /// Program upgrade authority
-    pub authority: Pubkey,
-    /// Server's signing key for settlements
-    pub server_pubkey: Pubkey,
-    /// Who can pause/unpause
-    pub pause_authority: Pubkey,
-    /// Bump for sol_vault PDA
-    pub sol_vault_bump: u8,
-    /// vToken mint (LP share token)
-    pub vtoken_mint: Pubkey,
-    /// LP's share of vTokens (e.g., 80 = 80%)
-    pub lp_percent: u8,
-    /// Emergency pause flag
-    pub paused: bool,
-    /// Total SOL (lamports) in LP pool (redeemable by vToken holders)
-    pub solsum: u64,
-    /// Total vTokens outstanding (redemption denominator)
-    pub vsum: u64,
-    /// Protocol's vToken account (receives haircut)
-    pub protocol_vtoken_account: Pubkey,
+pub struct LpPosition {
+    /// LP wallet
+    pub lp: Pubkey,
+    /// vTokens attributed to this position (minted via lock, less redeemed)
+    pub vtokens: u64,
+    /// Unused since the performance fee moved to the pool-wide high-water
+    /// rate; kept for account layout
+    pub high_water_rate: u64,
+    /// Timestamp of the LP's most recent lock (early-exit penalty clock)
+    pub last_locked_at: i64,
+    /// PDA bump
+    pub bump: u8,
 }
 
 #[account]
 #[derive(InitSpace)]
-pub struct PlayerEscrow {
-    /// Player's wallet pubkey
-    pub player: Pubkey,
-    /// Escrowed SOL balance (lamports)
+pub struct RedemptionStream {
+    /// LP wallet
+    pub lp: Pubkey,
+    /// Streamed lamports not yet claimed (locked + unlocked)
     pub balance: u64,
+    /// Vested lamports not yet claimed
+    pub unlocked: u64,
+    /// Last vesting checkpoint timestamp
+    pub checkpoint: i64,
+    /// Timestamp at which the current locked balance is fully vested
+    pub end: i64,
     /// PDA bump
     pub bump: u8,
-    /// Verified withdrawal address (set on first deposit, checked on withdraw)
-    pub verified_withdrawal_address: Pubkey,
 }
 
 #[account]
 #[derive(InitSpace)]
-pub struct SettledSession {
-    /// Unique session identifier
-    pub session_id: [u8; 32],
-    /// Player who was settled
-    pub player: Pubkey,
-    /// When settlement occurred
-    pub settled_at: i64,
+pub struct LpReferrer {
+    /// Referrer wallet
+    pub referrer: Pubkey,
+    /// Number of referred locks
+    pub referral_count: u64,
+    /// Cumulative lamports locked by referred LPs
+    pub total_referred_lamports: u64,
+    /// Cumulative vTokens minted to the referrer
+    pub total_referral_vtokens: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LpWhitelist {
+    /// Approved LP wallet
+    pub lp: Pubkey,
+    /// When the LP was approved
+    pub added_at: i64,
+    /// PDA bump
+    pub bump: u8,
 }
 
 #[account]
@@ -979,12 +14115,223 @@ pub struct SettledSession {
 pub struct RedemptionRequest {
     /// LP who requested redemption
     pub lp: Pubkey,
-    /// vToken amount to burn at execution time
+    /// vToken amount to burn at execution time (0 when redeem_all)
     pub vtoken_amount: u64,
     /// Unix timestamp when request was made
     pub requested_at: i64,
     /// PDA bump
     pub bump: u8,
+    /// Whether the claim window has been extended (allowed once)
+    pub extended: bool,
+    /// Earliest execution time (>= requested_at + REDEMPTION_DELAY_SECONDS)
+    pub execute_after: i64,
+    /// Redeem the LP's entire vToken balance at execution (vtoken_amount unused)
+    pub redeem_all: bool,
+}
+
+impl RedemptionRequest {
+    /// Earliest execution time.
+    pub fn ready_at(&self) -> i64 {
+        self.execute_after
+    }
+
+    /// Last execution time (inclusive), including any extension.
+    pub fn expires_at(&self) -> i64 {
+        let extension = if self.extended { REDEMPTION_EXPIRY_SECONDS } else { 0 };
+        self.ready_at().saturating_add(REDEMPTION_EXPIRY_SECONDS + extension)
+    }
+}
+
+// ============================================
+// EVENTS
+// ============================================
+
+#[event]
+pub struct LowEscrowBalance {
+    pub player: Pubkey,
+    pub balance: u64,
+    pub threshold: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClawbackProposed {
+    pub session_id: [u8; 32],
+    pub player: Pubkey,
+    pub amount: u64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct DepositMemo {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub memo: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlayerSettled {
+    pub player: Pubkey,
+    pub session_id: [u8; 32],
+    pub pnl: i64,
+    pub escrow_balance: u64,
+    pub affiliate: [u8; 16],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlayerWithdrew {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub affiliate: [u8; 16],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub actor: Pubkey,
+    pub holder: Pubkey,
+    pub role: RoleKind,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub actor: Pubkey,
+    pub holder: Pubkey,
+    pub role: RoleKind,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaintenanceScheduled {
+    pub actor: Pubkey,
+    pub start: i64,
+    pub end: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UpgradeAuthorityVerified {
+    pub authority: Pubkey,
+    pub upgrade_authority: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LamportsRescued {
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensRescued {
+    pub source: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseAuthorityUpdated {
+    pub old_pause_authority: Pubkey,
+    pub new_pause_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub actor: Pubkey,
+    pub old: ConfigParams,
+    pub new: ConfigParams,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasurySwept {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub actor: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EvacuationRequested {
+    pub evacuation_address: Pubkey,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultEvacuated {
+    pub evacuation_address: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolMigrationRequested {
+    pub new_version: u8,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolMigrationExecuted {
+    pub new_state: Pubkey,
+    pub new_version: u8,
+    pub lamports: u64,
+    pub solsum: u64,
+    pub vsum: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParameterChanged {
+    pub actor: Pubkey,
+    pub field: String,
+    pub old_value: ParameterValue,
+    pub new_value: ParameterValue,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowFrozen {
+    pub player: Pubkey,
+    pub frozen_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowUnfrozen {
+    pub player: Pubkey,
+    pub unfrozen_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SolvencyBreakerTripped {
+    pub solsum: u64,
+    pub floor: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JackpotAwarded {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
 }
 
 // ============================================
@@ -1025,4 +14372,222 @@ pub enum HouseboxError {
     RedemptionExpired,
     #[msg("Redemption has not expired yet")]
     RedemptionNotExpired,
+    #[msg("Lock amount below configured minimum")]
+    LockBelowMinimum,
+    #[msg("LST is not whitelisted")]
+    LstNotWhitelisted,
+    #[msg("Invalid stake pool oracle account")]
+    InvalidStakePool,
+    #[msg("Oracle rate is stale")]
+    StaleOracle,
+    #[msg("Insufficient liquid SOL in pool")]
+    InsufficientLiquidity,
+    #[msg("Metadata name, symbol, or URI too long")]
+    MetadataTooLong,
+    #[msg("vToken mint is not an interest-bearing Token-2022 mint")]
+    NotInterestBearing,
+    #[msg("vToken rate synced too recently")]
+    RateSyncTooSoon,
+    #[msg("vTokens out below minimum (slippage)")]
+    SlippageExceeded,
+    #[msg("Redemption has already been extended")]
+    RedemptionAlreadyExtended,
+    #[msg("LP is not whitelisted")]
+    LpNotWhitelisted,
+    #[msg("Scheduled execution is earlier than the minimum redemption delay")]
+    ExecuteAfterTooSoon,
+    #[msg("Invalid batch accounts")]
+    InvalidBatchAccounts,
+    #[msg("Round escrow deltas do not offset the house balance change")]
+    RoundNotConserved,
+    #[msg("Revealed secret does not match the bet commitment")]
+    InvalidReveal,
+    #[msg("Bet cannot be resolved this way yet, or its reveal window has passed")]
+    BetNotResolvable,
+    #[msg("Session seed already committed or revealed")]
+    SeedAlreadyCommitted,
+    #[msg("Session seed must be revealed before settlement")]
+    SeedNotRevealed,
+    #[msg("Settlement cannot be clawed back")]
+    ClawbackNotAllowed,
+    #[msg("A clawback is pending for this settlement")]
+    ClawbackPending,
+    #[msg("No clawback pending for this settlement")]
+    ClawbackNotPending,
+    #[msg("Clawback timelock has not elapsed")]
+    ClawbackTimelocked,
+    #[msg("Settlement has expired")]
+    SettlementExpired,
+    #[msg("Invalid Switchboard randomness account")]
+    InvalidRandomnessAccount,
+    #[msg("Randomness is stale, not yet revealed, or already consumed")]
+    RandomnessNotReady,
+    #[msg("Insufficient staked vTokens")]
+    InsufficientStake,
+    #[msg("No vTokens staked")]
+    NoStakers,
+    #[msg("Protocol vesting vault account required")]
+    ProtocolVestingVaultMissing,
+    #[msg("Invalid vesting period")]
+    InvalidVestingPeriod,
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    #[msg("Invalid basis points (must be <= 10000)")]
+    InvalidBps,
+    #[msg("LP position account required while an early-exit penalty is configured")]
+    LpPositionRequired,
+    #[msg("Lock would exceed the vToken supply cap")]
+    VsumCapExceeded,
+    #[msg("TWAP updated too recently")]
+    TwapUpdateTooSoon,
+    #[msg("Pool must be paused, with no unconfirmed guardian pause, to migrate")]
+    MigrationRequiresPause,
+    #[msg("Pool has been migrated")]
+    PoolMigrated,
+    #[msg("New state version must be greater than the current one")]
+    InvalidStateVersion,
+    #[msg("Unwind staked, vesting, LST-backed, and streaming balances before migrating")]
+    MigrationBlocked,
+    #[msg("Early-exit penalty decay period must be positive")]
+    InvalidPenaltyDecay,
+    #[msg("Invalid settlement Merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Missing or malformed Ed25519 voucher instruction")]
+    InvalidVoucher,
+    #[msg("Invalid recovery key")]
+    InvalidRecoveryKey,
+    #[msg("Deposit above the attestation threshold requires an attestation")]
+    AttestationRequired,
+    #[msg("Attestation is invalid, inactive, expired, or not issued to the player")]
+    InvalidAttestation,
+    #[msg("Invalid player signature")]
+    InvalidPlayerSignature,
+    #[msg("Session loss exceeds its escrow reservation")]
+    LossExceedsReservation,
+    #[msg("Settlement PnL exceeds the per-session limit")]
+    SettlementExceedsLimit,
+    #[msg("Session has not timed out")]
+    SessionNotExpired,
+    #[msg("Invalid session timeout")]
+    InvalidSessionTimeout,
+    #[msg("Unassisted withdrawal delay not yet elapsed")]
+    UnassistedWithdrawalNotReady,
+    #[msg("Invalid withdrawal address")]
+    InvalidWithdrawalAddress,
+    #[msg("Withdrawal address change delay not yet elapsed")]
+    AddressChangeNotReady,
+    #[msg("Escrow still has a balance or open sessions")]
+    EscrowNotEmpty,
+    #[msg("Deposit exceeds the player's daily deposit limit")]
+    DailyDepositLimitExceeded,
+    #[msg("Withdrawal exceeds the 24h withdrawal limit")]
+    DailyWithdrawalLimitExceeded,
+    #[msg("Withdrawal exceeds the maximum single withdrawal")]
+    WithdrawalExceedsMax,
+    #[msg("Loss exceeds the player's daily loss limit")]
+    DailyLossLimitExceeded,
+    #[msg("Player is self-excluded")]
+    PlayerSelfExcluded,
+    #[msg("Exclusion duration must be positive")]
+    InvalidExclusionDuration,
+    #[msg("Player escrow is frozen")]
+    EscrowFrozen,
+    #[msg("Settlement is under dispute")]
+    SettlementDisputed,
+    #[msg("Settlement is not under an open dispute")]
+    SettlementNotDisputed,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Asset settlements cannot be disputed")]
+    DisputeNotAllowed,
+    #[msg("Dispute window must be non-negative")]
+    InvalidDisputeWindow,
+    #[msg("Settlement nonce does not match the escrow")]
+    InvalidSettlementNonce,
+    #[msg("Server key is already authorized, unknown, or empty")]
+    InvalidServerKey,
+    #[msg("All server key slots are in use")]
+    ServerKeyListFull,
+    #[msg("Server key grace period must be non-negative")]
+    InvalidServerKeyGrace,
+    #[msg("Insufficient treasury balance")]
+    InsufficientTreasury,
+    #[msg("All escrow asset slots are in use")]
+    EscrowAssetSlotsFull,
+    #[msg("Sweep authorization must expire in the future")]
+    InvalidSweepAuthorization,
+    #[msg("Sweep exceeds the player's authorization or it has expired")]
+    SweepNotAuthorized,
+    #[msg("Invalid or untracked game type")]
+    InvalidGameType,
+    #[msg("All game type slots are in use")]
+    GameTypeListFull,
+    #[msg("Settlement exceeds the game type's max exposure")]
+    GameExposureExceeded,
+    #[msg("Escrow balance is below the minimum to open a session")]
+    EscrowBelowSessionMinimum,
+    #[msg("Invalid council members or threshold")]
+    InvalidCouncil,
+    #[msg("Signer is not a council member")]
+    NotCouncilMember,
+    #[msg("This operation requires an admin council proposal")]
+    CouncilRequired,
+    #[msg("Council proposal has not reached its threshold")]
+    ProposalNotApproved,
+    #[msg("Council proposal already executed")]
+    ProposalAlreadyExecuted,
+    #[msg("This change must be scheduled through the parameter timelock")]
+    TimelockRequired,
+    #[msg("Parameter change timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Timelock must be non-negative")]
+    InvalidTimelock,
+    #[msg("This parameter is managed through update_config")]
+    ConfigManaged,
+    #[msg("An evacuation is pending")]
+    EvacuationPending,
+    #[msg("Pool must be paused, with no unconfirmed guardian pause, to evacuate")]
+    EvacuationRequiresPause,
+    #[msg("No evacuation is pending")]
+    EvacuationNotPending,
+    #[msg("Invalid finalize flags")]
+    InvalidFinalizeFlags,
+    #[msg("Parameter has been permanently finalized")]
+    ParameterFinalized,
+    #[msg("Admin action log account is required")]
+    AdminLogRequired,
+    #[msg("Fee recipient account does not match the configured fee_recipient")]
+    InvalidFeeRecipient,
+    #[msg("New protocol vToken account must differ from the current one")]
+    InvalidProtocolVtokenAccount,
+    #[msg("Maintenance window must end in the future, after its start, within 7 days")]
+    InvalidMaintenanceWindow,
+    #[msg("Protocol is winding down: new locks and deposits are disabled")]
+    ProtocolSunset,
+    #[msg("No guardian pause is pending confirmation")]
+    GuardianPauseNotPending,
+    #[msg("Guardian pause duration must be positive")]
+    InvalidGuardianPause,
+    #[msg("Program upgrade authority does not match the protocol authority")]
+    UpgradeAuthorityMismatch,
+    #[msg("Rescue source must be an unaccounted ATA of housebox_state or vtoken_mint")]
+    InvalidRescueSource,
+    #[msg("Bonus exceeds the remaining bonus budget")]
+    BonusBudgetExceeded,
+    #[msg("Account is not a migratable program account in an older layout")]
+    InvalidMigrationAccount,
+    #[msg("Redemption streaming is on: the LP's redemption stream must be passed")]
+    RedemptionStreamRequired,
+    #[msg("Not enough LST locked")]
+    InsufficientLst,
+    #[msg("Stake pool paid out less than the minimum lamports")]
+    LstUnwindSlippage,
+    #[msg("Scheduled execution is later than the maximum redemption schedule")]
+    ExecuteAfterTooLate,
+    #[msg("A pool migration is pending")]
+    PoolMigrationPending,
+    #[msg("No pool migration is pending")]
+    PoolMigrationNotPending,
+    #[msg("Withdrawal voucher has expired")]
+    VoucherExpired,
 }